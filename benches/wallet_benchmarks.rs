@@ -269,12 +269,54 @@ fn bench_compare_with_file_store(c: &mut Criterion) {
     group.finish();
 }
 
+// cold open followed by an existence check, answered from the data flag vs the full changeset
+fn bench_existence_check(c: &mut Criterion) {
+    let mut group = c.benchmark_group("existence_check");
+    group.measurement_time(std::time::Duration::from_secs(10));
+
+    for tx_count in [10, 100, 1000].iter() {
+        // one populated database per size, reopened on every iteration
+        let temp_dir = TempDir::new().unwrap();
+        let db_path = temp_dir.path().join(format!("wallet_{}.redb", tx_count));
+        {
+            let (_, changeset) = create_test_wallet(*tx_count);
+            let mut store = RedbStore::create(&db_path).unwrap();
+            RedbStore::persist(&mut store, &changeset).unwrap();
+        }
+
+        group.bench_with_input(
+            BenchmarkId::new("has_wallet", tx_count),
+            &db_path,
+            |b, db_path| {
+                b.iter(|| {
+                    let store = RedbStore::open(db_path).unwrap();
+                    assert!(store.has_wallet().unwrap());
+                });
+            },
+        );
+
+        group.bench_with_input(
+            BenchmarkId::new("load_changeset", tx_count),
+            &db_path,
+            |b, db_path| {
+                b.iter(|| {
+                    let store = RedbStore::open(db_path).unwrap();
+                    assert!(store.load_changeset().unwrap().is_some());
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
 criterion_group!(
     benches,
     bench_wallet_create_and_persist,
     bench_wallet_load,
     bench_address_derivation,
-    bench_incremental_updates
+    bench_incremental_updates,
+    bench_existence_check
 );
 
 #[cfg(feature = "file_store_comparison")]
@@ -119,19 +119,200 @@
 //! }
 //! ```
 //!
-use bdk_chain::Merge;
-use bdk_wallet::{AsyncWalletPersister, ChangeSet, WalletPersister};
-use redb::{Database, ReadableTableMetadata, TableDefinition};
+use bdk_chain::{DescriptorExt, Merge};
+use bdk_wallet::descriptor::{Descriptor, DescriptorPublicKey};
+use bdk_wallet::{AsyncWalletPersister, ChangeSet, KeychainKind, WalletPersister};
+use bitcoin::hashes::{sha256, Hash, HashEngine};
+use bitcoin::Network;
+use redb::{Database, ReadableTable, ReadableTableMetadata, TableDefinition};
+use std::collections::BTreeMap;
 use std::future::Future;
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::pin::Pin;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
 
-/// The table definition for wallet data
-const WALLET_TABLE: TableDefinition<&str, &[u8]> = TableDefinition::new("wallet_data");
+/// Emit a `tracing` event at `$level` if the `tracing` feature is enabled
+///
+/// Expands to nothing otherwise, so instrumentation costs nothing in default builds.
+macro_rules! trace_event {
+    ($level:ident, $($arg:tt)+) => {
+        #[cfg(feature = "tracing")]
+        tracing::$level!($($arg)+);
+    };
+}
+
+/// The default name of the table holding wallet data
+const DEFAULT_TABLE_NAME: &str = "wallet_data";
 
 /// The key used to store the wallet changeset
 const CHANGESET_KEY: &str = "wallet_changeset";
 
+/// The prefix of keys holding metadata rather than the changeset
+const METADATA_PREFIX: &str = "meta::";
+
+/// The metadata key used to store the wallet network
+const NETWORK_KEY: &str = "meta::network";
+
+/// The metadata key recording when the changeset was last written
+const LAST_PERSISTED_KEY: &str = "meta::last_persisted";
+
+/// The metadata key recording when the store was last written to or touched
+const LAST_ACCESSED_KEY: &str = "meta::last_accessed";
+
+/// The metadata key flagging that the default profile holds a changeset
+const HAS_DATA_KEY: &str = "meta::has_data";
+
+/// The metadata key holding the SHA-256 of the default profile's stored changeset
+const CONTENT_HASH_KEY: &str = "meta::content_hash";
+
+/// The metadata key recording the format version of the default profile's changeset
+const CHANGESET_VERSION_KEY: &str = "meta::changeset_version";
+
+/// The changeset format version written by this version of the crate
+///
+/// Bumped whenever the stored JSON changes in a way older versions can't read, e.g. with
+/// a `bdk_wallet` upgrade that changes the `ChangeSet` shape. Version 1 stored the
+/// changeset as a single value; version 2 splits it into segments.
+const CHANGESET_FORMAT_VERSION: u32 = 2;
+
+/// The metadata key recording when the store was created
+const CREATED_AT_KEY: &str = "meta::created_at";
+
+/// The dump format version written by [`RedbStore::dump_json`]
+const DUMP_SCHEMA_VERSION: u32 = 1;
+
+/// The metadata key naming the active changeset profile
+const ACTIVE_PROFILE_KEY: &str = "meta::active_profile";
+
+/// The prefix of keys holding the changesets of named profiles
+const PROFILE_PREFIX: &str = "profile::";
+
+/// The prefix of keys holding changeset checkpoints
+const CHECKPOINT_PREFIX: &str = "checkpoint::";
+
+/// The metadata key holding the id of the default profile's most recent checkpoint
+const LAST_CHECKPOINT_KEY: &str = "meta::last_checkpoint";
+
+/// The metadata key counting the commits that changed the changeset
+const VERSION_KEY: &str = "meta::version";
+
+/// Metadata names used internally that applications may not write
+const RESERVED_METADATA_KEYS: &[&str] = &[
+    "network",
+    "last_persisted",
+    "created_at",
+    "version",
+    "active_profile",
+    "last_accessed",
+    "has_data",
+    "content_hash",
+    "changeset_version",
+    "last_checkpoint",
+];
+
+/// Reserved metadata names describing the stored changeset rather than the wallet, which
+/// [`RedbStore::dump_json`] leaves out
+const DERIVED_METADATA_KEYS: &[&str] = &[
+    "has_data",
+    "content_hash",
+    "changeset_version",
+    "version",
+    "last_checkpoint",
+];
+
+/// The prefix of keys belonging to a namespace, followed by the namespace and `::`
+const NAMESPACE_PREFIX: &str = "ns::";
+
+/// The prefix of the index keys mapping descriptor fingerprints to namespaces
+const FINGERPRINT_PREFIX: &str = "fingerprint::";
+
+/// The prefix of keys holding unfolded changesets in the log table
+#[cfg(feature = "wal")]
+const LOG_PREFIX: &str = "log::";
+
+/// The key an unreadable changeset is moved to by a recoverable store
+const CORRUPT_CHANGESET_KEY: &str = "corrupt::wallet_changeset";
+
+/// The bundle format version written by [`RedbStore::export_bundle`]
+#[cfg(feature = "bundle")]
+const BUNDLE_SCHEMA_VERSION: u32 = 1;
+
+/// The name of a bundle's manifest inside its archive
+#[cfg(feature = "bundle")]
+const BUNDLE_MANIFEST_FILE: &str = "manifest.json";
+
+/// The name of a bundle's changeset inside its archive
+#[cfg(feature = "bundle")]
+const BUNDLE_CHANGESET_FILE: &str = "changeset.json";
+
+/// A flag that clones of a store don't inherit: cloning resets it to `false`
+#[derive(Debug, Default)]
+struct NotInherited(bool);
+
+impl Clone for NotInherited {
+    fn clone(&self) -> Self {
+        Self(false)
+    }
+}
+
+/// A store's database handle, shared with its clones and empty once they no longer have a
+/// database
+///
+/// Read it through [`RedbStore::db`], which reports an empty handle as
+/// [`RedbError::Closed`]. [`RedbStore::compact`] write-locks it, so operations on every
+/// clone wait for the compaction instead of keeping it from running.
+#[derive(Clone)]
+struct DbHandle(Arc<RwLock<Option<Arc<Database>>>>);
+
+impl DbHandle {
+    fn new(db: Arc<Database>) -> Self {
+        Self(Arc::new(RwLock::new(Some(db))))
+    }
+
+    fn read(&self) -> RwLockReadGuard<'_, Option<Arc<Database>>> {
+        // The slot is only ever replaced whole, so a panic can't leave it half-written
+        self.0.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Option<Arc<Database>>> {
+        self.0.write().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Whether a clone of the store, or a handle from outside it, shares the database
+    fn is_shared(&self) -> bool {
+        Arc::strong_count(&self.0) > 1
+            || self
+                .read()
+                .as_ref()
+                .is_some_and(|db| Arc::strong_count(db) > 1)
+    }
+}
+
+/// A read lock on a store's database handle, see [`RedbStore::db`]
+struct DbRef<'a>(RwLockReadGuard<'a, Option<Arc<Database>>>);
+
+impl std::ops::Deref for DbRef<'_> {
+    type Target = Arc<Database>;
+
+    fn deref(&self) -> &Arc<Database> {
+        self.0.as_ref().expect("RedbStore::db checked the handle")
+    }
+}
+
+/// The external and internal descriptors a store's wallet must have
+type ExpectedDescriptors = (
+    Descriptor<DescriptorPublicKey>,
+    Descriptor<DescriptorPublicKey>,
+);
+
+/// How many unreceived [`PersistEvent`]s a subscriber may fall behind by
+#[cfg(feature = "tokio")]
+const PERSIST_EVENT_CAPACITY: usize = 64;
+
+/// Callback invoked with a changeset after it has been committed
+type PersistCallback = Arc<dyn Fn(&ChangeSet) + Send + Sync>;
+
 /// Persists a wallet changeset in a redb database.
 ///
 /// `RedbStore` implements both the `WalletPersister` trait for synchronous operations
@@ -143,6 +324,41 @@ const CHANGESET_KEY: &str = "wallet_changeset";
 /// provides a simple, efficient way to persist wallet state while maintaining ACID
 /// guarantees through redb's transactional model.
 ///
+/// Cloning a `RedbStore` is cheap: clones share the same underlying [`Database`] handle
+/// and settings, so they can be handed to different threads or tasks without an outer
+/// `Arc<Mutex<_>>`. Each persist still runs in its own redb write transaction, and redb
+/// lets only one writer in at a time, so persists from different clones never interleave.
+///
+/// # Thread safety
+///
+/// `RedbStore` is `Send + Sync`. The persister traits take `&mut self`, but
+/// [`RedbStore::initialize_shared`] and [`RedbStore::persist_shared`] only need `&self` and
+/// rely on redb's own locking, so a single store can live in a global. Reads run in redb
+/// read transactions, which never block each other or wait for a writer: an
+/// `initialize_shared` racing a persist sees the last committed state. Only persists are
+/// serialized.
+///
+/// The async persister holds `&mut self` until its write commits, so a store shared
+/// behind an async mutex makes every read queue behind a slow persist. Give readers a
+/// clone instead: its reads open their own read transactions and return right away,
+/// whatever the other clone is writing.
+///
+///
+/// ```rust,no_run
+/// use bdk_wallet::ChangeSet;
+/// use redb_wallet_storage::RedbStore;
+/// use std::sync::OnceLock;
+///
+/// static STORE: OnceLock<RedbStore> = OnceLock::new();
+///
+/// fn store() -> &'static RedbStore {
+///     STORE.get_or_init(|| RedbStore::open_or_create("wallet.redb").unwrap())
+/// }
+///
+/// let changeset = store().initialize_shared().unwrap();
+/// store().persist_shared(&ChangeSet::default()).unwrap();
+/// ```
+///
 /// # Examples
 ///
 /// ```rust,no_run
@@ -163,12 +379,227 @@ const CHANGESET_KEY: &str = "wallet_changeset";
 /// }
 /// ```
 ///
-#[derive(Debug)]
+#[derive(Clone)]
 pub struct RedbStore {
-    db: Database,
+    db: DbHandle,
+    path: Option<PathBuf>,
+    table_name: String,
+    namespace: Option<String>,
+    durability: redb::Durability,
+    recoverable: bool,
+    last_recovery: Option<RecoveryInfo>,
+    max_changeset_bytes: Option<usize>,
+    read_only: bool,
+    expected_descriptors: Option<ExpectedDescriptors>,
+    on_persist: Option<PersistCallback>,
+    read_counter: Option<Arc<ReadCounter>>,
+    /// Whether the database was opened with a caller's `redb::Builder`, whose settings
+    /// can't be reproduced when reopening it
+    custom_config: bool,
+    json_pretty: bool,
+    deserialize_limits: DeserializeLimits,
+    allow_descriptor_change: bool,
+    max_checkpoints: Option<usize>,
+    write_retry: (u32, std::time::Duration),
+    keys: KeyPrefixes,
+    compact_on_drop: NotInherited,
+    cache_size: Option<usize>,
+    created: bool,
+    lock_file: Option<Arc<redb::backends::FileBackend>>,
+    /// A duplicate of the handle a store built from a file was given, for its length
+    file: Option<Arc<std::fs::File>>,
+    flush_policy: Option<FlushPolicy>,
+    unflushed: Arc<std::sync::atomic::AtomicUsize>,
+    /// Runs inside a persist's write transaction right before it commits
+    #[cfg(test)]
+    before_commit: Option<Arc<dyn Fn() + Send + Sync>>,
+    /// Runs while the database is closed for reopening, before the file is locked again
+    #[cfg(test)]
+    before_reopen: Option<Arc<dyn Fn() + Send + Sync>>,
+    #[cfg(feature = "tokio")]
+    events: tokio::sync::broadcast::Sender<PersistEvent>,
+}
+
+/// Compacts the database if [`RedbStore::set_compact_on_drop`] was enabled
+impl Drop for RedbStore {
+    fn drop(&mut self) {
+        // Clones still using the database would have to wait for the compaction
+        if self.compact_on_drop.0
+            && !self.read_only
+            && self.db.read().is_some()
+            && !self.db.is_shared()
+        {
+            // Drop can't report errors; a failed compaction leaves the file as it was
+            let _ = self.compact();
+        }
+    }
+}
+
+/// Prints only fields that are safe to log
+///
+/// The file path is redacted, and neither the database internals nor the expected
+/// descriptors are printed.
+impl std::fmt::Debug for RedbStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedbStore")
+            .field("path", &self.path.as_ref().map(|_| "<redacted>"))
+            .field("table_name", &self.table_name)
+            .field("namespace", &self.namespace)
+            .field("read_only", &self.read_only)
+            .finish_non_exhaustive()
+    }
 }
 
 impl RedbStore {
+    /// Build a store around an initialized database with default settings
+    fn from_parts(db: Database, path: PathBuf) -> Self {
+        Self::from_handle(Arc::new(db), Some(path))
+    }
+
+    /// Build a store around a possibly shared database handle with default settings
+    fn from_handle(db: Arc<Database>, path: Option<PathBuf>) -> Self {
+        Self {
+            db: DbHandle::new(db),
+            path,
+            table_name: DEFAULT_TABLE_NAME.to_string(),
+            namespace: None,
+            durability: redb::Durability::Immediate,
+            recoverable: false,
+            last_recovery: None,
+            max_changeset_bytes: None,
+            read_only: false,
+            expected_descriptors: None,
+            on_persist: None,
+            read_counter: None,
+            custom_config: false,
+            json_pretty: false,
+            deserialize_limits: DeserializeLimits::default(),
+            allow_descriptor_change: false,
+            max_checkpoints: None,
+            write_retry: (0, std::time::Duration::ZERO),
+            keys: KeyPrefixes::default(),
+            compact_on_drop: NotInherited(false),
+            cache_size: None,
+            created: false,
+            lock_file: None,
+            file: None,
+            flush_policy: None,
+            unflushed: Arc::new(std::sync::atomic::AtomicUsize::new(0)),
+            #[cfg(test)]
+            before_commit: None,
+            #[cfg(test)]
+            before_reopen: None,
+            #[cfg(feature = "tokio")]
+            events: tokio::sync::broadcast::channel(PERSIST_EVENT_CAPACITY).0,
+        }
+    }
+
+    /// Open the file at `path` through a [`CountingBackend`]
+    ///
+    /// A read-only store opens the file the way [`redb::Builder::open`] does: it never
+    /// creates the file or initializes an empty one.
+    fn open_counting(
+        path: &Path,
+        config: &redb::Builder,
+        read_only: bool,
+    ) -> Result<Self, RedbError> {
+        // redb writes to the file when opening it, even for reads only
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(!read_only)
+            .truncate(false)
+            .open(path)?;
+        if read_only && file.metadata()?.len() == 0 {
+            let empty = redb::StorageError::Io(std::io::ErrorKind::InvalidData.into());
+            return Err(redb::DatabaseError::Storage(empty).into());
+        }
+
+        let counter = Arc::new(ReadCounter::default());
+        let backend = CountingBackend {
+            inner: redb::backends::FileBackend::new(file)?,
+            counter: Arc::clone(&counter),
+        };
+        let db = config.create_with_backend(backend)?;
+
+        let mut store = Self::from_parts(db, path.to_path_buf());
+        store.read_counter = Some(counter);
+        Ok(store)
+    }
+
+    /// Start configuring a store with a [`RedbStoreBuilder`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::builder()
+    ///     .namespace("savings")
+    ///     .cache_size(16 * 1024 * 1024)
+    ///     .open_or_create("wallet.redb")
+    ///     .unwrap();
+    /// ```
+    ///
+    pub fn builder() -> RedbStoreBuilder {
+        RedbStoreBuilder::default()
+    }
+
+    /// The definition of the table holding this store's wallet data
+    fn wallet_table(&self) -> TableDefinition<'_, &'static str, &'static [u8]> {
+        TableDefinition::new(&self.table_name)
+    }
+
+    /// The key under which `key` is stored for this store's namespace
+    ///
+    /// Stores without a namespace use the bare key, which keeps files written before
+    /// namespaces existed readable.
+    fn scoped_key(&self, key: &str) -> String {
+        let key = self.keys.translate(key);
+        match &self.namespace {
+            Some(namespace) => namespaced_key(namespace, &key),
+            None => key,
+        }
+    }
+
+    /// Initialize the database with the required table
+    ///
+    /// Marks the store as newly created if the table didn't exist yet, for
+    /// [`RedbStore::on_first_create`], and records the creation time of a store whose
+    /// scope holds no wallet yet.
+    fn init_table(&mut self) -> Result<(), RedbError> {
+        self.create_table(true)
+    }
+
+    /// Create the wallet table if it doesn't exist yet
+    ///
+    /// With `record_created`, also writes the creation time for the store's scope unless
+    /// it has one or already holds a wallet. Files written by older versions with a
+    /// wallet but no creation time get one on their next persist instead.
+    fn create_table(&mut self, record_created: bool) -> Result<(), RedbError> {
+        use redb::TableHandle;
+
+        let write_txn = self.db()?.begin_write()?;
+        let existed = write_txn
+            .list_tables()?
+            .any(|table| table.name() == self.table_name);
+        {
+            let mut table = write_txn.open_table(self.wallet_table())?;
+            let created_key = self.scoped_key(CREATED_AT_KEY);
+            let changeset_key = self.changeset_key(&table)?;
+            if record_created
+                && table.get(created_key.as_str())?.is_none()
+                && table.get(changeset_key.as_str())?.is_none()
+            {
+                table.insert(created_key.as_str(), unix_now().to_be_bytes().as_slice())?;
+            }
+        }
+        write_txn.commit()?;
+
+        self.created |= !existed;
+        Ok(())
+    }
+
     /// Create a new [`RedbStore`]; error if the file exists.
     ///
     /// This function creates a new redb database file at the specified path and
@@ -193,16 +624,12 @@ impl RedbStore {
     where
         P: AsRef<Path>,
     {
-        let db = Database::create(file_path)?;
+        let path = file_path.as_ref().to_path_buf();
+        let db = Database::create(&path)?;
 
-        // Initialize the database with the required table
-        let write_txn = db.begin_write()?;
-        {
-            let _table = write_txn.open_table(WALLET_TABLE)?;
-        }
-        write_txn.commit()?;
-
-        Ok(Self { db })
+        let mut store = Self::from_parts(db, path);
+        store.init_table()?;
+        Ok(store)
     }
 
     /// Create a new [`RedbStore`] with custom configuration; error if the file exists.
@@ -236,16 +663,171 @@ impl RedbStore {
     where
         P: AsRef<Path>,
     {
-        let db = config.create(file_path)?;
+        let path = file_path.as_ref().to_path_buf();
+        let db = config.create(&path)?;
+
+        let mut store = Self::from_parts(db, path);
+        store.custom_config = true;
+        store.init_table()?;
+        Ok(store)
+    }
+
+    /// Create a new [`RedbStore`] with a cache sized for the expected wallet size.
+    ///
+    /// Works like [`RedbStore::create`], with redb's cache size picked by
+    /// [`RedbStore::estimate_cache_size`] instead of redb's 1 GiB default. The cache size
+    /// is kept when the store reopens its file, e.g. in [`RedbStore::release_cache`].
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as [`RedbStore::create`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// // A merchant wallet expected to see about 50,000 transactions
+    /// let store = RedbStore::create_sized("wallet.redb", 50_000).unwrap();
+    /// ```
+    ///
+    pub fn create_sized<P>(file_path: P, expected_txs: usize) -> Result<Self, RedbError>
+    where
+        P: AsRef<Path>,
+    {
+        let cache_size = Self::estimate_cache_size(expected_txs);
+        let mut config = redb::Builder::new();
+        config.set_cache_size(cache_size);
+
+        let mut store = Self::create_with_config(file_path, &mut config)?;
+        store.cache_size = Some(cache_size);
+        Ok(store)
+    }
+
+    /// Estimate a redb cache size for a wallet with `expected_txs` transactions
+    ///
+    /// The whole changeset is read and rewritten on every persist, so the cache should
+    /// hold the stored changeset twice over: the pages being read and the ones being
+    /// written. A transaction with its anchors takes roughly 1 KiB of JSON, giving
+    /// `2 KiB * expected_txs`, clamped between 4 MiB, so small wallets still cache
+    /// redb's own pages, and redb's default of 1 GiB.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// assert_eq!(RedbStore::estimate_cache_size(10_000), 20 * 1024 * 1000);
+    /// ```
+    ///
+    pub fn estimate_cache_size(expected_txs: usize) -> usize {
+        const BYTES_PER_TX: usize = 1024;
+        const MIN_CACHE: usize = 4 * 1024 * 1024;
+        const MAX_CACHE: usize = 1024 * 1024 * 1024;
+
+        expected_txs
+            .saturating_mul(2 * BYTES_PER_TX)
+            .clamp(MIN_CACHE, MAX_CACHE)
+    }
+
+    /// Create a new [`RedbStore`] bound to a [`Network`]; error if the file exists.
+    ///
+    /// This works like [`RedbStore::create`] but also records the network in the
+    /// database metadata. Once a network is recorded, persisting a changeset for a
+    /// different network fails with [`RedbError::NetworkMismatch`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The file already exists
+    /// - The database cannot be created due to permission issues or other I/O errors
+    /// - The required table or the network metadata cannot be written
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bitcoin::Network;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::create_for_network("testnet_wallet.redb", Network::Testnet).unwrap();
+    /// assert_eq!(store.stored_network().unwrap(), Some(Network::Testnet));
+    /// ```
+    ///
+    pub fn create_for_network<P>(file_path: P, network: Network) -> Result<Self, RedbError>
+    where
+        P: AsRef<Path>,
+    {
+        let store = Self::create(file_path)?;
 
-        // Initialize the database with the required table
-        let write_txn = db.begin_write()?;
+        let write_txn = store.db()?.begin_write()?;
         {
-            let _table = write_txn.open_table(WALLET_TABLE)?;
+            let mut table = write_txn.open_table(store.wallet_table())?;
+            let network_bytes = serde_json::to_vec(&network).map_err(RedbError::Serialization)?;
+            table.insert(
+                store.scoped_key(NETWORK_KEY).as_str(),
+                network_bytes.as_slice(),
+            )?;
         }
         write_txn.commit()?;
 
-        Ok(Self { db })
+        Ok(store)
+    }
+
+    /// Create a new [`RedbStore`] for a wallet, checking its descriptors first.
+    ///
+    /// Parses `external` and `internal` for `network` the way wallet creation does, and
+    /// only creates the file once both are valid, so a bad descriptor never leaves an
+    /// empty file behind. The store is then bound to `network` like
+    /// [`RedbStore::create_for_network`] and expects these descriptors like
+    /// [`RedbStore::expect_descriptors`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Either descriptor cannot be parsed or is for another network
+    ///   ([`RedbError::InvalidDescriptor`]); no file is created
+    /// - The file already exists
+    /// - The database cannot be created or written
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::{CreateParams, PersistedWallet};
+    /// use bitcoin::Network;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let external = "wpkh(tprv.../84'/1'/0'/0/*)";
+    /// let internal = "wpkh(tprv.../84'/1'/0'/1/*)";
+    /// let mut store =
+    ///     RedbStore::create_validated("wallet.redb", external, internal, Network::Testnet)
+    ///         .unwrap();
+    /// let create_params = CreateParams::new(external, internal).network(Network::Testnet);
+    /// let wallet = PersistedWallet::create(&mut store, create_params).unwrap();
+    /// ```
+    ///
+    pub fn create_validated<P>(
+        file_path: P,
+        external: &str,
+        internal: &str,
+        network: Network,
+    ) -> Result<Self, RedbError>
+    where
+        P: AsRef<Path>,
+    {
+        use bdk_wallet::descriptor::IntoWalletDescriptor;
+
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let parse = |descriptor: &str| {
+            descriptor
+                .into_wallet_descriptor(&secp, network)
+                .map(|(descriptor, _)| descriptor)
+                .map_err(|e| RedbError::InvalidDescriptor(e.to_string()))
+        };
+        let expected = (parse(external)?, parse(internal)?);
+
+        let mut store = Self::create_for_network(file_path, network)?;
+        store.expected_descriptors = Some(expected);
+        Ok(store)
     }
 
     /// Open an existing [`RedbStore`].
@@ -269,8 +851,48 @@ impl RedbStore {
     where
         P: AsRef<Path>,
     {
-        let db = Database::open(file_path)?;
-        Ok(Self { db })
+        let path = file_path.as_ref().to_path_buf();
+        let db = Database::open(&path)?;
+        Ok(Self::from_parts(db, path))
+    }
+
+    /// Open an existing [`RedbStore`], or return `None` if the file doesn't exist.
+    ///
+    /// Like [`RedbStore::open`], but a missing file is not an error, mirroring
+    /// `PersistedWallet::load` returning `None` for a wallet that doesn't exist yet. The
+    /// check is made by the open itself, so there is no race with a file being created
+    /// or removed in between.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The database cannot be opened due to permission issues or other I/O errors
+    /// - The file is not a valid redb database or is corrupted
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// match RedbStore::try_open("wallet.redb").unwrap() {
+    ///     Some(store) => println!("Opened existing wallet store"),
+    ///     None => println!("No wallet store yet"),
+    /// }
+    /// ```
+    ///
+    pub fn try_open<P>(file_path: P) -> Result<Option<Self>, RedbError>
+    where
+        P: AsRef<Path>,
+    {
+        match Self::open(file_path) {
+            Ok(store) => Ok(Some(store)),
+            Err(RedbError::Database(redb::Error::Io(e)) | RedbError::Io(e))
+                if e.kind() == std::io::ErrorKind::NotFound =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
     }
 
     /// Open an existing [`RedbStore`] with custom configuration.
@@ -300,14 +922,66 @@ impl RedbStore {
     where
         P: AsRef<Path>,
     {
-        let db = config.open(file_path)?;
-        Ok(Self { db })
+        let path = file_path.as_ref().to_path_buf();
+        let db = config.open(&path)?;
+        let mut store = Self::from_parts(db, path);
+        store.custom_config = true;
+        Ok(store)
+    }
+
+    /// Open an existing [`RedbStore`], waiting for another holder of the file to let go.
+    ///
+    /// redb locks its file exclusively, so opening a file that another process or handle
+    /// has open fails straight away. This retries such lock failures up to `attempts`
+    /// times in total, sleeping `backoff` after the first failure and doubling the delay
+    /// after each further one. Any other error, such as a missing or corrupt file, is
+    /// returned immediately. The thread is blocked while waiting.
+    ///
+    /// # Errors
+    ///
+    /// Returns the last error if every attempt fails, or the first error that is not
+    /// caused by the file being locked.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    /// use std::time::Duration;
+    ///
+    /// // Waits up to 50 + 100 + 200 + 400 ms
+    /// let store =
+    ///     RedbStore::open_with_retry("wallet.redb", 5, Duration::from_millis(50)).unwrap();
+    /// ```
+    ///
+    pub fn open_with_retry<P>(
+        file_path: P,
+        attempts: u32,
+        backoff: std::time::Duration,
+    ) -> Result<Self, RedbError>
+    where
+        P: AsRef<Path>,
+    {
+        let mut delay = backoff;
+        let mut attempt = 1;
+        loop {
+            match Self::open(file_path.as_ref()) {
+                Err(RedbError::Database(redb::Error::DatabaseAlreadyOpen))
+                    if attempt < attempts =>
+                {
+                    std::thread::sleep(delay);
+                    delay = delay.saturating_mul(2);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
     }
 
     /// Attempt to open an existing [`RedbStore`]; create it if the file does not exist.
     ///
     /// This is a convenience function that tries to open an existing database file,
-    /// and if it doesn't exist, creates a new one instead.
+    /// and if it doesn't exist, creates a new one instead. A zero-byte file, as left
+    /// behind by a crash during creation, is treated like a missing file and initialized.
     ///
     /// # Errors
     ///
@@ -329,399 +1003,12089 @@ impl RedbStore {
     where
         P: AsRef<Path>,
     {
-        if file_path.as_ref().exists() {
-            Self::open(file_path)
-        } else {
-            Self::create(file_path)
-        }
+        Self::open_or_create_repair(file_path).map(|(store, _)| store)
     }
 
-    /// Get statistics about the wallet table
+    /// Open or create a [`RedbStore`], reporting whether an empty leftover file was replaced.
     ///
-    /// Returns statistics about the wallet data table, including the number of entries,
-    /// table size, and other metrics.
+    /// Behaves like [`RedbStore::open_or_create`], but also tells the caller whether the
+    /// path held a zero-byte file that had to be initialized as a new database. A file
+    /// with content that is not a valid redb database is never overwritten; it produces
+    /// an error so that no user data is lost.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok((store, true))` if an empty file was found and initialized
+    /// - `Ok((store, false))` if an existing database was opened or a new file was created
     ///
     /// # Errors
     ///
     /// Returns an error if:
-    /// - The database cannot be read
-    /// - The wallet table cannot be opened
+    /// - The file exists, is not empty and cannot be opened as a redb database
+    /// - The file doesn't exist and cannot be created
+    /// - The file metadata cannot be read
     ///
     /// # Examples
     ///
     /// ```rust,no_run
     /// use redb_wallet_storage::RedbStore;
     ///
-    /// let store = RedbStore::open("wallet.redb").unwrap();
-    /// let stats = store.table_stats().unwrap();
-    ///
-    /// // You can inspect the table statistics
-    /// println!("Table stats: {:?}", stats);
+    /// let (store, repaired) = RedbStore::open_or_create_repair("wallet.redb").unwrap();
+    /// if repaired {
+    ///     println!("Replaced an empty wallet file left by a previous crash");
+    /// }
     /// ```
     ///
-    pub fn table_stats(&self) -> Result<redb::TableStats, RedbError> {
-        let read_txn = self.db.begin_read()?;
-        let table = read_txn.open_table(WALLET_TABLE)?;
-        Ok(table.stats()?)
+    pub fn open_or_create_repair<P>(file_path: P) -> Result<(Self, bool), RedbError>
+    where
+        P: AsRef<Path>,
+    {
+        match std::fs::metadata(file_path.as_ref()) {
+            // redb initializes an empty file the same way as a missing one
+            Ok(metadata) if metadata.len() == 0 => Ok((Self::create(file_path)?, true)),
+            Ok(_) => Ok((Self::open(file_path)?, false)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                Ok((Self::create(file_path)?, false))
+            }
+            Err(e) => Err(e.into()),
+        }
     }
 
-    /// Get the changeset from the database
+    /// Open or create a [`RedbStore`], reporting whether the file was freshly created.
     ///
-    /// Internal method that retrieves the stored wallet changeset from the database.
+    /// The decision is made by the filesystem rather than by a prior existence check:
+    /// the file is created with `create_new`, which fails atomically if it already
+    /// exists, so two processes racing on the same path can't both report a new file.
+    /// A zero-byte file left by an interrupted create is initialized and reported as
+    /// created, like [`RedbStore::open_or_create_repair`] does.
     ///
     /// # Returns
     ///
-    /// - `Ok(Some(changeset))` if a changeset exists in the database
-    /// - `Ok(None)` if no changeset has been stored yet
-    /// - `Err(...)` if an error occurs during database access or deserialization
-    ///
-    fn get_changeset(&self) -> Result<Option<ChangeSet>, RedbError> {
-        let read_txn = self.db.begin_read()?;
-        let table = read_txn.open_table(WALLET_TABLE)?;
-
-        match table.get(CHANGESET_KEY)? {
-            Some(value) => {
-                let changeset_bytes = value.value();
-                let changeset: ChangeSet =
-                    serde_json::from_slice(changeset_bytes).map_err(RedbError::Deserialization)?;
-                Ok(Some(changeset))
-            }
-            None => Ok(None),
-        }
-    }
-
-    /// Store the changeset in the database
+    /// - `Ok((store, true))` if a new database was created
+    /// - `Ok((store, false))` if an existing database was opened
     ///
-    /// Internal method that persists a wallet changeset to the database.
-    /// If the changeset is empty, this method does nothing.
+    /// # Errors
     ///
-    /// # Arguments
+    /// Returns an error if:
+    /// - The file exists, is not empty and cannot be opened as a redb database
+    /// - The file doesn't exist and cannot be created
     ///
-    /// * `changeset` - The wallet changeset to store
+    /// # Examples
     ///
-    /// # Returns
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
     ///
-    /// - `Ok(())` if the changeset was successfully stored or was empty
-    /// - `Err(...)` if an error occurs during serialization or database access
+    /// let (store, created) = RedbStore::open_or_create_reporting("wallet.redb").unwrap();
+    /// if created {
+    ///     println!("new wallet");
+    /// } else {
+    ///     println!("existing wallet");
+    /// }
+    /// ```
     ///
-    fn store_changeset(&self, changeset: &ChangeSet) -> Result<(), RedbError> {
-        // Skip if changeset is empty
-        if changeset.is_empty() {
-            return Ok(());
+    pub fn open_or_create_reporting<P>(file_path: P) -> Result<(Self, bool), RedbError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = file_path.as_ref();
+        match std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(path)
+        {
+            Ok(file) => {
+                let db = redb::Builder::new().create_file(file)?;
+                let mut store = Self::from_parts(db, path.to_path_buf());
+                store.init_table()?;
+                Ok((store, true))
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
+                Self::open_or_create_repair(path)
+            }
+            Err(e) => Err(e.into()),
         }
+    }
 
-        let write_txn = self.db.begin_write()?;
-        {
-            let mut table = write_txn.open_table(WALLET_TABLE)?;
+    /// Open or create a [`RedbStore`], holding a lock file for as long as it is open.
+    ///
+    /// Takes an advisory lock on `<path>.lock` before opening or creating the database,
+    /// so processes racing to set up the same wallet are serialized with a clear error
+    /// instead of redb's "database already open". The lock is released when the store
+    /// and all its clones are dropped, or when the process exits; the lock file itself
+    /// is left in place. Only stores opened with this function take the lock.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Another store holds the lock ([`RedbError::AlreadyLocked`])
+    /// - The lock file cannot be created
+    /// - The database cannot be opened or created, see [`RedbStore::open_or_create`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::{RedbError, RedbStore};
+    ///
+    /// match RedbStore::open_or_create_exclusive("wallet.redb") {
+    ///     Ok(store) => println!("Opened wallet"),
+    ///     Err(RedbError::AlreadyLocked) => eprintln!("Wallet is in use by another process"),
+    ///     Err(e) => eprintln!("Failed to open wallet: {}", e),
+    /// }
+    /// ```
+    ///
+    pub fn open_or_create_exclusive<P>(file_path: P) -> Result<Self, RedbError>
+    where
+        P: AsRef<Path>,
+    {
+        let mut lock_path = file_path.as_ref().as_os_str().to_owned();
+        lock_path.push(".lock");
+        let lock_file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(lock_path)?;
+        // redb's file backend takes the same exclusive lock redb holds on the database
+        let lock = match redb::backends::FileBackend::new(lock_file) {
+            Ok(lock) => lock,
+            Err(redb::DatabaseError::DatabaseAlreadyOpen) => return Err(RedbError::AlreadyLocked),
+            Err(e) => return Err(e.into()),
+        };
+
+        let mut store = Self::open_or_create(file_path)?;
+        store.lock_file = Some(Arc::new(lock));
+        Ok(store)
+    }
+
+    /// Open or create a [`RedbStore`] that keeps its data in a custom table.
+    ///
+    /// By default wallet data lives in a table named `"wallet_data"`. This constructor
+    /// lets the wallet share a redb file with application tables under a name of the
+    /// caller's choosing. The same name must be used every time the file is opened;
+    /// opening with a different name yields an empty wallet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The file exists but cannot be opened
+    /// - The file doesn't exist and cannot be created
+    /// - The table cannot be created
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open_or_create_with_table("app.redb", "my_wallet").unwrap();
+    /// ```
+    ///
+    pub fn open_or_create_with_table<P>(file_path: P, table_name: &str) -> Result<Self, RedbError>
+    where
+        P: AsRef<Path>,
+    {
+        Self::builder()
+            .table_name(table_name)
+            .open_or_create(file_path)
+    }
 
-            // Serialize the changeset
-            let changeset_bytes =
-                serde_json::to_vec(changeset).map_err(RedbError::Serialization)?;
+    /// Create a [`RedbStore`] over a custom redb storage backend.
+    ///
+    /// Builds the database with `redb::Builder::create_with_backend`, so the wallet can
+    /// live anywhere a `redb::StorageBackend` can reach, such as
+    /// `redb::backends::InMemoryBackend` or an application-specific storage layer. Like
+    /// `Database::create`, an empty backend is initialized and a backend that already
+    /// holds a database is opened.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The backend holds data that is not a valid redb database
+    /// - The database or the required table cannot be created
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb::backends::InMemoryBackend;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::create_with_backend(InMemoryBackend::new()).unwrap();
+    /// ```
+    ///
+    pub fn create_with_backend<B>(backend: B) -> Result<Self, RedbError>
+    where
+        B: redb::StorageBackend,
+    {
+        let db = redb::Builder::new().create_with_backend(backend)?;
+        Self::from_database(Arc::new(db))
+    }
 
-            table.insert(CHANGESET_KEY, changeset_bytes.as_slice())?;
+    /// Open a [`RedbStore`] over a custom redb storage backend holding an existing database.
+    ///
+    /// The counterpart of [`RedbStore::open`] for [`RedbStore::create_with_backend`]: it
+    /// refuses to initialize an empty backend.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The backend is empty
+    /// - The backend does not hold a valid redb database
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb::backends::InMemoryBackend;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let backend = InMemoryBackend::new();
+    /// // ... fill the backend with a database image ...
+    /// let store = RedbStore::open_with_backend(backend).unwrap();
+    /// ```
+    ///
+    pub fn open_with_backend<B>(backend: B) -> Result<Self, RedbError>
+    where
+        B: redb::StorageBackend,
+    {
+        if backend.len()? == 0 {
+            return Err(RedbError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "storage backend holds no database",
+            )));
         }
-        write_txn.commit()?;
 
-        Ok(())
+        Self::create_with_backend(backend)
     }
-}
 
-/// Error type for redb storage operations
-/// This enum represents all possible errors that can occur when using the `RedbStore`.
-/// It wraps errors from the underlying redb database, serialization/deserialization errors,
-/// and I/O errors.
-#[derive(Debug)]
-pub enum RedbError {
-    /// Error from the redb database
-    Database(redb::Error),
-    /// Error serializing data
-    Serialization(serde_json::Error),
-    /// Error deserializing data
-    Deserialization(serde_json::Error),
-    /// I/O error
-    Io(std::io::Error),
-    /// Commit error
-    Commit(redb::CommitError),
-    /// Table error
-    Table(redb::TableError),
-    /// Transaction error
-    Transaction(redb::TransactionError),
-}
+    /// Open a read-only [`RedbStore`] over a database image held in memory.
+    ///
+    /// For wallets received as bytes, such as a file written by [`RedbStore::backup_to`]
+    /// and sent over the network, on platforms without a filesystem. The bytes are copied
+    /// into a `redb::backends::InMemoryBackend`; the wallet can be loaded from the store,
+    /// but writes fail with [`RedbError::ReadOnly`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - `bytes` is empty
+    /// - `bytes` is not a valid redb database
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::{LoadParams, PersistedWallet};
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let bytes = std::fs::read("wallet-backup.redb").unwrap();
+    /// let mut store = RedbStore::open_read_only_bytes(bytes).unwrap();
+    /// let wallet = PersistedWallet::load(&mut store, LoadParams::default()).unwrap();
+    /// ```
+    ///
+    pub fn open_read_only_bytes(bytes: Vec<u8>) -> Result<Self, RedbError> {
+        use redb::StorageBackend;
 
-impl std::fmt::Display for RedbError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Self::Database(e) => write!(f, "Database error: {}", e),
-            Self::Serialization(e) => write!(f, "Serialization error: {}", e),
-            Self::Deserialization(e) => write!(f, "Deserialization error: {}", e),
-            Self::Io(e) => write!(f, "I/O error: {}", e),
-            Self::Commit(e) => write!(f, "Commit error: {}", e),
-            Self::Table(e) => write!(f, "Table error: {}", e),
-            Self::Transaction(e) => write!(f, "Transaction error: {}", e),
+        let backend = redb::backends::InMemoryBackend::new();
+        backend.set_len(bytes.len() as u64)?;
+        backend.write(0, &bytes)?;
+
+        let mut store = Self::open_with_backend(backend)?;
+        store.read_only = true;
+        Ok(store)
+    }
+
+    /// Create a [`RedbStore`] in an already opened file.
+    ///
+    /// For platforms that hand out file handles rather than paths, such as Android or
+    /// sandboxed environments. The file must be opened for reading and writing. An empty
+    /// file is initialized; a file that already holds a database is opened, like
+    /// `Database::create` does for paths. The store doesn't know its path, so it keeps a
+    /// duplicate of the handle for [`RedbStore::file_size`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The file holds data that is not a valid redb database
+    /// - The database or the required table cannot be created
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    /// use std::fs::OpenOptions;
+    ///
+    /// let file = OpenOptions::new()
+    ///     .read(true)
+    ///     .write(true)
+    ///     .create(true)
+    ///     .truncate(false)
+    ///     .open("wallet.redb")
+    ///     .unwrap();
+    /// let store = RedbStore::create_from_file(file).unwrap();
+    /// ```
+    ///
+    pub fn create_from_file(file: std::fs::File) -> Result<Self, RedbError> {
+        let handle = file.try_clone()?;
+        let db = redb::Builder::new().create_file(file)?;
+        let mut store = Self::from_database(Arc::new(db))?;
+        store.file = Some(Arc::new(handle));
+        Ok(store)
+    }
+
+    /// Open a [`RedbStore`] from an already opened file holding an existing database.
+    ///
+    /// The counterpart of [`RedbStore::open`] for [`RedbStore::create_from_file`]: it
+    /// refuses to initialize an empty file.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The file is empty
+    /// - The file does not hold a valid redb database
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    /// use std::fs::OpenOptions;
+    ///
+    /// let file = OpenOptions::new().read(true).write(true).open("wallet.redb").unwrap();
+    /// let store = RedbStore::open_from_file(file).unwrap();
+    /// ```
+    ///
+    pub fn open_from_file(file: std::fs::File) -> Result<Self, RedbError> {
+        if file.metadata()?.len() == 0 {
+            return Err(RedbError::Io(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                "file holds no database",
+            )));
         }
+
+        Self::create_from_file(file)
     }
-}
 
-// impl std::error::Error for RedbError {}
-impl std::error::Error for RedbError {
-    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
-        match self {
-            Self::Database(e) => Some(e),
-            Self::Serialization(e) => Some(e),
-            Self::Deserialization(e) => Some(e),
-            Self::Io(e) => Some(e),
-            Self::Commit(e) => Some(e),
-            Self::Table(e) => Some(e),
-            Self::Transaction(e) => Some(e),
+    /// Build a [`RedbStore`] on top of an already opened database.
+    ///
+    /// redb holds an exclusive lock on its file, so a file can only be opened once per
+    /// process. Wrapping the [`Database`] in an [`Arc`] lets several stores share that
+    /// single handle. Stores sharing a handle are safe to use from different threads:
+    /// every persist runs in its own redb write transaction, and redb serializes writers.
+    ///
+    /// The store uses the un-namespaced keys, so it sees the same wallet as a store opened
+    /// directly on the file. Use [`RedbStore::from_database_with_namespace`] to keep
+    /// several wallets apart in one database.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the wallet table cannot be created.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb::Database;
+    /// use redb_wallet_storage::RedbStore;
+    /// use std::sync::Arc;
+    ///
+    /// let db = Arc::new(Database::create("wallet.redb").unwrap());
+    /// let store = RedbStore::from_database(db).unwrap();
+    /// ```
+    ///
+    pub fn from_database(db: Arc<Database>) -> Result<Self, RedbError> {
+        let mut store = Self::from_handle(db, None);
+        store.init_table()?;
+        Ok(store)
+    }
+
+    /// Get the underlying redb database
+    ///
+    /// Lets applications keep their own tables in the wallet's file and run their own
+    /// transactions on them. The store owns its wallet table, `"wallet_data"` unless
+    /// configured otherwise: don't read or write it directly, as the store relies on its
+    /// layout and caches nothing that would notice outside changes. A write transaction
+    /// held through this handle blocks the store's persists until it ends, and
+    /// [`RedbStore::compact`] fails with [`RedbError::SharedDatabase`] while the handle
+    /// is kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RedbError::Closed`] if the store no longer has a database.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb::TableDefinition;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// const LABELS: TableDefinition<&str, &str> = TableDefinition::new("labels");
+    ///
+    /// let store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// let write_txn = store.database().unwrap().begin_write().unwrap();
+    /// write_txn.open_table(LABELS).unwrap().insert("tx1", "Rent").unwrap();
+    /// write_txn.commit().unwrap();
+    /// ```
+    ///
+    pub fn database(&self) -> Result<Arc<Database>, RedbError> {
+        self.db().map(|db| Arc::clone(&db))
+    }
+
+    /// The store's database handle, or [`RedbError::Closed`] if it has none
+    ///
+    /// The handle stays read-locked until the returned guard is dropped, which holds off
+    /// [`RedbStore::compact`]. Drop it at the end of the statement that begins a
+    /// transaction: a compaction waiting for a write transaction to end would otherwise
+    /// wait for the guard too.
+    fn db(&self) -> Result<DbRef<'_>, RedbError> {
+        let handle = self.db.read();
+        if handle.is_none() {
+            return Err(RedbError::Closed);
+        }
+        Ok(DbRef(handle))
+    }
+
+    /// Take the underlying redb database out of the store
+    ///
+    /// The database is returned only if this store holds the only handle to it;
+    /// otherwise, e.g. for a store that has been cloned or was built with
+    /// [`RedbStore::from_database`] from a handle that is still shared, the store is given
+    /// back unchanged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// let db = store.into_inner().unwrap();
+    /// ```
+    ///
+    pub fn into_inner(self) -> Result<Database, Self> {
+        // Clones would be left without a database
+        if Arc::strong_count(&self.db.0) > 1 {
+            return Err(self);
+        }
+        let db = self.db.write().take();
+        let Some(db) = db else {
+            return Err(self);
+        };
+        match Arc::try_unwrap(db) {
+            Ok(db) => Ok(db),
+            Err(db) => {
+                *self.db.write() = Some(db);
+                Err(self)
+            }
+        }
+    }
+
+    /// Build a [`RedbStore`] for one namespace of a shared database.
+    ///
+    /// Works like [`RedbStore::from_database`], but the store's changeset and metadata are
+    /// kept under keys prefixed with `namespace`, so each namespace holds an independent
+    /// wallet. Namespaces must be non-empty and must not contain `::`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The namespace is empty or contains `::`
+    /// - The wallet table cannot be created
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb::Database;
+    /// use redb_wallet_storage::RedbStore;
+    /// use std::sync::Arc;
+    ///
+    /// let db = Arc::new(Database::create("wallets.redb").unwrap());
+    /// let savings = RedbStore::from_database_with_namespace(db.clone(), "savings").unwrap();
+    /// let spending = RedbStore::from_database_with_namespace(db, "spending").unwrap();
+    /// ```
+    ///
+    pub fn from_database_with_namespace(
+        db: Arc<Database>,
+        namespace: &str,
+    ) -> Result<Self, RedbError> {
+        validate_namespace(namespace)?;
+
+        let mut store = Self::from_handle(db, None);
+        store.namespace = Some(namespace.to_string());
+        store.init_table()?;
+        Ok(store)
+    }
+
+    /// Open or create a [`RedbStore`] that recovers from an unreadable changeset.
+    ///
+    /// A store opened normally fails `initialize` if the stored changeset cannot be
+    /// deserialized, e.g. because it was truncated, which leaves the wallet unable to
+    /// start. A recoverable store instead moves the unreadable bytes aside to a
+    /// `corrupt::` key, records what happened in [`RedbStore::last_recovery_info`] and
+    /// initializes with an empty changeset, so the wallet can be rebuilt by a full scan.
+    ///
+    /// Only deserialization failures are recovered from; database errors are still
+    /// returned. A read-only store can't move the bytes aside, so it returns the
+    /// deserialization error like a store opened normally.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The file exists but cannot be opened
+    /// - The file doesn't exist and cannot be created
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::WalletPersister;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open_or_create_recoverable("wallet.redb").unwrap();
+    /// let changeset = WalletPersister::initialize(&mut store).unwrap();
+    /// if let Some(info) = store.last_recovery_info() {
+    ///     eprintln!("Wallet data was unreadable and has been reset: {}", info.error);
+    /// }
+    /// ```
+    ///
+    pub fn open_or_create_recoverable<P>(file_path: P) -> Result<Self, RedbError>
+    where
+        P: AsRef<Path>,
+    {
+        Self::builder().recoverable(true).open_or_create(file_path)
+    }
+
+    /// Get details about the last recovery from an unreadable changeset
+    ///
+    /// Returns `None` unless the store was opened with
+    /// [`RedbStore::open_or_create_recoverable`] and `initialize` had to discard the
+    /// stored changeset.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open_or_create_recoverable("wallet.redb").unwrap();
+    /// assert!(store.last_recovery_info().is_none());
+    /// ```
+    ///
+    pub fn last_recovery_info(&self) -> Option<&RecoveryInfo> {
+        self.last_recovery.as_ref()
+    }
+
+    /// Repair a damaged wallet file and rewrite it with only valid data.
+    ///
+    /// The file is opened with redb's repair enabled, which recovers from an unclean
+    /// shutdown. Every table is then copied to a fresh, compact database that replaces the
+    /// original file, except that changesets that no longer deserialize are dropped.
+    /// Changesets are found with the default [`KeyPrefixes`], in every table. Only tables
+    /// keyed by strings with byte values, the kind this crate writes, can be copied;
+    /// other tables are dropped and counted in [`RepairReport::dropped_bytes`].
+    ///
+    /// If the file is too damaged for redb to open, it is moved aside to `<path>.corrupt`
+    /// and an empty database is created in its place, so the wallet can be restored from its
+    /// descriptors. A file kept by an earlier repair is not overwritten: the next free
+    /// name of `<path>.corrupt.1`, `<path>.corrupt.2` and so on is used instead. The
+    /// returned [`RepairReport`] tells how much data survived.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The file doesn't exist
+    /// - The file can't be opened for a reason other than damage, e.g. it is open
+    ///   elsewhere or not accessible; the file is left untouched
+    /// - The repaired database cannot be written or moved into place
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let (store, report) = RedbStore::repair("wallet.redb").unwrap();
+    /// println!(
+    ///     "Recovered {} bytes, dropped {} bytes",
+    ///     report.recovered_bytes, report.dropped_bytes
+    /// );
+    /// ```
+    ///
+    pub fn repair<P>(file_path: P) -> Result<(Self, RepairReport), RedbError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = file_path.as_ref();
+        let file_len = std::fs::metadata(path)?.len();
+        let sibling = |suffix: &str| {
+            let mut name = path.as_os_str().to_owned();
+            name.push(suffix);
+            PathBuf::from(name)
+        };
+
+        // redb asserts on some kinds of damage, e.g. a file shorter than its recorded
+        // layout, so a panic while reading is treated like any other unreadable file.
+        // Errors that don't mean the file is damaged, such as a lock held by another
+        // process or a permission problem, are returned and the file is left alone.
+        let tables = std::panic::catch_unwind(|| {
+            let mut config = redb::Builder::new();
+            config.set_repair_callback(|_| {});
+            let store = Self::open_with_config(path, config)?;
+            let tables = read_tables(&*store.db()?)?;
+            Ok::<_, RedbError>((tables, store.keys.clone()))
+        });
+
+        let ((tables, other_bytes), keys) = match tables {
+            Ok(Ok(tables)) => tables,
+            Ok(Err(e)) if !e.is_corruption() => return Err(e),
+            Ok(Err(_)) | Err(_) => {
+                // Nothing is readable: keep the damaged file, and any kept before, and
+                // start over. Names are claimed without replacing, so a name taken in
+                // the meantime is skipped rather than overwritten.
+                for n in 0.. {
+                    let corrupt_path = match n {
+                        0 => sibling(".corrupt"),
+                        n => sibling(&format!(".corrupt.{}", n)),
+                    };
+                    match move_no_replace(path, &corrupt_path) {
+                        Ok(()) => break,
+                        Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+                        Err(e) => return Err(e.into()),
+                    }
+                }
+                let store = Self::create(path)?;
+                let report = RepairReport {
+                    recovered_bytes: 0,
+                    dropped_bytes: file_len,
+                };
+                return Ok((store, report));
+            }
+        };
+
+        let mut report = RepairReport {
+            recovered_bytes: 0,
+            dropped_bytes: other_bytes,
+        };
+        let repaired_path = sibling(".repair");
+        // Left behind by an interrupted repair, and never moved into place
+        if repaired_path.exists() {
+            std::fs::remove_file(&repaired_path)?;
+        }
+        {
+            let repaired = Self::create(&repaired_path)?;
+            let write_txn = repaired.db()?.begin_write()?;
+            for (name, entries) in &tables {
+                let mut table = write_txn.open_table(TableDefinition::<&str, &[u8]>::new(name))?;
+
+                // A changeset that doesn't deserialize is dropped along with its segments
+                let mut dropped = std::collections::BTreeSet::new();
+                for key in entries.keys().filter(|key| keys.is_changeset_key(key)) {
+                    if decode_changeset_entries(entries, key).is_err() {
+                        dropped.extend(segment_keys(key));
+                    }
+                }
+                for (key, value) in entries {
+                    if dropped.contains(key) {
+                        report.dropped_bytes += value.len() as u64;
+                        continue;
+                    }
+                    table.insert(key.as_str(), value.as_slice())?;
+                    report.recovered_bytes += value.len() as u64;
+                }
+            }
+            write_txn.commit()?;
+        }
+
+        std::fs::rename(&repaired_path, path)?;
+        Ok((Self::open(path)?, report))
+    }
+
+    /// Atomically replace the wallet file at `target` with the one at `source`.
+    ///
+    /// Meant for restoring a backup into a live path: `source` is checked to be a
+    /// readable redb file whose stored changesets, in every table, all deserialize, then
+    /// renamed over `target`, so anyone opening `target` sees either the old or the new
+    /// file, never a half-written one. `source` no longer exists afterwards.
+    ///
+    /// Both paths must be on the same file system for the rename to be atomic. Every store
+    /// using `target` must be dropped first: Windows cannot replace a file that is still
+    /// open, and elsewhere a store left open would keep writing to the replaced file. An
+    /// open `target` is detected through redb's file lock and reported as an error. The
+    /// target's contents are never read, so a damaged `target` is replaced too.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - `source` cannot be opened or holds a changeset that doesn't deserialize
+    /// - `target` is still open
+    /// - The rename fails, e.g. because the paths are on different file systems
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    /// use std::path::Path;
+    ///
+    /// RedbStore::replace_file(Path::new("wallet.redb"), Path::new("wallet.redb.restore"))
+    ///     .unwrap();
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// ```
+    ///
+    pub fn replace_file(target: &Path, source: &Path) -> Result<(), RedbError> {
+        let source_store = Self::open(source)?;
+        let (tables, _) = read_tables(&*source_store.db()?)?;
+        for entries in tables.values() {
+            for key in entries.keys() {
+                if source_store.keys.is_changeset_key(key) {
+                    decode_changeset_entries(entries, key).map_err(RedbError::Deserialization)?;
+                }
+            }
+        }
+        drop(source_store);
+
+        // Taking redb's lock fails while another handle has the target open. Only the lock
+        // is taken: the target is never parsed, so a damaged one is replaced like any
+        // other. On Unix the lock is held across the rename, so nobody can open the old
+        // file in between; Windows can't replace a file that is still open.
+        let lock = match std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(target)
+        {
+            Ok(file) => Some(redb::backends::FileBackend::new(file)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+            Err(e) => return Err(e.into()),
+        };
+        #[cfg(windows)]
+        drop(lock);
+
+        std::fs::rename(source, target)?;
+        #[cfg(not(windows))]
+        drop(lock);
+
+        // Make the rename itself durable
+        #[cfg(unix)]
+        if let Some(parent) = target.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::File::open(parent)?.sync_all()?;
+        }
+        Ok(())
+    }
+
+    /// Get statistics about the wallet table
+    ///
+    /// Returns statistics about the wallet data table, including the number of entries,
+    /// table size, and other metrics.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The database cannot be read
+    /// - The wallet table cannot be opened
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// let stats = store.table_stats().unwrap();
+    ///
+    /// // You can inspect the table statistics
+    /// println!("Table stats: {:?}", stats);
+    /// ```
+    ///
+    pub fn table_stats(&self) -> Result<redb::TableStats, RedbError> {
+        self.table_stats_for(&self.table_name)
+    }
+
+    /// Check whether this store's namespace holds no wallet data
+    ///
+    /// Complements [`RedbStore::table_stats`], whose numbers can't tell a pristine store
+    /// from one holding a tiny changeset. Only this store's own entries count: other
+    /// namespaces sharing the table, and the un-namespaced wallet for a namespaced store,
+    /// are ignored. Metadata entries, both the reserved ones such as timestamps and those
+    /// written with [`RedbStore::put_metadata`], are ignored too; any other entry, such as
+    /// a changeset, checkpoint or unfolded log entry, makes the store non-empty.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// if store.is_empty().unwrap() {
+    ///     println!("No wallet has been persisted yet");
+    /// }
+    /// ```
+    ///
+    pub fn is_empty(&self) -> Result<bool, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let table = read_txn.open_table(self.wallet_table())?;
+
+        // This store's own data flag settles the common case without a scan
+        if table.get(self.has_data_key(&table)?.as_str())?.is_some() {
+            return Ok(false);
+        }
+        #[cfg(feature = "wal")]
+        if self.has_log(&read_txn)? {
+            return Ok(false);
+        }
+
+        let is_data = |key: &str| !key.starts_with(self.keys.metadata_prefix.as_str());
+        match &self.namespace {
+            Some(namespace) => {
+                let start = namespaced_key(namespace, "");
+                let end = format!("{};", &start[..start.len() - 1]);
+                for entry in table.range(start.as_str()..end.as_str())? {
+                    let (key, _) = entry?;
+                    if is_data(&key.value()[start.len()..]) {
+                        return Ok(false);
+                    }
+                }
+            }
+            None => {
+                for entry in table.iter()? {
+                    let (key, _) = entry?;
+                    let key = key.value();
+                    // Other namespaces' keys, and the index shared by all of them
+                    if key.starts_with(NAMESPACE_PREFIX) || key.starts_with(FINGERPRINT_PREFIX) {
+                        continue;
+                    }
+                    if is_data(key) {
+                        return Ok(false);
+                    }
+                }
+            }
+        }
+
+        Ok(true)
+    }
+
+    /// Get statistics about any table in the database
+    ///
+    /// Like [`RedbStore::table_stats`], but for the table called `name`, such as the
+    /// `_log` table kept next to the wallet table with the `wal` feature.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The database cannot be read
+    /// - No table called `name` exists
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// let stats = store.table_stats_for("wallet_data_log").unwrap();
+    /// println!("Log entries use {} bytes", stats.stored_bytes());
+    /// ```
+    ///
+    pub fn table_stats_for(&self, name: &str) -> Result<redb::TableStats, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let table = read_txn.open_untyped_table(TableDefinition::<&str, &[u8]>::new(name))?;
+        Ok(table.stats()?)
+    }
+
+    /// Get statistics about every table in the database
+    ///
+    /// Returns each table's name with its statistics, in name order, all read from a
+    /// single read transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// for (name, stats) in store.all_table_stats().unwrap() {
+    ///     println!("{}: {} bytes", name, stats.stored_bytes());
+    /// }
+    /// ```
+    ///
+    pub fn all_table_stats(&self) -> Result<Vec<(String, redb::TableStats)>, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+
+        let mut stats = Vec::new();
+        for handle in read_txn.list_tables()? {
+            let name = redb::TableHandle::name(&handle).to_string();
+            let table = read_txn.open_untyped_table(handle)?;
+            stats.push((name, table.stats()?));
+        }
+        stats.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        Ok(stats)
+    }
+
+    /// Attempt to open an existing [`RedbStore`] with custom configuration; create it if
+    /// the file does not exist.
+    ///
+    /// This is the configurable counterpart of [`RedbStore::open_or_create`]: an existing
+    /// file is opened with [`RedbStore::open_with_config`], otherwise a new one is created
+    /// with [`RedbStore::create_with_config`]. Both use the same `config`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The file exists but cannot be opened with the given configuration, for example
+    ///   because it was written with incompatible settings
+    /// - The file doesn't exist and cannot be created
+    /// - The database is corrupted or invalid
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut config = redb::Builder::new();
+    /// config.set_cache_size(1024 * 1024 * 10); // 10 MB cache
+    ///
+    /// let store = RedbStore::open_or_create_with_config("wallet.redb", config).unwrap();
+    /// ```
+    ///
+    pub fn open_or_create_with_config<P>(
+        file_path: P,
+        mut config: redb::Builder,
+    ) -> Result<Self, RedbError>
+    where
+        P: AsRef<Path>,
+    {
+        let has_data = std::fs::metadata(file_path.as_ref()).is_ok_and(|m| m.len() > 0);
+        if has_data {
+            Self::open_with_config(file_path, config)
+        } else {
+            Self::create_with_config(file_path, &mut config)
+        }
+    }
+
+    /// Open or create a [`RedbStore`], keeping rotating backups of the existing file.
+    ///
+    /// If the file already holds a database, it is opened and a backup is written to
+    /// `<path>.bak1` before the store is returned. Older backups shift to `<path>.bak2`,
+    /// `<path>.bak3` and so on, and the oldest is deleted so that at most `keep` backups
+    /// exist. Nothing is backed up when the file is created, or when `keep` is `0`.
+    ///
+    /// Backups are written with [`RedbStore::backup_to`], so each one is a consistent
+    /// snapshot of the wallet table. The new backup is written to `<path>.bak.tmp` first
+    /// and the older ones are only rotated once it is complete, so a failed backup leaves
+    /// the existing ones untouched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The file exists but cannot be opened
+    /// - The file doesn't exist and cannot be created
+    /// - Rotating or writing the backups fails
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// // Keeps wallet.redb.bak1 (newest) to wallet.redb.bak3 (oldest)
+    /// let store = RedbStore::open_or_create_with_backups("wallet.redb", 3).unwrap();
+    /// ```
+    ///
+    pub fn open_or_create_with_backups<P>(file_path: P, keep: usize) -> Result<Self, RedbError>
+    where
+        P: AsRef<Path>,
+    {
+        let path = file_path.as_ref();
+        let (store, created) = match std::fs::metadata(path) {
+            Ok(metadata) if metadata.len() > 0 => (Self::open(path)?, false),
+            _ => (Self::open_or_create(path)?, true),
+        };
+        if created || keep == 0 {
+            return Ok(store);
+        }
+
+        let backup_path = |n: usize| {
+            let mut name = path.as_os_str().to_owned();
+            name.push(format!(".bak{}", n));
+            PathBuf::from(name)
+        };
+
+        let mut pending_path = path.as_os_str().to_owned();
+        pending_path.push(".bak.tmp");
+        let pending_path = PathBuf::from(pending_path);
+        // Left behind by an interrupted backup, and never rotated in
+        if pending_path.exists() {
+            std::fs::remove_file(&pending_path)?;
+        }
+        if let Err(e) = store.backup_to(&pending_path) {
+            let _ = std::fs::remove_file(&pending_path);
+            return Err(e);
+        }
+
+        // Drop the oldest backup and shift the rest up by one
+        let oldest = backup_path(keep);
+        if oldest.exists() {
+            std::fs::remove_file(&oldest)?;
+        }
+        for n in (1..keep).rev() {
+            let from = backup_path(n);
+            if from.exists() {
+                std::fs::rename(&from, backup_path(n + 1))?;
+            }
+        }
+
+        std::fs::rename(&pending_path, backup_path(1))?;
+        Ok(store)
+    }
+
+    /// Open the newest intact file among `paths`
+    ///
+    /// Meant for falling back to a backup when the primary file is damaged, e.g. with the
+    /// backups of [`RedbStore::open_or_create_with_backups`]. Candidates are tried newest
+    /// first by modification time, and the first one that opens and passes
+    /// [`RedbStore::health_check`] is returned along with its path. Candidates whose
+    /// modification time can't be read are tried last, in the order given.
+    ///
+    /// Each candidate is probed in place through a read-only handle: pages are read from
+    /// the file as the health check needs them, and anything redb writes while opening it
+    /// is kept in memory. Probing never repairs or otherwise writes to a file, and doesn't
+    /// load whole files into memory; only the chosen file is then opened for writing.
+    ///
+    /// # Errors
+    ///
+    /// Returns the error of the last candidate tried if none is intact, such as
+    /// [`RedbError::Unhealthy`] for one that opened but failed its health check, or an
+    /// I/O error if `paths` is empty.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    /// use std::path::Path;
+    ///
+    /// let (store, path) = RedbStore::open_latest(&[
+    ///     Path::new("wallet.redb"),
+    ///     Path::new("wallet.redb.bak1"),
+    ///     Path::new("wallet.redb.bak2"),
+    /// ])
+    /// .unwrap();
+    /// println!("Opened {}", path.display());
+    /// ```
+    ///
+    pub fn open_latest(paths: &[&Path]) -> Result<(Self, PathBuf), RedbError> {
+        let mut candidates: Vec<_> = paths
+            .iter()
+            .map(|path| {
+                let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+                (modified, *path)
+            })
+            .collect();
+        // Newest first; the sort is stable, so ties and unknown times keep their order
+        candidates.sort_by_key(|candidate| std::cmp::Reverse(candidate.0));
+
+        let mut last_error = None;
+        for (_, path) in candidates {
+            let probe = ProbeBackend::open(path)
+                .map_err(RedbError::from)
+                .and_then(Self::open_with_backend)
+                .and_then(|probe| {
+                    let report = probe.health_check(None)?;
+                    if report.is_healthy() {
+                        Ok(())
+                    } else {
+                        Err(RedbError::Unhealthy(report.problems))
+                    }
+                });
+            let result = probe.and_then(|()| Self::open(path));
+            match result {
+                Ok(store) => return Ok((store, path.to_path_buf())),
+                Err(e) => {
+                    trace_event!(warn, path = %path.display(), error = %e, "skipping candidate");
+                    last_error = Some(e);
+                }
+            }
+        }
+        Err(last_error.unwrap_or_else(|| {
+            RedbError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "no candidate wallet files given",
+            ))
+        }))
+    }
+
+    /// Get the size of the backing database file in bytes
+    ///
+    /// Returns the current on-disk length of the redb file, including redb's own
+    /// page allocation and bookkeeping overhead, not just the size of the stored changeset.
+    /// Stores built from a file handle with [`RedbStore::create_from_file`] report the
+    /// handle's length. Stores built with [`RedbStore::from_database`] or over a custom
+    /// backend don't know their file and report `0`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file metadata cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// println!("Wallet file uses {} bytes", store.file_size().unwrap());
+    /// ```
+    ///
+    pub fn file_size(&self) -> Result<u64, RedbError> {
+        match (&self.path, &self.file) {
+            (Some(path), _) => Ok(std::fs::metadata(path)?.len()),
+            (None, Some(file)) => Ok(file.metadata()?.len()),
+            (None, None) => Ok(0),
+        }
+    }
+
+    /// Get statistics about redb's page cache
+    ///
+    /// redb only reports how often pages were evicted from its cache because it was full;
+    /// a growing [`CacheStats::evictions`] count means [`RedbStoreBuilder::cache_size`] is
+    /// too small for the working set. redb doesn't count hits or misses, so for stores
+    /// opened with [`RedbStoreBuilder::track_reads`] the reads that missed the cache and
+    /// went to the file are counted as well. All counts are totals since the database was
+    /// opened and are shared by every store using the same database.
+    ///
+    /// # Errors
+    ///
+    /// This function currently does not return errors; the `Result` leaves room for
+    /// backends that have to query their statistics.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::builder()
+    ///     .track_reads(true)
+    ///     .open_or_create("wallet.redb")
+    ///     .unwrap();
+    /// let stats = store.cache_stats().unwrap();
+    /// println!("{} evictions, {:?} file reads", stats.evictions, stats.physical_reads);
+    /// ```
+    ///
+    pub fn cache_stats(&self) -> Result<CacheStats, RedbError> {
+        let counter = self.read_counter.as_deref();
+        Ok(CacheStats {
+            evictions: self.db()?.cache_stats().evictions(),
+            physical_reads: counter.map(|c| c.reads.load(Ordering::Relaxed)),
+            bytes_read: counter.map(|c| c.bytes.load(Ordering::Relaxed)),
+        })
+    }
+
+    /// Remove the stored wallet changeset
+    ///
+    /// Deletes the persisted changeset so that the next `initialize` returns an empty
+    /// changeset. Metadata, including the recorded network and anything written with
+    /// [`RedbStore::put_metadata`], is kept. The space used by the removed data is not
+    /// returned to the file system until [`RedbStore::compact`] is called.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the write transaction cannot be started or committed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open("wallet.redb").unwrap();
+    /// store.clear().unwrap();
+    /// ```
+    ///
+    pub fn clear(&mut self) -> Result<(), RedbError> {
+        self.check_writable()?;
+
+        let write_txn = self.db()?.begin_write()?;
+        {
+            let mut table = write_txn.open_table(self.wallet_table())?;
+            self.remove_changeset(&write_txn, &mut table)?;
+        }
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Compact the database file to reclaim unused space
+    ///
+    /// redb does not shrink its file when data is removed. This wraps redb's
+    /// `Database::compact`, which rewrites the file and releases free pages.
+    ///
+    /// Compaction requires exclusive access to the database with no transactions in
+    /// progress. It locks the database handle the store shares with its clones, so
+    /// operations on any clone wait until it finishes, and a write transaction already in
+    /// progress is waited for. Compacting doesn't need `&mut self`, so it can run on a
+    /// store shared with the rest of the application, see
+    /// [`RedbStore::spawn_maintenance`].
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(true)` if the file was compacted
+    /// - `Ok(false)` if there was nothing to compact
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RedbError::SharedDatabase`] without compacting if the database is shared
+    /// with other stores (see [`RedbStore::from_database`]) or a handle returned by
+    /// [`RedbStore::database`], and an error if redb cannot compact the database, for
+    /// example because a read transaction is still in progress.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open("wallet.redb").unwrap();
+    /// store.clear().unwrap();
+    /// let compacted = store.compact().unwrap();
+    /// println!("Compacted: {}", compacted);
+    /// ```
+    ///
+    pub fn compact(&self) -> Result<bool, RedbError> {
+        self.check_writable()?;
+
+        // Operations in progress hold a read lock only while they begin their transaction,
+        // and those transactions never wait for the handle, so this can't deadlock
+        let mut handle = self.db.write();
+        let db = handle.as_mut().ok_or(RedbError::Closed)?;
+        match Arc::get_mut(db) {
+            Some(db) => Ok(db.compact()?),
+            None => Err(RedbError::SharedDatabase),
+        }
+    }
+
+    /// Run maintenance on a shared store every `interval` in a background task
+    ///
+    /// Each cycle folds the write-ahead log with `fold_log` if the `wal` feature is
+    /// enabled and compacts the file with [`RedbStore::compact`]. The application keeps
+    /// using the store, through the same `Arc` or through clones, while the task runs:
+    /// compaction locks the database handle the clones share, so their operations wait for
+    /// it, and it waits for a persist already in progress, including one handed to another
+    /// thread by `AsyncWalletPersister::persist`. A cycle reports
+    /// [`RedbError::SharedDatabase`] if the database is shared with stores built by
+    /// [`RedbStore::from_database`] on the same handle, and fails to compact while a
+    /// snapshot or other read transaction is open. The work runs on tokio's blocking
+    /// thread pool, and the first cycle starts one `interval` after the call.
+    ///
+    /// Errors don't stop the task: each one is sent to [`MaintenanceHandle::errors`] and
+    /// the next cycle runs as scheduled. Must be called from within a tokio runtime.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::ChangeSet;
+    /// use redb_wallet_storage::RedbStore;
+    /// use std::sync::Arc;
+    /// use std::time::Duration;
+    ///
+    /// # async fn example() {
+    /// let store = Arc::new(RedbStore::open_or_create("wallet.redb").unwrap());
+    /// let interval = Duration::from_secs(600);
+    /// let mut maintenance = Arc::clone(&store).spawn_maintenance(interval);
+    ///
+    /// // The application keeps persisting to the same store
+    /// store.persist_shared(&ChangeSet::default()).unwrap();
+    ///
+    /// while let Some(error) = maintenance.errors().recv().await {
+    ///     eprintln!("Wallet maintenance failed: {}", error);
+    /// }
+    /// # }
+    /// ```
+    ///
+    #[cfg(feature = "tokio")]
+    pub fn spawn_maintenance(self: Arc<Self>, interval: std::time::Duration) -> MaintenanceHandle {
+        let (sender, errors) = tokio::sync::mpsc::unbounded_channel();
+        let cycles = Arc::new(std::sync::atomic::AtomicU64::new(0));
+
+        let task = {
+            let cycles = Arc::clone(&cycles);
+            tokio::spawn(async move {
+                let start = tokio::time::Instant::now() + interval;
+                let mut ticks = tokio::time::interval_at(start, interval);
+                ticks.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+                loop {
+                    ticks.tick().await;
+                    let store = Arc::clone(&self);
+                    let cycle = tokio::task::spawn_blocking(move || store.maintain()).await;
+                    cycles.fetch_add(1, Ordering::SeqCst);
+                    let result =
+                        cycle.unwrap_or_else(|e| Err(RedbError::Io(std::io::Error::other(e))));
+                    if let Err(e) = result {
+                        trace_event!(warn, error = %e, "maintenance cycle failed");
+                        // Nobody listening is fine
+                        let _ = sender.send(e);
+                    }
+                }
+            })
+        };
+
+        MaintenanceHandle {
+            task,
+            errors,
+            cycles,
+        }
+    }
+
+    /// One maintenance cycle of [`RedbStore::spawn_maintenance`]
+    #[cfg(feature = "tokio")]
+    fn maintain(&self) -> Result<(), RedbError> {
+        #[cfg(feature = "wal")]
+        self.fold_log_entries()?;
+        self.compact()?;
+        Ok(())
+    }
+
+    /// Run first-run setup if this store created the wallet
+    ///
+    /// Calls `setup` with the store if opening it created the wallet table, i.e. the file
+    /// or backend was new, and does nothing for a store opened on an existing wallet.
+    /// Combined with [`RedbStore::open_or_create`], this separates onboarding, such as
+    /// asking for a passphrase, from later starts. `setup` runs at most once per store; a
+    /// clone made before the call could run it again.
+    ///
+    /// # Returns
+    ///
+    /// `true` if `setup` ran.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// store.on_first_create(|store| {
+    ///     store.put_metadata("label", b"Savings").unwrap();
+    /// });
+    /// ```
+    ///
+    pub fn on_first_create<F>(&mut self, setup: F) -> bool
+    where
+        F: FnOnce(&mut Self),
+    {
+        if !std::mem::take(&mut self.created) {
+            return false;
+        }
+        setup(self);
+        true
+    }
+
+    /// Compact the database when the store is dropped
+    ///
+    /// Keeps files left behind by short-lived tools small without an explicit call to
+    /// [`RedbStore::compact`]. Defaults to `false`. The setting is not passed on to
+    /// clones, so compaction never runs on whichever clone happens to be dropped last,
+    /// such as the one an async persist moves to its thread. It only happens if no clone
+    /// still shares the database when this store is dropped, and never for read-only
+    /// stores; since `Drop` can't return errors, a failed compaction is ignored.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open("wallet.redb").unwrap();
+    /// store.set_compact_on_drop(true);
+    /// store.clear().unwrap();
+    /// drop(store); // the file shrinks here
+    /// ```
+    ///
+    pub fn set_compact_on_drop(&mut self, compact_on_drop: bool) {
+        self.compact_on_drop = NotInherited(compact_on_drop);
+    }
+
+    /// Get the network recorded for this store
+    ///
+    /// Returns the network written by [`RedbStore::create_for_network`], or `None` if the
+    /// store was created without one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read or the stored value is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// if let Some(network) = store.stored_network().unwrap() {
+    ///     println!("Wallet network: {}", network);
+    /// }
+    /// ```
+    ///
+    pub fn stored_network(&self) -> Result<Option<Network>, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let table = read_txn.open_table(self.wallet_table())?;
+        self.network_in(&table)
+    }
+
+    /// The network recorded for the store in an open table
+    fn network_in<T>(&self, table: &T) -> Result<Option<Network>, RedbError>
+    where
+        T: ReadableTable<&'static str, &'static [u8]>,
+    {
+        match table.get(self.scoped_key(NETWORK_KEY).as_str())? {
+            Some(value) => {
+                let network: Network =
+                    serde_json::from_slice(value.value()).map_err(RedbError::Deserialization)?;
+                Ok(Some(network))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Check that the stored wallet is for the given network
+    ///
+    /// Compares `expected` with the network recorded by [`RedbStore::create_for_network`],
+    /// or else the network in the stored changeset, so a wallet can be rejected before it
+    /// is loaded. Only the changeset's descriptors segment is deserialized, not the
+    /// transaction graph. A store that has never had a network persisted passes the
+    /// check, since there is nothing to disagree with.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RedbError::NetworkMismatch`] if the stored changeset is for another
+    /// network, with `found` set to the stored network, or an error if the database cannot
+    /// be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bitcoin::Network;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// store.assert_network(Network::Testnet).unwrap();
+    /// ```
+    ///
+    pub fn assert_network(&self, expected: Network) -> Result<(), RedbError> {
+        let found = match self.stored_network()? {
+            Some(network) => Some(network),
+            None => self.changeset_network()?,
+        };
+        match found {
+            Some(found) if found != expected => Err(RedbError::NetworkMismatch { expected, found }),
+            _ => Ok(()),
+        }
+    }
+
+    /// The network in the stored changeset, deserializing as little of it as possible
+    fn changeset_network(&self) -> Result<Option<Network>, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let network = {
+            let table = read_txn.open_table(self.wallet_table())?;
+            let key = self.changeset_key(&table)?;
+            let stored = StoredChangeSet::read(&table, &key)?;
+            match stored {
+                Some(StoredChangeSet::Segments(segments)) => {
+                    let mut changeset = ChangeSet::default();
+                    codec::decode_segment_into(
+                        &mut changeset,
+                        0,
+                        segments[0].value(),
+                        &self.deserialize_limits,
+                    )
+                    .map_err(RedbError::Deserialization)?;
+                    changeset.network
+                }
+                Some(StoredChangeSet::Whole(value)) => {
+                    self.decode::<ChangeSetSummary>(value.value())
+                        .map_err(RedbError::Deserialization)?
+                        .network
+                }
+                None => None,
+            }
+        };
+
+        #[cfg(feature = "wal")]
+        let network = self
+            .read_log(&read_txn)?
+            .into_iter()
+            .fold(network, |network, entry| entry.network.or(network));
+        Ok(network)
+    }
+
+    /// Get the time the store was created
+    ///
+    /// Recorded in the database itself, so unlike the file's modification time it
+    /// survives copies and backups. Files written by older versions that didn't record it
+    /// get it on their next persist; until then this returns `None`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// if let Some(created) = store.created_at().unwrap() {
+    ///     println!("Wallet created at {:?}", created);
+    /// }
+    /// ```
+    ///
+    pub fn created_at(&self) -> Result<Option<std::time::SystemTime>, RedbError> {
+        self.read_timestamp(CREATED_AT_KEY)
+    }
+
+    /// Get the time the wallet changeset was last written
+    ///
+    /// Updated by every persist that changes the stored changeset. Persists that change
+    /// nothing leave it untouched. Returns `None` if nothing has been persisted yet.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// if let Some(persisted) = store.last_persisted_at().unwrap() {
+    ///     println!("Wallet last written at {:?}", persisted);
+    /// }
+    /// ```
+    ///
+    pub fn last_persisted_at(&self) -> Result<Option<std::time::SystemTime>, RedbError> {
+        self.read_timestamp(LAST_PERSISTED_KEY)
+    }
+
+    /// Get the time the store was last written to or touched
+    ///
+    /// Updated by [`RedbStore::touch`] and by every persist that changes the stored
+    /// changeset, so it tracks liveness rather than data changes. Returns `None` if the
+    /// store has never been written to or touched.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// if let Some(accessed) = store.last_accessed_at().unwrap() {
+    ///     println!("Wallet last used at {:?}", accessed);
+    /// }
+    /// ```
+    ///
+    pub fn last_accessed_at(&self) -> Result<Option<std::time::SystemTime>, RedbError> {
+        self.read_timestamp(LAST_ACCESSED_KEY)
+    }
+
+    /// Record that the store is in use, without writing wallet data
+    ///
+    /// Sets [`RedbStore::last_accessed_at`] to the current time. Persisting an empty
+    /// changeset writes nothing at all, timestamps included, so an application that wants
+    /// to record liveness, e.g. for a data-retention policy, calls `touch` instead.
+    /// [`RedbStore::last_persisted_at`] is left alone.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store is read-only or the write transaction fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// store.touch().unwrap();
+    /// ```
+    ///
+    pub fn touch(&self) -> Result<(), RedbError> {
+        self.check_writable()?;
+
+        let mut write_txn = self.db()?.begin_write()?;
+        write_txn.set_durability(self.durability);
+        {
+            let mut table = write_txn.open_table(self.wallet_table())?;
+            table.insert(
+                self.scoped_key(LAST_ACCESSED_KEY).as_str(),
+                unix_now().to_be_bytes().as_slice(),
+            )?;
+        }
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Read a timestamp stored under this store's scoped `key`
+    fn read_timestamp(&self, key: &str) -> Result<Option<std::time::SystemTime>, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let table = read_txn.open_table(self.wallet_table())?;
+        self.timestamp_in(&table, key)
+    }
+
+    /// Read a timestamp stored under this store's scoped `key` in an open table
+    fn timestamp_in<T>(
+        &self,
+        table: &T,
+        key: &str,
+    ) -> Result<Option<std::time::SystemTime>, RedbError>
+    where
+        T: ReadableTable<&'static str, &'static [u8]>,
+    {
+        let value = table.get(self.scoped_key(key).as_str())?;
+        Ok(value.and_then(|value| decode_timestamp(value.value())))
+    }
+
+    /// Store a piece of application metadata next to the wallet
+    ///
+    /// Values are kept under a reserved `meta::` key prefix in the wallet table, so they
+    /// can never collide with the changeset. Writing an existing key replaces its value.
+    /// Metadata is not part of the wallet changeset and survives [`RedbStore::clear`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - `key` is a name reserved for internal use, such as `network`
+    /// - The write transaction cannot be started or committed
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// store.put_metadata("label", b"Savings").unwrap();
+    /// ```
+    ///
+    pub fn put_metadata(&mut self, key: &str, value: &[u8]) -> Result<(), RedbError> {
+        self.check_writable()?;
+        if RESERVED_METADATA_KEYS.contains(&key) {
+            return Err(RedbError::ReservedKey(key.to_string()));
+        }
+
+        let mut write_txn = self.db()?.begin_write()?;
+        write_txn.set_durability(self.durability);
+        {
+            let mut table = write_txn.open_table(self.wallet_table())?;
+            let metadata_key = self.scoped_key(&format!("{}{}", METADATA_PREFIX, key));
+            table.insert(metadata_key.as_str(), value)?;
+        }
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Read a piece of application metadata
+    ///
+    /// Returns the value last written with [`RedbStore::put_metadata`], or `None` if the
+    /// key has never been set.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// if let Some(label) = store.get_metadata("label").unwrap() {
+    ///     println!("Label: {}", String::from_utf8_lossy(&label));
+    /// }
+    /// ```
+    ///
+    pub fn get_metadata(&self, key: &str) -> Result<Option<Vec<u8>>, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let table = read_txn.open_table(self.wallet_table())?;
+
+        let metadata_key = self.scoped_key(&format!("{}{}", METADATA_PREFIX, key));
+        Ok(table
+            .get(metadata_key.as_str())?
+            .map(|value| value.value().to_vec()))
+    }
+
+    /// Switch the store to a named changeset profile
+    ///
+    /// Profiles keep several independent changesets in one file, e.g. a watch-only and a
+    /// signing view of the same descriptors. `initialize`, `persist` and everything else
+    /// reading or writing the changeset use the active profile's changeset, stored under
+    /// a `profile::{name}::` key. The choice is recorded in the file, so reopening the
+    /// store restores it, and it applies to every handle on the same namespace. A store
+    /// that never switched uses the default changeset. Metadata, such as the network, is
+    /// shared by all profiles.
+    ///
+    /// Profile names must be non-empty and must not contain `::`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The name is empty or contains `::`
+    /// - The store is read-only
+    /// - The write transaction cannot be started or committed
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// store.set_active_profile("watch-only").unwrap();
+    /// ```
+    ///
+    pub fn set_active_profile(&mut self, name: &str) -> Result<(), RedbError> {
+        validate_namespace(name)?;
+        self.check_writable()?;
+
+        let write_txn = self.db()?.begin_write()?;
+        {
+            let mut table = write_txn.open_table(self.wallet_table())?;
+            table.insert(
+                self.scoped_key(ACTIVE_PROFILE_KEY).as_str(),
+                name.as_bytes(),
+            )?;
+        }
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Get the name of the active changeset profile
+    ///
+    /// Returns `None` if the store uses the default changeset.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// println!("Active profile: {:?}", store.active_profile().unwrap());
+    /// ```
+    ///
+    pub fn active_profile(&self) -> Result<Option<String>, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let table = read_txn.open_table(self.wallet_table())?;
+
+        Ok(table
+            .get(self.scoped_key(ACTIVE_PROFILE_KEY).as_str())?
+            .map(|value| String::from_utf8_lossy(value.value()).into_owned()))
+    }
+
+    /// Register a callback invoked after each successful commit
+    ///
+    /// The callback receives the changeset that was just written, i.e. the stored
+    /// changeset after merging. It runs only once the redb write transaction has been
+    /// committed, so it never fires for a persist that fails. Registering a new callback
+    /// replaces the previous one. Clones made before the call keep their own callback.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// store.on_persist(Box::new(|changeset| {
+    ///     println!("Persisted changeset for network {:?}", changeset.network);
+    /// }));
+    /// ```
+    ///
+    pub fn on_persist(&mut self, callback: Box<dyn Fn(&ChangeSet) + Send + Sync>) {
+        self.on_persist = Some(Arc::from(callback));
+    }
+
+    /// Subscribe to an event sent after each successful commit
+    ///
+    /// Every commit that changes the stored changeset, through this store or any of its
+    /// clones, sends a [`PersistEvent`] with the namespace and the new version. Sending
+    /// never blocks the writer: a receiver that falls more than 64 events behind gets
+    /// [`tokio::sync::broadcast::error::RecvError::Lagged`] and skips ahead, and dropped
+    /// receivers are simply forgotten. Stores opened separately on the same database
+    /// don't share events.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// # async fn example() {
+    /// let store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// let mut events = store.subscribe();
+    /// while let Ok(event) = events.recv().await {
+    ///     println!("Wallet {:?} is now at version {}", event.namespace, event.version);
+    /// }
+    /// # }
+    /// ```
+    ///
+    #[cfg(feature = "tokio")]
+    pub fn subscribe(&self) -> tokio::sync::broadcast::Receiver<PersistEvent> {
+        self.events.subscribe()
+    }
+
+    /// Tell the `on_persist` callback and any subscribers about a committed changeset
+    ///
+    /// `merged` comes from the write transaction that committed it, so the version sent is
+    /// the one this commit wrote even if another writer has committed since.
+    fn notify_persisted(&self, merged: &Merged) {
+        if let Some(callback) = &self.on_persist {
+            callback(&merged.changeset);
+        }
+
+        #[cfg(feature = "tokio")]
+        if self.events.receiver_count() > 0 {
+            // Fails only when every receiver is gone, which is fine
+            let _ = self.events.send(PersistEvent {
+                namespace: self.namespace.clone(),
+                version: merged.version,
+            });
+        }
+    }
+
+    /// Set the durability used when committing persisted changesets
+    ///
+    /// Defaults to `Durability::Immediate`, which fsyncs on every commit.
+    /// `Durability::Eventual` skips the fsync, trading crash safety of the most recent
+    /// commits for throughput; such commits become durable with the next immediate commit.
+    /// The setting applies to `persist` and to transactions started with
+    /// [`RedbStore::transaction`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb::Durability;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open_or_create("wallet.redb").unwrap();
+    ///
+    /// // Fast commits while scanning
+    /// store.set_durability(Durability::Eventual);
+    /// // ... persist scan results ...
+    ///
+    /// // Make the final commit durable
+    /// store.set_durability(Durability::Immediate);
+    /// ```
+    ///
+    pub fn set_durability(&mut self, durability: redb::Durability) {
+        self.durability = durability;
+    }
+
+    /// Choose how often persists are flushed to disk
+    ///
+    /// Overrides [`RedbStore::set_durability`] for `persist` and
+    /// [`RedbStore::persist_if_version`]; other writes keep using the configured durability.
+    /// Only persists that commit count towards [`FlushPolicy::EveryN`]. Commits that aren't
+    /// flushed are visible to readers right away but only reach the disk with the next
+    /// flushed commit, so a crash rolls the file back to the last flushed commit. The file
+    /// is never left half-written. See [`FlushPolicy`] for the tradeoff of each policy.
+    ///
+    /// redb only reuses the space freed by a commit after a flushed one, so the file grows
+    /// with every unflushed commit. With [`FlushPolicy::Never`] it grows without bound
+    /// unless some other write is flushed, e.g. one made with the configured durability
+    /// or after switching back to another policy; use it only for short bursts, such as
+    /// an initial scan.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::{FlushPolicy, RedbStore};
+    ///
+    /// let mut store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// // Lose at most the last 9 persists in a crash
+    /// store.set_flush_policy(FlushPolicy::EveryN(10));
+    /// ```
+    ///
+    pub fn set_flush_policy(&mut self, policy: FlushPolicy) {
+        self.flush_policy = Some(policy);
+        self.unflushed.store(0, Ordering::SeqCst);
+    }
+
+    /// The durability of the next persist's commit, and whether it flushes
+    fn persist_durability(&self) -> (redb::Durability, bool) {
+        match self.flush_policy {
+            None => (self.durability, false),
+            Some(FlushPolicy::Always) => (redb::Durability::Immediate, true),
+            Some(FlushPolicy::Never) => (redb::Durability::None, false),
+            Some(FlushPolicy::EveryN(n)) => {
+                if self.unflushed.load(Ordering::SeqCst) + 1 >= n {
+                    (redb::Durability::Immediate, true)
+                } else {
+                    (redb::Durability::None, false)
+                }
+            }
+        }
+    }
+
+    /// Count a committed persist towards the flush policy
+    ///
+    /// Called only after the commit succeeded, so aborted and failed persists don't
+    /// bring the next flush forward.
+    fn persist_committed(&self, flushed: bool) {
+        if flushed {
+            self.unflushed.store(0, Ordering::SeqCst);
+        } else {
+            self.unflushed.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    /// Limit the size of the stored changeset
+    ///
+    /// When set, a persist whose merged changeset would serialize to more than `limit`
+    /// bytes fails with [`RedbError::TooLarge`] and leaves the stored changeset untouched.
+    /// The limit applies to the whole stored changeset, not to each update, and is
+    /// checked by `persist` and [`StoreTxn::commit`]. `None`, the default, means unlimited.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// store.set_max_changeset_bytes(Some(16 * 1024 * 1024));
+    /// ```
+    ///
+    pub fn set_max_changeset_bytes(&mut self, limit: Option<usize>) {
+        self.max_changeset_bytes = limit;
+    }
+
+    /// Let a persist with different descriptors replace the stored wallet
+    ///
+    /// By default a persist whose changeset carries a descriptor that differs from the
+    /// stored one fails with [`RedbError::DescriptorConflict`], since merging changesets
+    /// of two different wallets corrupts both. Allowing descriptor changes makes such a
+    /// persist replace the stored changeset with the incoming one instead; the old
+    /// wallet's state is discarded, not merged. Changesets without descriptors, and ones
+    /// with the stored descriptors, merge as usual either way.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// // Re-create the wallet with new descriptors on purpose
+    /// store.set_allow_descriptor_change(true);
+    /// ```
+    ///
+    pub fn set_allow_descriptor_change(&mut self, allow: bool) {
+        self.allow_descriptor_change = allow;
+    }
+
+    /// Keep at most `max` checkpoints per profile
+    ///
+    /// Each [`RedbStore::create_checkpoint`] holds a full copy of the changeset, so
+    /// checkpoints taken on a schedule grow the file without bound. With a limit, taking
+    /// a checkpoint deletes the oldest ones of the active profile beyond it, in the same
+    /// transaction. `None`, the default, keeps every checkpoint.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// store.set_max_checkpoints(Some(10));
+    /// ```
+    ///
+    pub fn set_max_checkpoints(&mut self, max: Option<usize>) {
+        self.max_checkpoints = max;
+    }
+
+    /// Retry persists that fail with a transient I/O error
+    ///
+    /// On a flaky filesystem a commit can fail with an I/O error and succeed when tried
+    /// again. With `attempts` above zero, a persist that fails with an I/O-class storage
+    /// error waits `backoff` and re-runs the whole transaction, up to `attempts` more
+    /// times. Logic errors, such as a network mismatch, and corruption are returned at
+    /// once. The default is no retry.
+    ///
+    /// redb refuses any further I/O on a database that hit an I/O error, so before each
+    /// retry the file is closed and reopened with the store's settings. Retrying therefore
+    /// only works for a store opened from a path, without a caller's `redb::Builder`,
+    /// that holds the only handle to its database; otherwise the original error is
+    /// returned. If the database can't be opened again on the file, the original error is
+    /// returned and the store is left closed: later operations fail with
+    /// [`RedbError::Closed`] until a retried persist or [`RedbStore::release_cache`]
+    /// manages to reopen it. It applies to both [`WalletPersister::persist`] and
+    /// [`AsyncWalletPersister::persist`]; with the `tokio` or `async-std` feature, the async
+    /// persist waits out the backoff without blocking its executor.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    /// use std::time::Duration;
+    ///
+    /// let mut store = RedbStore::open_or_create("/mnt/nfs/wallet.redb").unwrap();
+    /// store.set_write_retry(3, Duration::from_millis(200));
+    /// ```
+    ///
+    pub fn set_write_retry(&mut self, attempts: u32, backoff: std::time::Duration) {
+        self.write_retry = (attempts, backoff);
+    }
+
+    /// Persist a changeset, retrying transient I/O errors as configured
+    fn persist_retrying(&mut self, changeset: &ChangeSet) -> Result<bool, RedbError> {
+        let mut attempt = 0;
+        loop {
+            match self.persist_changeset(changeset) {
+                Err(e) if self.should_retry(&e, attempt) => {
+                    attempt += 1;
+                    trace_event!(warn, error = %e, attempt, "retrying persist after I/O error");
+                    std::thread::sleep(self.write_retry.1);
+                    self.reopen_for_retry(e)?;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Persist a changeset on another thread, retrying transient I/O errors as configured
+    ///
+    /// Each attempt runs `offload` on a clone of the store, which must be dropped before
+    /// the returned future completes, so that the database can be reopened here between
+    /// attempts. `sleep` waits out the backoff without blocking the executor.
+    #[cfg(any(feature = "tokio", feature = "async-std"))]
+    async fn persist_offloaded<O, F, S, W>(
+        &mut self,
+        changeset: &ChangeSet,
+        offload: O,
+        sleep: S,
+    ) -> Result<bool, RedbError>
+    where
+        O: Fn(RedbStore, ChangeSet) -> F,
+        F: Future<Output = Result<bool, RedbError>>,
+        S: Fn(std::time::Duration) -> W,
+        W: Future<Output = ()>,
+    {
+        let mut attempt = 0;
+        loop {
+            match offload(self.clone(), changeset.clone()).await {
+                Err(e) if self.should_retry(&e, attempt) => {
+                    attempt += 1;
+                    trace_event!(warn, error = %e, attempt, "retrying persist after I/O error");
+                    sleep(self.write_retry.1).await;
+                    self.reopen_for_retry(e)?;
+                }
+                result => return result,
+            }
+        }
+    }
+
+    /// Whether a persist that failed with `error` gets another attempt
+    ///
+    /// A closed store is retried too, as reopening it may succeed this time.
+    fn should_retry(&self, error: &RedbError, attempt: u32) -> bool {
+        (error.is_transient() || matches!(error, RedbError::Closed)) && attempt < self.write_retry.0
+    }
+
+    /// Reopen the database for another attempt after `error`, or return `error`
+    fn reopen_for_retry(&mut self, error: RedbError) -> Result<(), RedbError> {
+        match self.reopen() {
+            Ok(true) => Ok(()),
+            Ok(false) => Err(error),
+            Err(_reopen_error) => {
+                trace_event!(warn, error = %_reopen_error, "could not reopen the database");
+                Err(error)
+            }
+        }
+    }
+
+    /// Close and reopen the database file, with the settings it was opened with
+    ///
+    /// Returns `false` without doing anything if the store has no path, shares its
+    /// database handle, as the file can't be reopened while another handle holds it, or
+    /// was opened with a caller's `redb::Builder`, whose settings can't be reproduced.
+    /// A closed store is opened again.
+    ///
+    /// The file is opened before the database is closed, so a file that can't be opened
+    /// leaves the store as it was. If redb then fails to open the database on it, the
+    /// store is left closed, and every later operation fails with [`RedbError::Closed`].
+    fn reopen(&mut self) -> Result<bool, RedbError> {
+        let Some(path) = self.path.clone() else {
+            return Ok(false);
+        };
+        if self.custom_config || self.db.is_shared() {
+            return Ok(false);
+        }
+
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .open(&path)?;
+
+        // redb locks its file, so the old handle must go before the file is opened again
+        let mut handle = self.db.write();
+        *handle = None;
+        #[cfg(test)]
+        if let Some(hook) = &self.before_reopen {
+            hook();
+        }
+        let mut config = redb::Builder::new();
+        if let Some(bytes) = self.cache_size {
+            config.set_cache_size(bytes);
+        }
+        let backend = redb::backends::FileBackend::new(file)?;
+        let db = match &self.read_counter {
+            Some(counter) => config.create_with_backend(CountingBackend {
+                inner: backend,
+                counter: Arc::clone(counter),
+            })?,
+            None => config.create_with_backend(backend)?,
+        };
+        *handle = Some(Arc::new(db));
+        Ok(true)
+    }
+
+    /// Release the memory held by redb's page cache
+    ///
+    /// redb has no call to empty its cache, so the database is closed, which flushes it,
+    /// and opened again with an empty cache; the cache fills up again as the wallet is
+    /// read. Meant for long-lived stores on memory-constrained devices, between bursts of
+    /// activity. A cache size set with [`RedbStoreBuilder::cache_size`] is kept.
+    ///
+    /// This needs the only handle to the database, so unlike [`RedbStore::compact`] it
+    /// fails for a store that has been cloned, as well as one that shares its database
+    /// with other stores; drop the clones first. It also needs the store's path to reopen it: stores built
+    /// from a database, backend or file, and stores opened with a caller's
+    /// `redb::Builder`, whose settings can't be reproduced, are left alone. Reads keep
+    /// being counted for stores opened with [`RedbStoreBuilder::track_reads`]. A closed
+    /// store is opened again.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the cache was released, `false` if the store has no path or settings to
+    /// reopen it with.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RedbError::SharedDatabase`] if the database handle is shared, and an
+    /// error if the database file cannot be opened again, leaving the store as it was.
+    /// If the file opens but redb then fails to open the database on it, the store is
+    /// closed and every later operation on it fails with [`RedbError::Closed`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open("wallet.redb").unwrap();
+    /// let changeset = store.load_changeset().unwrap();
+    /// // ... idle for a while ...
+    /// store.release_cache().unwrap();
+    /// ```
+    ///
+    pub fn release_cache(&mut self) -> Result<bool, RedbError> {
+        if self.db.is_shared() {
+            return Err(RedbError::SharedDatabase);
+        }
+        self.reopen()
+    }
+
+    /// Write changesets as indented JSON
+    ///
+    /// Pretty JSON makes a dumped file readable while debugging, at the cost of noticeably
+    /// larger changesets; compact JSON, the default, is meant for production. Reading is
+    /// unaffected, as either form parses the same, so a file can hold changesets written
+    /// in both. The stored changeset switches format on its next write, and
+    /// [`RedbStore::serialized_size`] reports the size in the format set here.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// store.set_json_pretty(cfg!(debug_assertions));
+    /// ```
+    ///
+    pub fn set_json_pretty(&mut self, pretty: bool) {
+        self.json_pretty = pretty;
+    }
+
+    /// Serialize a changeset in this store's JSON format
+    #[cfg(feature = "wal")]
+    fn encode_changeset(&self, changeset: &ChangeSet) -> Result<Vec<u8>, RedbError> {
+        codec::encode_changeset(changeset, self.json_pretty).map_err(RedbError::Serialization)
+    }
+
+    /// Limit the size and nesting of the JSON this store deserializes
+    ///
+    /// Every changeset read from the file is checked against `limits` before it is
+    /// parsed, so a corrupted or malicious file cannot make `initialize` allocate
+    /// unbounded memory or recurse arbitrarily deep. Data exceeding a limit fails with
+    /// [`RedbError::Deserialization`], which a recoverable store handles like any other
+    /// unreadable changeset. The defaults are far above what a real wallet needs.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::{DeserializeLimits, RedbStore};
+    ///
+    /// let mut store = RedbStore::open("wallet.redb").unwrap();
+    /// store.set_deserialize_limits(DeserializeLimits {
+    ///     max_bytes: 64 * 1024 * 1024,
+    ///     ..DeserializeLimits::default()
+    /// });
+    /// ```
+    ///
+    pub fn set_deserialize_limits(&mut self, limits: DeserializeLimits) {
+        self.deserialize_limits = limits;
+    }
+
+    /// Deserialize stored JSON within this store's limits
+    fn decode<T>(&self, bytes: &[u8]) -> Result<T, serde_json::Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        codec::decode_json(bytes, &self.deserialize_limits)
+    }
+
+    /// Require the stored wallet to have the given descriptors
+    ///
+    /// Once set, `initialize` compares the descriptors of the stored changeset with
+    /// `external` and `internal` and fails with [`RedbError::DescriptorMismatch`] if they
+    /// differ, so a swapped file can't silently load the wallet of somebody else. The
+    /// descriptors may contain private keys; only their public form is kept and compared.
+    /// A store with no wallet yet passes the check. Without this call, `initialize`
+    /// returns whatever is stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`RedbError::InvalidDescriptor`] if either descriptor cannot be parsed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::WalletPersister;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open("wallet.redb").unwrap();
+    /// store
+    ///     .expect_descriptors("wpkh(tpub.../0/*)", "wpkh(tpub.../1/*)")
+    ///     .unwrap();
+    /// let changeset = WalletPersister::initialize(&mut store).unwrap();
+    /// ```
+    ///
+    pub fn expect_descriptors(&mut self, external: &str, internal: &str) -> Result<(), RedbError> {
+        let secp = bitcoin::secp256k1::Secp256k1::new();
+        let parse = |descriptor: &str| {
+            Descriptor::parse_descriptor(&secp, descriptor)
+                .map(|(descriptor, _)| descriptor)
+                .map_err(|e| RedbError::InvalidDescriptor(e.to_string()))
+        };
+
+        self.expected_descriptors = Some((parse(external)?, parse(internal)?));
+        Ok(())
+    }
+
+    /// Merge the wallet stored in another store into this one
+    ///
+    /// Reads `other`'s changeset, including its unfolded log entries, and merges it into
+    /// this store's changeset in a single write transaction. This combines partial copies
+    /// of the same wallet, e.g. a backup and the copy on a device, into one authoritative
+    /// file. `other` is only read.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - This store is read-only
+    /// - The two wallets have different descriptors ([`RedbError::DescriptorConflict`])
+    ///   or networks ([`RedbError::NetworkMismatch`])
+    /// - Either database cannot be read, or the write fails
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open("wallet.redb").unwrap();
+    /// let backup = RedbStore::open("wallet-backup.redb").unwrap();
+    /// store.merge_from(&backup).unwrap();
+    /// ```
+    ///
+    pub fn merge_from(&mut self, other: &RedbStore) -> Result<(), RedbError> {
+        self.check_writable()?;
+
+        let Some(mut incoming) = other.get_changeset_with_log()? else {
+            return Ok(());
+        };
+        self.check_network(&incoming)?;
+
+        let mut write_txn = self.db()?.begin_write()?;
+        write_txn.set_durability(self.durability);
+        let folded_log = self.fold_log_before(&write_txn, &mut incoming)?;
+        let merged = {
+            let mut table = write_txn.open_table(self.wallet_table())?;
+            if let Some(stored) = StoredChangeSet::read(&table, &self.changeset_key(&table)?)? {
+                let existing = stored
+                    .decode(&self.deserialize_limits)
+                    .map_err(RedbError::Deserialization)?;
+                check_same_wallet(&existing, &incoming)?;
+            }
+            self.merge_into_table(&mut table, incoming)?
+        };
+
+        let Some(merged) = merged else {
+            if folded_log {
+                write_txn.commit()?;
+            } else {
+                write_txn.abort()?;
+            }
+            return Ok(());
+        };
+        write_txn.commit()?;
+
+        // Notify only once the data is committed
+        self.notify_persisted(&merged);
+
+        Ok(())
+    }
+
+    /// Start a transaction that batches several persists into a single commit
+    ///
+    /// Each [`StoreTxn::persist`] call merges its changeset into the transaction, and
+    /// [`StoreTxn::commit`] writes the combined result with one redb write transaction.
+    /// Dropping the guard without committing discards everything staged in it.
+    ///
+    /// The guard holds redb's write lock for its whole lifetime, so keep it short-lived.
+    /// Outside of a transaction the regular `WalletPersister::persist` keeps committing
+    /// on every call.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the write transaction cannot be started.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::ChangeSet;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// let updates: Vec<ChangeSet> = Vec::new();
+    ///
+    /// let mut txn = store.transaction().unwrap();
+    /// for update in &updates {
+    ///     txn.persist(update).unwrap();
+    /// }
+    /// txn.commit().unwrap();
+    /// ```
+    ///
+    pub fn transaction(&mut self) -> Result<StoreTxn<'_>, RedbError> {
+        self.check_writable()?;
+
+        let mut write_txn = self.db()?.begin_write()?;
+        write_txn.set_durability(self.durability);
+        Ok(StoreTxn {
+            store: self,
+            write_txn,
+            staged: ChangeSet::default(),
+        })
+    }
+
+    /// Take a consistent, read-only view of the store
+    ///
+    /// The returned [`StoreSnapshot`] holds a single redb read transaction for its
+    /// lifetime, so the changeset, metadata and version read through it all come from
+    /// the same point in time, even while other handles persist. Long-lived snapshots
+    /// keep redb from reclaiming the pages they see, so drop them promptly.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the read transaction cannot be started.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// let snapshot = store.snapshot().unwrap();
+    /// let changeset = snapshot.changeset().unwrap();
+    /// let label = snapshot.metadata("label").unwrap();
+    /// println!("Version {}: {:?} {:?}", snapshot.version().unwrap(), changeset, label);
+    /// ```
+    ///
+    pub fn snapshot(&self) -> Result<StoreSnapshot<'_>, RedbError> {
+        Ok(StoreSnapshot {
+            store: self,
+            read_txn: self.db()?.begin_read()?,
+        })
+    }
+
+    /// Load the stored wallet changeset without constructing a wallet
+    ///
+    /// Returns the aggregated changeset exactly as persisted, which is useful for
+    /// diagnostics and migration tooling. Unlike loading a `PersistedWallet`, this does not
+    /// need descriptors or network parameters. With the `wal` feature, unfolded log entries
+    /// are merged in, as [`WalletPersister::initialize`] would.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(changeset))` if a changeset has been persisted
+    /// - `Ok(None)` if nothing has been persisted yet
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read or the changeset cannot be
+    /// deserialized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// if let Some(changeset) = store.load_changeset().unwrap() {
+    ///     println!("Stored network: {:?}", changeset.network);
+    /// }
+    /// ```
+    ///
+    pub fn load_changeset(&self) -> Result<Option<ChangeSet>, RedbError> {
+        self.get_changeset_with_log()
+    }
+
+    /// Get the last revealed derivation index of each keychain
+    ///
+    /// Reads only the descriptors and the indexer from the stored changeset; the
+    /// transaction graph is skipped without being built, so this is cheap enough for a
+    /// status endpoint. Keychains without revealed addresses are left out, and a store
+    /// that has never been persisted to returns an empty map.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read or the stored changeset cannot be
+    /// deserialized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::KeychainKind;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// let indices = store.last_revealed_indices().unwrap();
+    /// println!("External index: {:?}", indices.get(&KeychainKind::External));
+    /// ```
+    ///
+    pub fn last_revealed_indices(&self) -> Result<BTreeMap<KeychainKind, u32>, RedbError> {
+        Ok(self
+            .changeset_summary()?
+            .map(|summary| summary.last_revealed())
+            .unwrap_or_default())
+    }
+
+    /// Load only the indexer part of the stored changeset
+    ///
+    /// The stored changeset is split into segments, and the indexer, which records the
+    /// last revealed index of each descriptor, is one of them. Loading it reads neither
+    /// the transaction graph nor the local chain, so it stays cheap however large the
+    /// wallet grows, e.g. for handing out a receive address. Unfolded log entries are
+    /// merged in, as `initialize` does; they are small deltas and are deserialized whole.
+    /// Changesets written before they were segmented fall back to parsing the indexer out
+    /// of the full changeset.
+    ///
+    /// # Returns
+    ///
+    /// The stored indexer changeset, or `None` if nothing has been persisted.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read or the indexer cannot be
+    /// deserialized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// if let Some(indexer) = store.load_indexer_only().unwrap() {
+    ///     println!("Revealed: {:?}", indexer.last_revealed);
+    /// }
+    /// ```
+    ///
+    pub fn load_indexer_only(
+        &self,
+    ) -> Result<Option<bdk_chain::keychain_txout::ChangeSet>, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let indexer = {
+            let table = read_txn.open_table(self.wallet_table())?;
+            let key = self.changeset_key(&table)?;
+            let stored = StoredChangeSet::read(&table, &key)?;
+            match stored {
+                Some(StoredChangeSet::Segments(segments)) => Some(
+                    self.decode(segments[3].value())
+                        .map_err(RedbError::Deserialization)?,
+                ),
+                Some(StoredChangeSet::Whole(value)) => Some(
+                    self.decode::<ChangeSetSummary>(value.value())
+                        .map_err(RedbError::Deserialization)?
+                        .indexer,
+                ),
+                None => None,
+            }
+        };
+
+        #[cfg(feature = "wal")]
+        let indexer = {
+            let mut indexer = indexer;
+            for entry in self.read_log(&read_txn)? {
+                indexer
+                    .get_or_insert_with(Default::default)
+                    .merge(entry.indexer);
+            }
+            indexer
+        };
+        Ok(indexer)
+    }
+
+    /// Get the height of the stored local chain's tip
+    ///
+    /// Like [`RedbStore::last_revealed_indices`], this reads only part of the stored
+    /// changeset. Returns `None` if nothing has been persisted or no block is stored.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read or the stored changeset cannot be
+    /// deserialized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// if let Some(height) = store.tip_height().unwrap() {
+    ///     println!("Synced to block {}", height);
+    /// }
+    /// ```
+    ///
+    pub fn tip_height(&self) -> Result<Option<u32>, RedbError> {
+        let Some(summary) = self.changeset_summary()? else {
+            return Ok(None);
+        };
+        Ok(chain_tip(&summary.local_chain))
+    }
+
+    /// Summarize the stored wallet for status output
+    ///
+    /// Reports the network, descriptors, last revealed indices, number of transactions
+    /// and chain tip without building a `Wallet`. Like [`RedbStore::last_revealed_indices`],
+    /// it only parses part of the stored changeset: transactions are counted, not
+    /// deserialized. With the `wal` feature, unfolded log entries are included, but their
+    /// transactions are only counted once [`RedbStore::fold_log`] has run. A store that
+    /// has never been persisted to returns an empty summary.
+    /// The summary's [`std::fmt::Display`] output is meant for a terminal.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read or the stored changeset cannot be
+    /// deserialized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// println!("{}", store.describe().unwrap());
+    /// ```
+    ///
+    pub fn describe(&self) -> Result<WalletSummary, RedbError> {
+        let Some(summary) = self.changeset_summary()? else {
+            return Ok(WalletSummary::default());
+        };
+        Ok(WalletSummary {
+            network: summary.network,
+            descriptor: summary.descriptor.as_ref().map(ToString::to_string),
+            change_descriptor: summary.change_descriptor.as_ref().map(ToString::to_string),
+            last_revealed: summary.last_revealed(),
+            tx_count: summary.tx_graph.txs.0,
+            tip_height: chain_tip(&summary.local_chain),
+        })
+    }
+
+    /// Deserialize the parts of the stored changeset a [`ChangeSetSummary`] holds
+    ///
+    /// With the `wal` feature, unfolded log entries are merged in, except for their
+    /// transactions, which can't be counted without the stored ones.
+    fn changeset_summary(&self) -> Result<Option<ChangeSetSummary>, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let summary = self
+            .changeset_bytes_in(&read_txn)?
+            .map(|bytes| self.decode(&bytes).map_err(RedbError::Deserialization))
+            .transpose()?;
+
+        #[cfg(feature = "wal")]
+        let summary = {
+            let mut summary = summary;
+            for entry in self.read_log(&read_txn)? {
+                summary
+                    .get_or_insert_with(ChangeSetSummary::default)
+                    .merge(entry);
+            }
+            summary
+        };
+        Ok(summary)
+    }
+
+    /// Check whether a wallet has been persisted, without loading it
+    ///
+    /// Returns `true` if a non-empty changeset is stored for this store's namespace. The
+    /// answer comes from a small flag kept next to the changeset, so the changeset is
+    /// neither read nor deserialized; this is cheap even for large wallets and never
+    /// needs descriptors. A freshly created store returns `false`. With the `wal` feature,
+    /// unfolded log entries count as a wallet too.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// if !store.has_wallet().unwrap() {
+    ///     println!("No wallet yet, creating one");
+    /// }
+    /// ```
+    ///
+    pub fn has_wallet(&self) -> Result<bool, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let table = read_txn.open_table(self.wallet_table())?;
+
+        if table.get(self.has_data_key(&table)?.as_str())?.is_some() {
+            return Ok(true);
+        }
+        // Changesets only appended to the log don't set the flag
+        #[cfg(feature = "wal")]
+        if self.has_log(&read_txn)? {
+            return Ok(true);
+        }
+
+        // Files written before the flag existed still need the changeset itself
+        let key = self.changeset_key(&table)?;
+        Ok(table
+            .get(key.as_str())?
+            .is_some_and(|value| !value.value().is_empty()))
+    }
+
+    /// Check whether a wallet has been persisted under another namespace
+    ///
+    /// Like [`RedbStore::has_wallet`], but looks at `namespace` in the same wallet table
+    /// instead of this store's own namespace.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the namespace is invalid or the database cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallets.redb").unwrap();
+    /// println!("Savings wallet exists: {}", store.has_wallet_in("savings").unwrap());
+    /// ```
+    ///
+    pub fn has_wallet_in(&self, namespace: &str) -> Result<bool, RedbError> {
+        validate_namespace(namespace)?;
+
+        let mut store = self.clone();
+        store.namespace = Some(namespace.to_string());
+        store.has_wallet()
+    }
+
+    /// Copy the wallet stored under `namespace` to `dest_namespace` in another store
+    ///
+    /// Reads the source namespace's changeset, with any unfolded log entries merged in,
+    /// and persists it into `dest` under
+    /// `dest_namespace`, in a single write transaction on the destination. If the
+    /// destination namespace already holds a wallet, the copied changeset is merged into
+    /// it like any other persist. The source is left unchanged; follow up with a delete
+    /// of the source namespace to complete a move. Copying a namespace without a wallet
+    /// writes nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - Either namespace is invalid
+    /// - The source changeset cannot be read or deserialized
+    /// - `dest` is read-only, holds a wallet for a different network, or the write fails
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let source = RedbStore::open("shard_a.redb").unwrap();
+    /// let mut dest = RedbStore::open_or_create("shard_b.redb").unwrap();
+    /// source.copy_wallet_to("alice", &mut dest, "alice").unwrap();
+    /// ```
+    ///
+    pub fn copy_wallet_to(
+        &self,
+        namespace: &str,
+        dest: &mut RedbStore,
+        dest_namespace: &str,
+    ) -> Result<(), RedbError> {
+        validate_namespace(namespace)?;
+        validate_namespace(dest_namespace)?;
+
+        let mut source = self.clone();
+        source.namespace = Some(namespace.to_string());
+        let read_txn = source.db()?.begin_read()?;
+        let mut changeset = source.changeset_in(&read_txn)?.unwrap_or_default();
+        source.merge_log(&read_txn, &mut changeset)?;
+        drop(read_txn);
+        if changeset.is_empty() {
+            return Ok(());
+        }
+
+        let mut target = dest.clone();
+        target.namespace = Some(dest_namespace.to_string());
+        target.persist_changeset(&changeset)?;
+
+        Ok(())
+    }
+
+    /// Dump every raw key/value pair in the wallet table
+    ///
+    /// Intended for debugging and tooling. The result includes every entry in the table,
+    /// whatever its naming convention: the changeset, metadata such as the network, and
+    /// entries belonging to other namespaces sharing the table. Entries are returned in
+    /// key order from a single read transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// for (key, value) in store.iter_entries().unwrap() {
+    ///     println!("{}: {} bytes", key, value.len());
+    /// }
+    /// ```
+    ///
+    pub fn iter_entries(&self) -> Result<Vec<(String, Vec<u8>)>, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let table = read_txn.open_table(self.wallet_table())?;
+
+        let mut entries = Vec::new();
+        for entry in table.iter()? {
+            let (key, value) = entry?;
+            entries.push((key.value().to_string(), value.value().to_vec()));
+        }
+
+        Ok(entries)
+    }
+
+    /// Write a consistent copy of the wallet table to a new database file
+    ///
+    /// All entries of the wallet table are read in a single read transaction and written
+    /// to a fresh redb file at `dest_path`, so the copy reflects exactly one committed
+    /// state even if other handles keep writing. Unlike copying the file on disk, this
+    /// never captures a half-written commit. Other tables in the same file are not copied.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - `dest_path` already exists
+    /// - The wallet table cannot be read
+    /// - The backup database cannot be created or written
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// store.backup_to("wallet-backup.redb").unwrap();
+    /// ```
+    ///
+    pub fn backup_to<P>(&self, dest_path: P) -> Result<(), RedbError>
+    where
+        P: AsRef<Path>,
+    {
+        let read_txn = self.db()?.begin_read()?;
+        let source = read_txn.open_table(self.wallet_table())?;
+
+        let file = std::fs::OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create_new(true)
+            .open(dest_path.as_ref())?;
+        let backup = redb::Builder::new().create_file(file)?;
+
+        let write_txn = backup.begin_write()?;
+        {
+            let mut dest = write_txn.open_table(self.wallet_table())?;
+            for entry in source.iter()? {
+                let (key, value) = entry?;
+                dest.insert(key.value(), value.value())?;
+            }
+        }
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Export the wallet to a portable bundle archive
+    ///
+    /// A bundle is a `.tar` archive holding two JSON documents: `manifest.json`, a
+    /// [`BundleManifest`] describing the wallet, and `changeset.json`, the wallet
+    /// changeset. Unlike a `.redb` file it doesn't depend on redb's binary format, so it is
+    /// small, can be unpacked and inspected with standard tools, and can be imported by
+    /// other versions with [`RedbStore::import_bundle`]. Only the wallet is exported;
+    /// application metadata is not. The changeset and the manifest are read in a single
+    /// read transaction, so they always describe the same state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A file already exists at `path`
+    /// - The stored changeset cannot be read
+    /// - The bundle cannot be written
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    /// use std::path::Path;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// store.export_bundle(Path::new("wallet-support.tar")).unwrap();
+    /// ```
+    ///
+    #[cfg(feature = "bundle")]
+    pub fn export_bundle(&self, path: &Path) -> Result<(), RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let changeset = self.changeset_in(&read_txn)?.unwrap_or_default();
+        let changeset = self.complete_initialize(&read_txn, changeset)?;
+        let table = read_txn.open_table(self.wallet_table())?;
+        let as_secs = |time: Option<std::time::SystemTime>| {
+            time.and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_secs())
+        };
+        let manifest = BundleManifest {
+            schema_version: BUNDLE_SCHEMA_VERSION,
+            network: changeset.network.or(self.network_in(&table)?),
+            descriptor_fingerprint: Self::descriptor_fingerprint(&changeset),
+            created_at: as_secs(self.timestamp_in(&table, CREATED_AT_KEY)?),
+            last_persisted_at: as_secs(self.timestamp_in(&table, LAST_PERSISTED_KEY)?),
+            exported_at: as_secs(Some(std::time::SystemTime::now())).unwrap_or_default(),
+        };
+        drop(table);
+        drop(read_txn);
+
+        let entries = [
+            (
+                BUNDLE_MANIFEST_FILE,
+                serde_json::to_vec(&manifest).map_err(RedbError::Serialization)?,
+            ),
+            (
+                BUNDLE_CHANGESET_FILE,
+                serde_json::to_vec(&changeset).map_err(RedbError::Serialization)?,
+            ),
+        ];
+        let file = std::fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(path)?;
+        let mut archive = tar::Builder::new(std::io::BufWriter::new(file));
+        for (name, bytes) in entries {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(bytes.len() as u64);
+            header.set_mode(0o644);
+            header.set_mtime(manifest.exported_at);
+            header.set_cksum();
+            archive.append_data(&mut header, name, bytes.as_slice())?;
+        }
+        let writer = archive.into_inner()?;
+        let file = writer.into_inner().map_err(|e| e.into_error())?;
+        file.sync_all()?;
+
+        Ok(())
+    }
+
+    /// Create a new store at `redb_path` from a bundle written by
+    /// [`RedbStore::export_bundle`]
+    ///
+    /// The bundle's changeset, network and creation time are written in a single
+    /// transaction, to a `<redb_path>.import` file that is moved to `redb_path` once
+    /// complete, so a failed import never leaves a partial wallet at `redb_path`. The move
+    /// never replaces a file, so a wallet created at `redb_path` during the import, e.g.
+    /// by a concurrent import, is kept and this import fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - A file already exists at `redb_path`
+    /// - The bundle cannot be read or parsed, or lacks its manifest or changeset
+    /// - The bundle was written with a newer, unsupported format version, reported as
+    ///   [`RedbError::UnsupportedBundleVersion`]
+    /// - The store cannot be created
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    /// use std::path::Path;
+    ///
+    /// let store =
+    ///     RedbStore::import_bundle(Path::new("restored.redb"), Path::new("wallet-support.tar"))
+    ///         .unwrap();
+    /// ```
+    ///
+    #[cfg(feature = "bundle")]
+    pub fn import_bundle(redb_path: &Path, bundle_path: &Path) -> Result<Self, RedbError> {
+        let bundle = Self::read_bundle(bundle_path)?;
+
+        // An existing wallet is never replaced. Checked again when the import is moved into
+        // place, in case one appears in the meantime.
+        if redb_path.exists() {
+            return Err(RedbError::Io(std::io::Error::new(
+                std::io::ErrorKind::AlreadyExists,
+                format!("{} already exists", redb_path.display()),
+            )));
+        }
+
+        let mut import_path = redb_path.as_os_str().to_owned();
+        import_path.push(".import");
+        let import_path = PathBuf::from(import_path);
+        // Left behind by an interrupted import, and never moved into place
+        if import_path.exists() {
+            std::fs::remove_file(&import_path)?;
+        }
+        if let Err(e) = Self::write_bundle(&import_path, bundle) {
+            let _ = std::fs::remove_file(&import_path);
+            return Err(e);
+        }
+
+        if let Err(e) = move_no_replace(&import_path, redb_path) {
+            let _ = std::fs::remove_file(&import_path);
+            return Err(e.into());
+        }
+        Self::open(redb_path)
+    }
+
+    /// Read the manifest and changeset of the bundle archive at `path`
+    ///
+    /// The manifest's format version is checked before the changeset is parsed.
+    #[cfg(feature = "bundle")]
+    fn read_bundle(path: &Path) -> Result<Bundle, RedbError> {
+        let mut archive = tar::Archive::new(std::io::BufReader::new(std::fs::File::open(path)?));
+        let (mut manifest, mut changeset) = (None, None);
+        for entry in archive.entries()? {
+            let mut entry = entry?;
+            let name = entry.path()?.into_owned();
+            let slot = if name == Path::new(BUNDLE_MANIFEST_FILE) {
+                &mut manifest
+            } else if name == Path::new(BUNDLE_CHANGESET_FILE) {
+                &mut changeset
+            } else {
+                continue;
+            };
+            let mut bytes = Vec::new();
+            std::io::Read::read_to_end(&mut entry, &mut bytes)?;
+            *slot = Some(bytes);
+        }
+        let missing = |name: &str| {
+            RedbError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("bundle has no {}", name),
+            ))
+        };
+
+        let manifest = manifest.ok_or_else(|| missing(BUNDLE_MANIFEST_FILE))?;
+        let manifest: BundleManifest =
+            serde_json::from_slice(&manifest).map_err(RedbError::Deserialization)?;
+        if manifest.schema_version > BUNDLE_SCHEMA_VERSION {
+            return Err(RedbError::UnsupportedBundleVersion {
+                found: manifest.schema_version,
+                supported: BUNDLE_SCHEMA_VERSION,
+            });
+        }
+        let changeset = changeset.ok_or_else(|| missing(BUNDLE_CHANGESET_FILE))?;
+        let changeset = serde_json::from_slice(&changeset).map_err(RedbError::Deserialization)?;
+
+        Ok(Bundle {
+            manifest,
+            changeset,
+        })
+    }
+
+    /// Create a store at `path` holding the contents of `bundle`
+    #[cfg(feature = "bundle")]
+    fn write_bundle(path: &Path, bundle: Bundle) -> Result<(), RedbError> {
+        let store = Self::create(path)?;
+        let write_txn = store.db()?.begin_write()?;
+        {
+            let mut table = write_txn.open_table(store.wallet_table())?;
+            if let Some(network) = bundle.manifest.network {
+                let network_bytes =
+                    serde_json::to_vec(&network).map_err(RedbError::Serialization)?;
+                table.insert(
+                    store.scoped_key(NETWORK_KEY).as_str(),
+                    network_bytes.as_slice(),
+                )?;
+            }
+            if !bundle.changeset.is_empty() {
+                store.merge_into_table(&mut table, bundle.changeset)?;
+            }
+            if let Some(created_at) = bundle.manifest.created_at {
+                table.insert(
+                    store.scoped_key(CREATED_AT_KEY).as_str(),
+                    created_at.to_be_bytes().as_slice(),
+                )?;
+            }
+        }
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Write every wallet and its metadata in the table as one JSON document
+    ///
+    /// The backbone for dump/restore tooling. The document lists each namespace sharing
+    /// the wallet table, the un-namespaced wallet included, with its changeset and its
+    /// metadata, reserved entries such as timestamps and the active profile included.
+    /// Entries derived from the stored changeset, such as its hash and version, are left
+    /// out and recomputed by [`RedbStore::load_json`]. The changeset is the active
+    /// profile's, with the `wal` feature's unfolded entries merged in; other profiles and
+    /// checkpoints are not written. Everything is read in a single read transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The database cannot be read
+    /// - A stored changeset cannot be deserialized
+    /// - The document cannot be written
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallets.redb").unwrap();
+    /// store.dump_json(std::io::stdout()).unwrap();
+    /// ```
+    ///
+    pub fn dump_json(&self, writer: impl std::io::Write) -> Result<(), RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let table = read_txn.open_table(self.wallet_table())?;
+
+        let mut namespaces: BTreeMap<Option<String>, NamespaceDump> = BTreeMap::new();
+        for entry in table.iter()? {
+            let (key, value) = entry?;
+            let key = key.value();
+            let (namespace, local) = split_namespace(key);
+            let namespace = namespace.map(str::to_string);
+
+            if self.keys.is_changeset_key(key) {
+                namespaces
+                    .entry(namespace.clone())
+                    .or_insert_with(|| NamespaceDump::new(namespace));
+            } else if let Some(name) = local.strip_prefix(self.keys.metadata_prefix.as_str()) {
+                let dump = namespaces
+                    .entry(namespace.clone())
+                    .or_insert_with(|| NamespaceDump::new(namespace));
+                if !DERIVED_METADATA_KEYS.contains(&name) {
+                    dump.metadata
+                        .insert(name.to_string(), value.value().to_vec());
+                }
+            }
+        }
+
+        // Each namespace's wallet is its active profile's changeset
+        for dump in namespaces.values_mut() {
+            let mut target = self.clone();
+            target.namespace = dump.namespace.clone();
+            let key = target.changeset_key(&table)?;
+            dump.changeset = StoredChangeSet::read(&table, &key)?
+                .map(|stored| stored.decode(&self.deserialize_limits))
+                .transpose()
+                .map_err(RedbError::Deserialization)?;
+            #[cfg(feature = "wal")]
+            for entry in target.read_log(&read_txn)? {
+                dump.changeset
+                    .get_or_insert_with(ChangeSet::default)
+                    .merge(entry);
+            }
+        }
+
+        let dump = Dump {
+            schema_version: DUMP_SCHEMA_VERSION,
+            namespaces: namespaces.into_values().collect(),
+        };
+        serde_json::to_writer(writer, &dump).map_err(RedbError::Serialization)
+    }
+
+    /// Restore wallets and metadata from a document written by [`RedbStore::dump_json`]
+    ///
+    /// Intended for loading into a fresh store. Each namespace in the document has its
+    /// changeset and metadata written over whatever the store held under the same keys;
+    /// namespaces missing from the document are left alone. The changeset is written to
+    /// the namespace's active profile as recorded in the document. Everything is written
+    /// in a single transaction, so a failed load changes nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The store is read-only
+    /// - The document cannot be read or parsed
+    /// - The document was written with a newer, unsupported format version
+    /// - A namespace in the document is invalid
+    /// - The write transaction fails
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::create("restored.redb").unwrap();
+    /// store.load_json(std::io::stdin()).unwrap();
+    /// ```
+    ///
+    pub fn load_json(&mut self, reader: impl std::io::Read) -> Result<(), RedbError> {
+        self.check_writable()?;
+
+        let dump: Dump = serde_json::from_reader(reader).map_err(RedbError::Deserialization)?;
+        if dump.schema_version > DUMP_SCHEMA_VERSION {
+            return Err(RedbError::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("unsupported dump schema version {}", dump.schema_version),
+            )));
+        }
+        for namespace in dump
+            .namespaces
+            .iter()
+            .filter_map(|n| n.namespace.as_deref())
+        {
+            validate_namespace(namespace)?;
+        }
+
+        let mut write_txn = self.db()?.begin_write()?;
+        write_txn.set_durability(self.durability);
+        {
+            let mut table = write_txn.open_table(self.wallet_table())?;
+            for namespace in dump.namespaces {
+                let mut target = self.clone();
+                target.namespace = namespace.namespace;
+                let mut metadata = namespace.metadata;
+                // Recomputed for the changeset written here; older dumps include them
+                metadata.retain(|name, _| !DERIVED_METADATA_KEYS.contains(&name.as_str()));
+
+                // The active profile decides which key the changeset is written to
+                let active_key = target.scoped_key(ACTIVE_PROFILE_KEY);
+                match metadata.remove("active_profile") {
+                    Some(profile) => table.insert(active_key.as_str(), profile.as_slice())?,
+                    None => table.remove(active_key.as_str())?,
+                };
+                if let Some(changeset) = namespace.changeset {
+                    target.write_changeset(&mut table, &changeset)?;
+                }
+                // Written last so the dumped timestamps win
+                for (name, value) in metadata {
+                    let key = target.scoped_key(&format!("{}{}", METADATA_PREFIX, name));
+                    table.insert(key.as_str(), value.as_slice())?;
+                }
+            }
+        }
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Report the storage used by every wallet in the table
+    ///
+    /// Returns one [`WalletUsage`] per stored changeset, covering all namespaces sharing
+    /// this wallet table, in key order. The un-namespaced wallet is reported with an empty
+    /// namespace. `last_modified` is the time of the last persist that changed the
+    /// changeset, and is `None` for data written before timestamps were recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallets.redb").unwrap();
+    /// for usage in store.usage_report().unwrap() {
+    ///     println!("{:?}: {} bytes", usage.namespace, usage.bytes);
+    /// }
+    /// ```
+    ///
+    pub fn usage_report(&self) -> Result<Vec<WalletUsage>, RedbError> {
+        let entries = self.iter_entries()?;
+
+        let mut timestamps = std::collections::HashMap::new();
+        for (key, value) in &entries {
+            if let Some(namespace) = namespace_of(key, &self.keys.translate(LAST_PERSISTED_KEY)) {
+                if let Some(time) = decode_timestamp(value) {
+                    timestamps.insert(namespace, time);
+                }
+            }
+        }
+
+        // A segmented changeset is as large as the JSON its segments join into
+        let sizes: BTreeMap<&str, usize> = entries
+            .iter()
+            .map(|(key, value)| (key.as_str(), value.len()))
+            .collect();
+        let changeset_len =
+            |key: &str| match segment_keys(key).map(|key| sizes.get(key.as_str()).copied()) {
+                [Some(descriptors), Some(local_chain), Some(tx_graph), Some(indexer)] => {
+                    codec::joined_len([descriptors, local_chain, tx_graph, indexer])
+                }
+                [whole, ..] => whole.unwrap_or_default(),
+            };
+        Ok(entries
+            .iter()
+            .filter_map(|(key, _)| {
+                let namespace = namespace_of(key, &self.keys.changeset_key)?;
+                Some(WalletUsage {
+                    last_modified: timestamps.get(&namespace).copied(),
+                    namespace,
+                    bytes: changeset_len(key),
+                })
+            })
+            .collect())
+    }
+
+    /// Gather the store's metrics in one call
+    ///
+    /// Bundles what [`RedbStore::file_size`], [`RedbStore::last_persisted_at`] and
+    /// [`RedbStore::usage_report`] report, plus the size of the wallet table, for metrics
+    /// exporters. Everything but the file size is read in a single read transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file metadata or the database cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// let stats = store.stats().unwrap();
+    /// println!("wallet_file_bytes {}", stats.file_size);
+    /// println!("wallet_changeset_bytes {}", stats.changeset_bytes);
+    /// ```
+    ///
+    pub fn stats(&self) -> Result<RedbStoreStats, RedbError> {
+        let file_size = self.file_size()?;
+
+        let read_txn = self.db()?.begin_read()?;
+        let table = read_txn.open_table(self.wallet_table())?;
+        let changeset_bytes = StoredChangeSet::read(&table, &self.changeset_key(&table)?)?
+            .map_or(0, |stored| stored.len());
+        let last_persisted = table
+            .get(self.scoped_key(LAST_PERSISTED_KEY).as_str())?
+            .and_then(|value| decode_timestamp(value.value()));
+
+        // Every wallet sharing the table has a changeset key of its own
+        let mut namespace_count = 0;
+        for entry in table.iter()? {
+            let (key, _) = entry?;
+            if namespace_of(key.value(), &self.keys.changeset_key).is_some() {
+                namespace_count += 1;
+            }
+        }
+
+        Ok(RedbStoreStats {
+            file_size,
+            changeset_bytes,
+            entry_count: table.len()?,
+            namespace_count,
+            changeset_format_version: self.stored_changeset_version(&table)?,
+            last_persisted,
+        })
+    }
+
+    /// Close the store, making sure all committed data is durable
+    ///
+    /// Dropping a [`RedbStore`] closes the database silently. `close` instead calls
+    /// [`RedbStore::flush`], which makes anything still pending durable, and returns any
+    /// error from doing so before the database is dropped.
+    /// Consuming the store guarantees that no transaction is still open.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the final commit fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// store.close().unwrap();
+    /// ```
+    ///
+    pub fn close(self) -> Result<(), RedbError> {
+        self.flush()
+    }
+
+    /// Force everything committed so far onto disk
+    ///
+    /// Commits an empty write transaction with immediate durability, which fsyncs the
+    /// database file. Commits made with `Durability::Eventual` (see
+    /// [`RedbStore::set_durability`]) are not guaranteed to survive a crash until an
+    /// immediate commit follows them; `flush` provides that commit at a point of the
+    /// caller's choosing, without changing the store's durability setting. With the default
+    /// immediate durability every persist is already on disk, so `flush` only costs an
+    /// extra fsync.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the write transaction cannot be started or committed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb::Durability;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// store.set_durability(Durability::Eventual);
+    /// // ... persist many changesets ...
+    /// store.flush().unwrap();
+    /// ```
+    ///
+    pub fn flush(&self) -> Result<(), RedbError> {
+        let mut write_txn = self.db()?.begin_write()?;
+        write_txn.set_durability(redb::Durability::Immediate);
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Get the changeset from the database
+    ///
+    /// Internal method that retrieves the stored wallet changeset from the database.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(changeset))` if a changeset exists in the database
+    /// - `Ok(None)` if no changeset has been stored yet
+    /// - `Err(...)` if an error occurs during database access or deserialization
+    ///
+    #[cfg(test)]
+    fn get_changeset(&self) -> Result<Option<ChangeSet>, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        self.changeset_in(&read_txn)
+    }
+
+    /// Retrieve the stored changeset with the unfolded log entries merged in
+    ///
+    /// This is the wallet `initialize` would load, without the descriptor check. Returns
+    /// `None` if neither a changeset nor log entries are stored.
+    fn get_changeset_with_log(&self) -> Result<Option<ChangeSet>, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let stored = self.changeset_in(&read_txn)?;
+        let mut unfolded = ChangeSet::default();
+        self.merge_log(&read_txn, &mut unfolded)?;
+        if unfolded.is_empty() {
+            return Ok(stored);
+        }
+        let mut changeset = stored.unwrap_or_default();
+        changeset.merge(unfolded);
+        Ok(Some(changeset))
+    }
+
+    /// Retrieve the stored changeset as of an open read transaction
+    fn changeset_in(
+        &self,
+        read_txn: &redb::ReadTransaction,
+    ) -> Result<Option<ChangeSet>, RedbError> {
+        #[cfg(feature = "tracing")]
+        let _span = tracing::debug_span!("get_changeset", namespace = ?self.namespace).entered();
+
+        let table = read_txn.open_table(self.wallet_table())?;
+        self.check_changeset_version(&table)?;
+
+        let key = self.changeset_key(&table)?;
+        let stored = StoredChangeSet::read(&table, &key)?;
+        match stored {
+            Some(stored) => {
+                trace_event!(debug, bytes = stored.len(), "read changeset");
+                // Decoded straight from redb's value guards, without copying the bytes
+                let changeset = stored
+                    .decode(&self.deserialize_limits)
+                    .map_err(RedbError::Deserialization)?;
+                Ok(Some(changeset))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Get the JSON of the stored changeset, if any, joined from its segments
+    #[cfg(test)]
+    fn get_changeset_bytes(&self) -> Result<Option<Vec<u8>>, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        self.changeset_bytes_in(&read_txn)
+    }
+
+    /// Get the JSON of the stored changeset as of an open read transaction
+    fn changeset_bytes_in(
+        &self,
+        read_txn: &redb::ReadTransaction,
+    ) -> Result<Option<Vec<u8>>, RedbError> {
+        let table = read_txn.open_table(self.wallet_table())?;
+
+        let key = self.changeset_key(&table)?;
+        let stored = StoredChangeSet::read(&table, &key)?;
+        stored
+            .map(|stored| stored.to_json().map_err(RedbError::Deserialization))
+            .transpose()
+    }
+
+    /// The key of the stored changeset, which depends on the active profile
+    ///
+    /// The key holds the changeset's descriptors segment; see [`StoredChangeSet`].
+    fn changeset_key<T>(&self, table: &T) -> Result<String, RedbError>
+    where
+        T: ReadableTable<&'static str, &'static [u8]>,
+    {
+        self.profile_key(table, CHANGESET_KEY, CHANGESET_KEY)
+    }
+
+    /// The key flagging that the active profile holds a changeset
+    ///
+    /// The flag is set whenever a changeset is written and removed along with it, so
+    /// [`RedbStore::has_wallet`] can answer without reading the changeset. Files written
+    /// before the flag existed don't have it, so a missing flag is not conclusive.
+    fn has_data_key<T>(&self, table: &T) -> Result<String, RedbError>
+    where
+        T: ReadableTable<&'static str, &'static [u8]>,
+    {
+        self.profile_key(table, HAS_DATA_KEY, "has_data")
+    }
+
+    /// The key holding the SHA-256 of the active profile's stored changeset
+    fn content_hash_key<T>(&self, table: &T) -> Result<String, RedbError>
+    where
+        T: ReadableTable<&'static str, &'static [u8]>,
+    {
+        self.profile_key(table, CONTENT_HASH_KEY, "content_hash")
+    }
+
+    /// The key recording the format version of the active profile's changeset
+    fn changeset_version_key<T>(&self, table: &T) -> Result<String, RedbError>
+    where
+        T: ReadableTable<&'static str, &'static [u8]>,
+    {
+        self.profile_key(table, CHANGESET_VERSION_KEY, "changeset_version")
+    }
+
+    /// The key holding the id of the active profile's most recent checkpoint
+    ///
+    /// Ids are never reused, even after the newest checkpoint is deleted, so the counter
+    /// is kept apart from the checkpoints themselves.
+    fn last_checkpoint_key<T>(&self, table: &T) -> Result<String, RedbError>
+    where
+        T: ReadableTable<&'static str, &'static [u8]>,
+    {
+        self.profile_key(table, LAST_CHECKPOINT_KEY, "last_checkpoint")
+    }
+
+    /// The format version recorded with the stored changeset, `1` if none is recorded
+    fn stored_changeset_version<T>(&self, table: &T) -> Result<u32, RedbError>
+    where
+        T: ReadableTable<&'static str, &'static [u8]>,
+    {
+        Ok(table
+            .get(self.changeset_version_key(table)?.as_str())?
+            .and_then(|value| <[u8; 4]>::try_from(value.value()).ok())
+            .map_or(1, u32::from_be_bytes))
+    }
+
+    /// Refuse a changeset written in a format newer than this version can read
+    ///
+    /// Changesets written before the format was recorded are taken to be version 1.
+    fn check_changeset_version<T>(&self, table: &T) -> Result<(), RedbError>
+    where
+        T: ReadableTable<&'static str, &'static [u8]>,
+    {
+        let found = self.stored_changeset_version(table)?;
+        if found > CHANGESET_FORMAT_VERSION {
+            return Err(RedbError::UnsupportedChangeSetVersion {
+                found,
+                supported: CHANGESET_FORMAT_VERSION,
+            });
+        }
+        Ok(())
+    }
+
+    /// The scoped key `default` of the default profile, or `profile::{name}::{suffix}`
+    /// of the active one
+    fn profile_key<T>(&self, table: &T, default: &str, suffix: &str) -> Result<String, RedbError>
+    where
+        T: ReadableTable<&'static str, &'static [u8]>,
+    {
+        let profile = table.get(self.scoped_key(ACTIVE_PROFILE_KEY).as_str())?;
+        Ok(match profile {
+            Some(name) => self.scoped_key(&format!(
+                "{}{}::{}",
+                PROFILE_PREFIX,
+                String::from_utf8_lossy(name.value()),
+                suffix
+            )),
+            None => self.scoped_key(default),
+        })
+    }
+
+    /// Remove the active profile's changeset and its flag, returning the changeset's JSON
+    ///
+    /// Unfolded log entries are removed from `write_txn` too, so they can't be merged
+    /// back into the next load.
+    fn remove_changeset(
+        &self,
+        write_txn: &redb::WriteTransaction,
+        table: &mut redb::Table<&str, &[u8]>,
+    ) -> Result<Option<Vec<u8>>, RedbError> {
+        #[cfg(feature = "wal")]
+        self.clear_log(write_txn)?;
+        #[cfg(not(feature = "wal"))]
+        let _ = write_txn;
+        let flag_key = self.has_data_key(table)?;
+        table.remove(flag_key.as_str())?;
+        let hash_key = self.content_hash_key(table)?;
+        table.remove(hash_key.as_str())?;
+        let format_key = self.changeset_version_key(table)?;
+        table.remove(format_key.as_str())?;
+
+        // An unreadable changeset is still returned, as far as its segments can be joined
+        let key = self.changeset_key(table)?;
+        let bytes = StoredChangeSet::read(table, &key)?.map(|stored| match stored.to_json() {
+            Ok(json) => json,
+            Err(_) => stored
+                .segments()
+                .map_or_else(Vec::new, |segments| segments.concat()),
+        });
+        for key in segment_keys(&key) {
+            table.remove(key.as_str())?;
+        }
+
+        // Unindex the wallet; an unreadable changeset's fingerprint can't be known
+        let summary = bytes
+            .as_deref()
+            .and_then(|bytes| self.decode::<ChangeSetSummary>(bytes).ok());
+        if let Some(fingerprint) = summary.and_then(|summary| {
+            fingerprint_descriptors(
+                summary.descriptor.as_ref(),
+                summary.change_descriptor.as_ref(),
+            )
+        }) {
+            table.remove(self.fingerprint_index_key(&fingerprint).as_str())?;
+        }
+        Ok(bytes)
+    }
+
+    /// Load the changeset for `initialize`
+    ///
+    /// Internal method shared by the sync and async persister implementations. Everything
+    /// `initialize` reads comes from a single read transaction, so it sees one committed
+    /// state even when a writer updates several tables at once. In recoverable mode an
+    /// unreadable changeset is moved to the corrupt key and an empty changeset is
+    /// returned instead of the deserialization error.
+    ///
+    fn initialize_changeset(&mut self) -> Result<ChangeSet, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        match self.changeset_in(&read_txn) {
+            Ok(changeset) => self.complete_initialize(&read_txn, changeset.unwrap_or_default()),
+            Err(RedbError::Deserialization(e)) if self.recoverable => {
+                drop(read_txn);
+                trace_event!(warn, error = %e, "quarantining unreadable changeset");
+                self.quarantine_changeset(e)?;
+                Ok(ChangeSet::default())
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Apply unfolded log entries and the descriptor check to a loaded changeset
+    ///
+    /// The log is read from `read_txn`, the transaction the changeset was read from, so a
+    /// concurrent `fold_log` can't make entries show up twice or not at all.
+    fn complete_initialize(
+        &self,
+        read_txn: &redb::ReadTransaction,
+        mut changeset: ChangeSet,
+    ) -> Result<ChangeSet, RedbError> {
+        self.merge_log(read_txn, &mut changeset)?;
+        self.check_descriptors(&changeset)?;
+        Ok(changeset)
+    }
+
+    /// Fold this store's unfolded log entries in front of `changeset` within `write_txn`
+    ///
+    /// The entries are removed in the same transaction, so the next load can't merge them
+    /// over the newer `changeset`. Returns whether there were any.
+    fn fold_log_before(
+        &self,
+        write_txn: &redb::WriteTransaction,
+        changeset: &mut ChangeSet,
+    ) -> Result<bool, RedbError> {
+        #[cfg(feature = "wal")]
+        if let Some(mut folded) = self.take_log(write_txn)? {
+            folded.merge(std::mem::take(changeset));
+            *changeset = folded;
+            return Ok(true);
+        }
+        #[cfg(not(feature = "wal"))]
+        let _ = (write_txn, changeset);
+        Ok(false)
+    }
+
+    /// Merge this store's unfolded log entries as of `read_txn` into `changeset`
+    fn merge_log(
+        &self,
+        read_txn: &redb::ReadTransaction,
+        changeset: &mut ChangeSet,
+    ) -> Result<(), RedbError> {
+        #[cfg(feature = "wal")]
+        for entry in self.read_log(read_txn)? {
+            changeset.merge(entry);
+        }
+        #[cfg(not(feature = "wal"))]
+        let _ = (read_txn, changeset);
+        Ok(())
+    }
+
+    /// Move the unreadable changeset to the corrupt key and record the recovery
+    ///
+    /// A read-only store can't move it, so `error` is returned instead.
+    fn quarantine_changeset(&mut self, error: serde_json::Error) -> Result<(), RedbError> {
+        if self.check_writable().is_err() {
+            return Err(RedbError::Deserialization(error));
+        }
+        let corrupt_key = self.scoped_key(CORRUPT_CHANGESET_KEY);
+
+        let write_txn = self.db()?.begin_write()?;
+        let corrupt_len = {
+            let mut table = write_txn.open_table(self.wallet_table())?;
+            let bytes = self
+                .remove_changeset(&write_txn, &mut table)?
+                .unwrap_or_default();
+            table.insert(corrupt_key.as_str(), bytes.as_slice())?;
+            bytes.len()
+        };
+        write_txn.commit()?;
+
+        self.last_recovery = Some(RecoveryInfo {
+            error: error.to_string(),
+            corrupt_key,
+            corrupt_len,
+        });
+        Ok(())
+    }
+
+    /// Merge a changeset into the one stored in an open table
+    ///
+    /// Internal method that reads this store's changeset from `table`, merges `changeset`
+    /// into it and writes the result back, all within the caller's write transaction.
+    /// Nothing is written if the merged result exceeds the configured size limit.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(Some(merged))` with the merged changeset and its new version if the stored
+    ///   bytes changed
+    /// - `Ok(None)` if the merged result is identical to what was already stored
+    /// - `Err(...)` if the merged result is too large, or an error occurs during
+    ///   (de)serialization or database access
+    ///
+    fn merge_into_table(
+        &self,
+        table: &mut redb::Table<&str, &[u8]>,
+        changeset: ChangeSet,
+    ) -> Result<Option<Merged>, RedbError> {
+        Ok(self
+            .merge_into_table_sized(table, changeset)?
+            .map(|(merged, _)| merged))
+    }
+
+    /// Like [`RedbStore::merge_into_table`], also returning the size of the written bytes
+    fn merge_into_table_sized(
+        &self,
+        table: &mut redb::Table<&str, &[u8]>,
+        changeset: ChangeSet,
+    ) -> Result<Option<(Merged, usize)>, RedbError> {
+        // Merging into a newer format would drop whatever this version doesn't know about
+        self.check_changeset_version(table)?;
+
+        let key = self.changeset_key(table)?;
+        let mut replaced = None;
+        let (final_changeset, measured) = {
+            // Read the stored segments in place, without copying them; they are released
+            // before the write
+            let stored = StoredChangeSet::read(table, &key)?;
+
+            // Merge with existing or use the new one
+            let final_changeset = match &stored {
+                Some(stored) => {
+                    let mut existing = stored
+                        .decode(&self.deserialize_limits)
+                        .map_err(RedbError::Deserialization)?;
+                    match check_same_descriptors(&existing, &changeset) {
+                        Ok(()) => {
+                            existing.merge(changeset);
+                            existing
+                        }
+                        // A wallet with other descriptors replaces the stored one
+                        Err(_) if self.allow_descriptor_change => {
+                            trace_event!(info, "replacing stored wallet with new descriptors");
+                            replaced = Self::descriptor_fingerprint(&existing);
+                            changeset
+                        }
+                        Err(e) => return Err(e),
+                    }
+                }
+                None => changeset,
+            };
+
+            // Size each serialized segment and compare it with the stored one without
+            // buffering it; the segments that changed are serialized again straight into
+            // the database. A changeset stored as a single value is rewritten whole. The
+            // hash is recorded for `health_check` only: the comparison doesn't rely on it,
+            // as writes made through `database()` leave it stale.
+            let stored_segments = stored.as_ref().and_then(StoredChangeSet::segments);
+            let measured = self.measure_changeset(&final_changeset, stored_segments)?;
+            (final_changeset, measured)
+        };
+        let len = measured.len();
+        let unchanged = measured.unchanged.iter().all(|unchanged| *unchanged);
+        trace_event!(debug, bytes = len, unchanged, "merged changeset");
+        if unchanged {
+            return Ok(None);
+        }
+
+        if let Some(limit) = self.max_changeset_bytes {
+            if len > limit {
+                return Err(RedbError::TooLarge { size: len, limit });
+            }
+        }
+
+        if let Some(fingerprint) = replaced {
+            table.remove(self.fingerprint_index_key(&fingerprint).as_str())?;
+        }
+        let version = self.write_changeset_streamed(table, &final_changeset, &measured)?;
+        let merged = Merged {
+            changeset: final_changeset,
+            version,
+        };
+        Ok(Some((merged, len)))
+    }
+
+    /// Serialize each segment of a changeset in this store's JSON format without keeping
+    /// the bytes, comparing it with the stored one in `stored`
+    fn measure_changeset(
+        &self,
+        changeset: &ChangeSet,
+        stored: Option<[&[u8]; 4]>,
+    ) -> Result<Measured, RedbError> {
+        let mut engine = sha256::Hash::engine();
+        let mut lens = [0; 4];
+        let mut unchanged = [false; 4];
+        for index in 0..codec::SEGMENTS.len() {
+            let stored = stored.map(|segments| segments[index]);
+            let mut writer = MeasuringWriter {
+                engine: &mut engine,
+                len: 0,
+                stored,
+                matches: stored.is_some(),
+            };
+            codec::encode_segment_to(changeset, index, self.json_pretty, &mut writer)
+                .map_err(RedbError::Serialization)?;
+            lens[index] = writer.len;
+            unchanged[index] =
+                writer.matches && stored.is_some_and(|stored| stored.len() == writer.len);
+        }
+        Ok(Measured {
+            lens,
+            unchanged,
+            hash: sha256::Hash::from_engine(engine),
+        })
+    }
+
+    /// Serialize the changed segments of a changeset straight into space reserved in the
+    /// table, then write the metadata tracking it
+    ///
+    /// `measured` must come from [`RedbStore::measure_changeset`] for the same changeset.
+    /// The serialized segments never exist outside the database's own pages. Returns the
+    /// new version of the stored changeset.
+    fn write_changeset_streamed(
+        &self,
+        table: &mut redb::Table<&str, &[u8]>,
+        changeset: &ChangeSet,
+        measured: &Measured,
+    ) -> Result<u64, RedbError> {
+        let keys = segment_keys(&self.changeset_key(table)?);
+        for (index, key) in keys.iter().enumerate() {
+            if measured.unchanged[index] {
+                continue;
+            }
+            let len = measured.lens[index];
+            let value_len = u32::try_from(len).map_err(|_| RedbError::TooLarge {
+                size: len,
+                limit: u32::MAX as usize,
+            })?;
+            let mut value = table.insert_reserve(key.as_str(), value_len)?;
+            codec::encode_segment_to(changeset, index, self.json_pretty, value.as_mut())
+                .map_err(RedbError::Serialization)?;
+        }
+        self.write_changeset_meta(table, changeset, measured.hash)
+    }
+
+    /// Write a changeset over the stored one, along with the metadata tracking it
+    ///
+    /// Returns the new version of the stored changeset.
+    fn write_changeset(
+        &self,
+        table: &mut redb::Table<&str, &[u8]>,
+        changeset: &ChangeSet,
+    ) -> Result<u64, RedbError> {
+        let measured = self.measure_changeset(changeset, None)?;
+        self.write_changeset_streamed(table, changeset, &measured)
+    }
+
+    /// Write the metadata tracking a changeset that was just written
+    ///
+    /// Records the hash, write time and version, and indexes the wallet by its descriptors.
+    /// Returns the new version.
+    fn write_changeset_meta(
+        &self,
+        table: &mut redb::Table<&str, &[u8]>,
+        changeset: &ChangeSet,
+        hash: sha256::Hash,
+    ) -> Result<u64, RedbError> {
+        let flag_key = self.has_data_key(table)?;
+        table.insert(flag_key.as_str(), [1].as_slice())?;
+        let hash_key = self.content_hash_key(table)?;
+        table.insert(hash_key.as_str(), hash.as_byte_array().as_slice())?;
+        let format_key = self.changeset_version_key(table)?;
+        table.insert(
+            format_key.as_str(),
+            CHANGESET_FORMAT_VERSION.to_be_bytes().as_slice(),
+        )?;
+
+        // Record when the changeset was written, in seconds since the Unix epoch
+        let now = unix_now();
+        table.insert(
+            self.scoped_key(LAST_PERSISTED_KEY).as_str(),
+            now.to_be_bytes().as_slice(),
+        )?;
+        table.insert(
+            self.scoped_key(LAST_ACCESSED_KEY).as_str(),
+            now.to_be_bytes().as_slice(),
+        )?;
+        // Stores record their creation time when created; this covers files written by
+        // older versions that didn't
+        let created_key = self.scoped_key(CREATED_AT_KEY);
+        if table.get(created_key.as_str())?.is_none() {
+            table.insert(created_key.as_str(), now.to_be_bytes().as_slice())?;
+        }
+        let version_key = self.scoped_key(VERSION_KEY);
+        let version = read_version(table, &version_key)? + 1;
+        table.insert(version_key.as_str(), version.to_be_bytes().as_slice())?;
+
+        // Index the wallet by its descriptors once it has them
+        if let Some(fingerprint) = Self::descriptor_fingerprint(changeset) {
+            let index_key = self.fingerprint_index_key(&fingerprint);
+            table.insert(index_key.as_str(), [].as_slice())?;
+        }
+
+        Ok(version)
+    }
+
+    /// Merge a changeset into the stored one and persist the result
+    ///
+    /// Internal method shared by the sync and async persister implementations.
+    /// Rejects changesets whose network disagrees with the network recorded for this store.
+    /// The read, merge and write happen in a single write transaction.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(true)` if the stored changeset changed and was committed
+    /// - `Ok(false)` if the changeset was empty or left the stored bytes unchanged
+    /// - `Err(...)` if an error occurs during serialization or database access
+    ///
+    fn persist_changeset(&self, changeset: &ChangeSet) -> Result<bool, RedbError> {
+        // Skip if changeset is empty
+        if changeset.is_empty() {
+            return Ok(false);
+        }
+
+        #[cfg(feature = "tracing")]
+        let _span =
+            tracing::debug_span!("persist_changeset", namespace = ?self.namespace).entered();
+
+        self.check_writable()?;
+
+        // Reject changesets for a different network before merging
+        self.check_network(changeset)?;
+
+        let (durability, flushes) = self.persist_durability();
+        let mut write_txn = self.db()?.begin_write()?;
+        write_txn.set_durability(durability);
+        trace_event!(debug, "began write transaction");
+        let mut changeset = changeset.clone();
+        let folded_log = self.fold_log_before(&write_txn, &mut changeset)?;
+        let merged = {
+            let mut table = write_txn.open_table(self.wallet_table())?;
+            self.merge_into_table_sized(&mut table, changeset)?
+        };
+
+        let Some((merged, _bytes)) = merged else {
+            if folded_log {
+                // The stored changeset already held the log entries; commit to remove them
+                write_txn.commit()?;
+                self.persist_committed(flushes);
+            } else {
+                write_txn.abort()?;
+                trace_event!(debug, "nothing changed, aborted write transaction");
+            }
+            return Ok(false);
+        };
+        #[cfg(test)]
+        if let Some(hook) = &self.before_commit {
+            hook();
+        }
+        write_txn.commit()?;
+        trace_event!(debug, bytes = _bytes, "committed changeset");
+        self.persist_committed(flushes);
+
+        // Notify only once the data is committed
+        self.notify_persisted(&merged);
+
+        Ok(true)
+    }
+
+    /// Drop old, fully spent transactions from the stored changeset
+    ///
+    /// Long-running wallets keep every transaction they have ever seen. This removes the
+    /// ones that can no longer affect balances or UTXOs: transactions confirmed below
+    /// `keep_after_height` whose outputs are all spent by confirmed transactions. A
+    /// transaction is only dropped together with the transactions it spends from, so no
+    /// spent output ever reappears as unspent. Unconfirmed transactions, transactions with
+    /// unspent outputs, and anything confirmed at or after `keep_after_height` are kept.
+    ///
+    /// Dropped transactions no longer show up in the wallet's history, and a later full
+    /// scan will add them back. The space is only returned to the file system by
+    /// [`RedbStore::compact`]. With the `wal` feature, the log is folded in the same
+    /// transaction, so no pruned transaction survives in it.
+    ///
+    /// # Returns
+    ///
+    /// The number of bytes the stored changeset shrank by, `0` if nothing was dropped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store is read-only, or if the changeset cannot be read,
+    /// deserialized or written back.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open("wallet.redb").unwrap();
+    /// let reclaimed = store.prune(800_000).unwrap();
+    /// println!("Pruned {} bytes", reclaimed);
+    /// ```
+    ///
+    pub fn prune(&mut self, keep_after_height: u32) -> Result<usize, RedbError> {
+        self.check_writable()?;
+
+        let mut write_txn = self.db()?.begin_write()?;
+        write_txn.set_durability(self.durability);
+        // Fold the log first, so pruned transactions can't come back from it
+        #[cfg(feature = "wal")]
+        let log = self.take_log(&write_txn)?;
+        #[cfg(not(feature = "wal"))]
+        let log: Option<ChangeSet> = None;
+        let reclaimed = {
+            let mut table = write_txn.open_table(self.wallet_table())?;
+            let key = self.changeset_key(&table)?;
+            let stored = match StoredChangeSet::read(&table, &key)? {
+                Some(stored) => Some((
+                    stored.len(),
+                    stored
+                        .decode(&self.deserialize_limits)
+                        .map_err(RedbError::Deserialization)?,
+                )),
+                None => None,
+            };
+            let folded = log.is_some();
+            let stored = match (stored, log) {
+                (Some((old_len, mut changeset)), Some(log)) => {
+                    changeset.merge(log);
+                    Some((old_len, changeset))
+                }
+                (None, Some(log)) => Some((0, log)),
+                (stored, None) => stored,
+            };
+
+            match stored {
+                Some((old_len, mut changeset)) => {
+                    let pruned = prune_tx_graph(&mut changeset.tx_graph, keep_after_height);
+                    if pruned > 0 || folded {
+                        let measured = self.measure_changeset(&changeset, None)?;
+                        self.write_changeset_streamed(&mut table, &changeset, &measured)?;
+                        Some(old_len.saturating_sub(measured.len()))
+                    } else {
+                        None
+                    }
+                }
+                None => None,
+            }
+        };
+
+        match reclaimed {
+            Some(reclaimed) => {
+                write_txn.commit()?;
+                Ok(reclaimed)
+            }
+            None => {
+                write_txn.abort()?;
+                Ok(0)
+            }
+        }
+    }
+
+    /// Save a copy of the stored changeset as a checkpoint
+    ///
+    /// Checkpoints let the wallet be rewound with [`RedbStore::reset_to_checkpoint`], e.g.
+    /// after a bad chain update was persisted. Each one holds a full copy of the active
+    /// profile's changeset bytes and the time it was taken, under a `checkpoint::` key in
+    /// the wallet table. Checkpoints belong to the active profile and are numbered from
+    /// `1` in the order they are taken; an id is never reused, even once its checkpoint
+    /// is deleted. The oldest ones beyond
+    /// [`RedbStore::set_max_checkpoints`] are deleted; others stay until
+    /// [`RedbStore::delete_checkpoint`] is called. With the `wal` feature, the log is
+    /// folded first, so the checkpoint holds every appended changeset.
+    ///
+    /// # Returns
+    ///
+    /// The id of the new checkpoint.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store is read-only or a write transaction fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open("wallet.redb").unwrap();
+    /// let checkpoint = store.create_checkpoint().unwrap();
+    /// // ... apply a chain update ...
+    /// store.reset_to_checkpoint(checkpoint).unwrap();
+    /// ```
+    ///
+    pub fn create_checkpoint(&mut self) -> Result<u64, RedbError> {
+        self.check_writable()?;
+        #[cfg(feature = "wal")]
+        self.fold_log()?;
+
+        let mut write_txn = self.db()?.begin_write()?;
+        write_txn.set_durability(self.durability);
+        let id = {
+            let mut table = write_txn.open_table(self.wallet_table())?;
+            let (start, end) = self.checkpoint_range(&table)?;
+            // Files written before the counter existed only have their checkpoints to go by
+            let newest = match table.range(start.as_str()..end.as_str())?.next_back() {
+                Some(entry) => {
+                    let (key, _) = entry?;
+                    key.value()
+                        .strip_prefix(start.as_str())
+                        .and_then(|id| id.parse::<u64>().ok())
+                        .unwrap_or_default()
+                }
+                None => 0,
+            };
+            let counter_key = self.last_checkpoint_key(&table)?;
+            let id = read_version(&table, &counter_key)?.max(newest) + 1;
+            table.insert(counter_key.as_str(), id.to_be_bytes().as_slice())?;
+
+            let mut value = unix_now().to_be_bytes().to_vec();
+            if let Some(stored) = StoredChangeSet::read(&table, &self.changeset_key(&table)?)? {
+                value.extend(stored.to_json().map_err(RedbError::Deserialization)?);
+            }
+            table.insert(format!("{}{:020}", start, id).as_str(), value.as_slice())?;
+
+            if let Some(max) = self.max_checkpoints {
+                let count = table.range(start.as_str()..end.as_str())?.count();
+                let mut excess = count.saturating_sub(max);
+                table.retain_in(start.as_str()..end.as_str(), |_, _| {
+                    // Keys sort oldest first
+                    if excess == 0 {
+                        return true;
+                    }
+                    excess -= 1;
+                    false
+                })?;
+            }
+            id
+        };
+        write_txn.commit()?;
+
+        Ok(id)
+    }
+
+    /// Delete a checkpoint of the active profile
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No checkpoint with `id` exists
+    /// - The store is read-only or the write fails
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open("wallet.redb").unwrap();
+    /// for checkpoint in store.list_checkpoints().unwrap() {
+    ///     store.delete_checkpoint(checkpoint.id).unwrap();
+    /// }
+    /// ```
+    ///
+    pub fn delete_checkpoint(&mut self, id: u64) -> Result<(), RedbError> {
+        self.check_writable()?;
+
+        let mut write_txn = self.db()?.begin_write()?;
+        write_txn.set_durability(self.durability);
+        let removed = {
+            let mut table = write_txn.open_table(self.wallet_table())?;
+            let (start, _) = self.checkpoint_range(&table)?;
+            let removed = table
+                .remove(format!("{}{:020}", start, id).as_str())?
+                .is_some();
+            removed
+        };
+
+        if !removed {
+            write_txn.abort()?;
+            return Err(RedbError::CheckpointNotFound(id));
+        }
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// List the checkpoints taken with [`RedbStore::create_checkpoint`], oldest first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// for checkpoint in store.list_checkpoints().unwrap() {
+    ///     println!("#{} taken at {:?}", checkpoint.id, checkpoint.created_at);
+    /// }
+    /// ```
+    ///
+    pub fn list_checkpoints(&self) -> Result<Vec<CheckpointInfo>, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let table = read_txn.open_table(self.wallet_table())?;
+        let (start, end) = self.checkpoint_range(&table)?;
+
+        let mut checkpoints = Vec::new();
+        for entry in table.range(start.as_str()..end.as_str())? {
+            let (key, value) = entry?;
+            let id = key.value().strip_prefix(start.as_str()).unwrap_or_default();
+            let (Ok(id), Some(created_at)) = (
+                id.parse::<u64>(),
+                value.value().get(..8).and_then(decode_timestamp),
+            ) else {
+                continue;
+            };
+            checkpoints.push(CheckpointInfo {
+                id,
+                created_at,
+                bytes: value.value().len() - 8,
+            });
+        }
+
+        Ok(checkpoints)
+    }
+
+    /// Restore the stored changeset to a checkpoint
+    ///
+    /// Replaces the active profile's changeset with the copy saved by
+    /// [`RedbStore::create_checkpoint`]; everything persisted since is discarded. The
+    /// checkpoint itself is kept, so it can be returned to again. A wallet loaded before
+    /// the reset must be loaded again to see the restored state.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - No checkpoint with `id` exists
+    /// - The store is read-only
+    /// - The saved changeset cannot be deserialized or the write fails
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open("wallet.redb").unwrap();
+    /// if let Some(last) = store.list_checkpoints().unwrap().last() {
+    ///     store.reset_to_checkpoint(last.id).unwrap();
+    /// }
+    /// ```
+    ///
+    pub fn reset_to_checkpoint(&mut self, id: u64) -> Result<(), RedbError> {
+        self.check_writable()?;
+
+        let mut write_txn = self.db()?.begin_write()?;
+        write_txn.set_durability(self.durability);
+        let restored = {
+            let mut table = write_txn.open_table(self.wallet_table())?;
+            let (start, _) = self.checkpoint_range(&table)?;
+            let saved = table
+                .get(format!("{}{:020}", start, id).as_str())?
+                .map(|value| value.value().get(8..).unwrap_or_default().to_vec());
+
+            match saved {
+                Some(bytes) if bytes.is_empty() => {
+                    self.remove_changeset(&write_txn, &mut table)?;
+                    true
+                }
+                Some(bytes) => {
+                    let changeset: ChangeSet =
+                        self.decode(&bytes).map_err(RedbError::Deserialization)?;
+                    #[cfg(feature = "wal")]
+                    self.clear_log(&write_txn)?;
+                    self.write_changeset(&mut table, &changeset)?;
+                    true
+                }
+                None => false,
+            }
+        };
+
+        if !restored {
+            write_txn.abort()?;
+            return Err(RedbError::CheckpointNotFound(id));
+        }
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// The key range holding the active profile's checkpoints
+    ///
+    /// The end bound is the prefix with its final `:` replaced by the next character, `;`.
+    fn checkpoint_range<T>(&self, table: &T) -> Result<(String, String), RedbError>
+    where
+        T: ReadableTable<&'static str, &'static [u8]>,
+    {
+        let start = self.profile_key(table, CHECKPOINT_PREFIX, CHECKPOINT_PREFIX)?;
+        let end = format!("{};", &start[..start.len() - 1]);
+        Ok((start, end))
+    }
+
+    /// Get the version of the stored changeset
+    ///
+    /// The version starts at `0` and is incremented by every commit that changes the
+    /// stored changeset, so it can be used with [`RedbStore::persist_if_version`] for
+    /// optimistic concurrency.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// println!("Changeset version {}", store.version().unwrap());
+    /// ```
+    ///
+    pub fn version(&self) -> Result<u64, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let table = read_txn.open_table(self.wallet_table())?;
+        read_version(&table, &self.scoped_key(VERSION_KEY))
+    }
+
+    /// Persist a changeset only if the stored changeset is still at `expected`
+    ///
+    /// A compare-and-swap for writers sharing a file: read [`RedbStore::version`] along
+    /// with the changeset, compute the update, then persist it with the version that was
+    /// read. If another writer committed in between, nothing is written and
+    /// [`RedbError::VersionConflict`] is returned, so the update can be recomputed from
+    /// fresh data. The check and the write happen in the same write transaction.
+    ///
+    /// # Returns
+    ///
+    /// The version after the call: `expected + 1` if the stored changeset changed, or
+    /// `expected` if there was nothing to write.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The stored version is not `expected`
+    /// - The changeset's network does not match the store's network
+    /// - Serialization or database access fails
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::ChangeSet;
+    /// use redb_wallet_storage::{RedbError, RedbStore};
+    ///
+    /// let mut store = RedbStore::open("wallet.redb").unwrap();
+    /// let version = store.version().unwrap();
+    /// match store.persist_if_version(version, &ChangeSet::default()) {
+    ///     Ok(new_version) => println!("Now at version {}", new_version),
+    ///     Err(RedbError::VersionConflict { actual, .. }) => {
+    ///         println!("Someone else wrote version {}, retrying", actual)
+    ///     }
+    ///     Err(e) => panic!("{}", e),
+    /// }
+    /// ```
+    ///
+    pub fn persist_if_version(
+        &mut self,
+        expected: u64,
+        changeset: &ChangeSet,
+    ) -> Result<u64, RedbError> {
+        self.check_writable()?;
+        self.check_network(changeset)?;
+
+        let (durability, flushes) = self.persist_durability();
+        let mut write_txn = self.db()?.begin_write()?;
+        write_txn.set_durability(durability);
+        let actual = read_version(
+            &write_txn.open_table(self.wallet_table())?,
+            &self.scoped_key(VERSION_KEY),
+        )?;
+        if actual != expected {
+            write_txn.abort()?;
+            return Err(RedbError::VersionConflict { expected, actual });
+        }
+        if changeset.is_empty() {
+            write_txn.abort()?;
+            return Ok(actual);
+        }
+
+        let merged = {
+            let mut table = write_txn.open_table(self.wallet_table())?;
+            self.merge_into_table(&mut table, changeset.clone())?
+        };
+        let Some(merged) = merged else {
+            write_txn.abort()?;
+            return Ok(actual);
+        };
+        write_txn.commit()?;
+        self.persist_committed(flushes);
+
+        self.notify_persisted(&merged);
+
+        Ok(merged.version)
+    }
+
+    /// Persist a changeset and report whether the stored data changed
+    ///
+    /// Works like `WalletPersister::persist`, but compares the serialized merge result with
+    /// the bytes already on disk and skips the write when they are identical. An empty
+    /// changeset never writes anything.
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(true)` if the stored changeset changed
+    /// - `Ok(false)` if nothing had to be written
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the changeset's network does not match the store's network, or
+    /// if serialization or database access fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::ChangeSet;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// let changed = store.persist_reporting(&ChangeSet::default()).unwrap();
+    /// assert!(!changed);
+    /// ```
+    ///
+    pub fn persist_reporting(&mut self, changeset: &ChangeSet) -> Result<bool, RedbError> {
+        self.persist_changeset(changeset)
+    }
+
+    /// Compute the descriptor fingerprint of a changeset
+    ///
+    /// The fingerprint is the hex-encoded SHA-256 of the external and internal descriptors
+    /// in the changeset. Every persist records the fingerprint of the stored wallet, so it
+    /// can later be resolved with [`RedbStore::find_by_fingerprint`].
+    ///
+    /// Returns `None` if the changeset has no external descriptor yet. A wallet without a
+    /// change descriptor is fingerprinted from its external descriptor alone.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// if let Some(changeset) = store.load_changeset().unwrap() {
+    ///     println!("{:?}", RedbStore::descriptor_fingerprint(&changeset));
+    /// }
+    /// ```
+    ///
+    pub fn descriptor_fingerprint(changeset: &ChangeSet) -> Option<String> {
+        fingerprint_descriptors(
+            changeset.descriptor.as_ref(),
+            changeset.change_descriptor.as_ref(),
+        )
+    }
+
+    /// The key indexing this store's namespace under a descriptor fingerprint
+    fn fingerprint_index_key(&self, fingerprint: &str) -> String {
+        format!(
+            "{}{}::{}",
+            FINGERPRINT_PREFIX,
+            fingerprint,
+            self.namespace.as_deref().unwrap_or_default()
+        )
+    }
+
+    /// Find the namespaces holding the wallet with a given descriptor fingerprint
+    ///
+    /// Looks up the fingerprint index written by persists from every store sharing this
+    /// wallet table, without loading any wallet. The un-namespaced wallet is reported as
+    /// an empty string.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallets.redb").unwrap();
+    /// for namespace in store.find_by_fingerprint("9f86d081884c7d65...").unwrap() {
+    ///     println!("Found in namespace {:?}", namespace);
+    /// }
+    /// ```
+    ///
+    pub fn find_by_fingerprint(&self, fingerprint: &str) -> Result<Vec<String>, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let table = read_txn.open_table(self.wallet_table())?;
+
+        let prefix = format!("{}{}::", FINGERPRINT_PREFIX, fingerprint);
+        let mut namespaces = Vec::new();
+        for entry in table.range(prefix.as_str()..)? {
+            let (key, _) = entry?;
+            match key.value().strip_prefix(prefix.as_str()) {
+                Some(namespace) => namespaces.push(namespace.to_string()),
+                None => break,
+            }
+        }
+
+        Ok(namespaces)
+    }
+
+    /// Get the number of bytes a changeset occupies once serialized
+    ///
+    /// Measures `changeset` in the store's encoding, including
+    /// [`RedbStore::set_json_pretty`], without buffering it or touching the database.
+    /// Because persisting merges into the stored changeset, this is the size the stored
+    /// value would have if `changeset` were the only thing persisted, as compared against
+    /// [`RedbStore::set_max_changeset_bytes`]; use it to enforce quotas before committing
+    /// an update.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the changeset cannot be serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::ChangeSet;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// let size = store.serialized_size(&ChangeSet::default()).unwrap();
+    /// println!("An empty changeset takes {} bytes", size);
+    /// ```
+    ///
+    pub fn serialized_size(&self, changeset: &ChangeSet) -> Result<usize, RedbError> {
+        Ok(self.measure_changeset(changeset, None)?.len())
+    }
+
+    /// Check what persisting a changeset would do, without writing anything
+    ///
+    /// Reads the stored changeset and checks `changeset` against it the same way
+    /// `persist` and [`RedbStore::merge_from`] would: the descriptors must not conflict
+    /// with the stored ones or with the ones set by [`RedbStore::expect_descriptors`], the
+    /// network must match, and the merged result must fit the
+    /// [`RedbStore::set_max_changeset_bytes`] limit. Only a read transaction is used, so
+    /// it works on read-only stores and never blocks writers.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the stored changeset cannot be read or deserialized, or if the
+    /// merged changeset cannot be serialized. Problems with `changeset` itself are
+    /// reported in the [`ValidationReport`] rather than as errors.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::ChangeSet;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// let report = store.validate(&ChangeSet::default()).unwrap();
+    /// if !report.is_valid() {
+    ///     println!("Rejecting changeset: {:?}", report);
+    /// }
+    /// ```
+    ///
+    pub fn validate(&self, changeset: &ChangeSet) -> Result<ValidationReport, RedbError> {
+        let stored = self.get_changeset_with_log()?.unwrap_or_default();
+
+        let descriptors_match = !matches!(
+            check_same_wallet(&stored, changeset),
+            Err(RedbError::DescriptorConflict(_))
+        ) && self.check_descriptors(changeset).is_ok();
+        // The network is recorded apart from the changeset by `create_for_network`
+        let stored_network = self.stored_network()?.or(stored.network);
+        let network_matches = match (stored_network, changeset.network) {
+            (Some(expected), Some(found)) => expected == found,
+            _ => true,
+        };
+
+        // Merging conflicting changesets is a logic error in bdk, so only compatible ones
+        // get a merged size
+        let merged_size = if descriptors_match && network_matches {
+            let mut merged = stored;
+            merged.merge(changeset.clone());
+            Some(self.measure_changeset(&merged, None)?.len())
+        } else {
+            None
+        };
+        let within_limit = match (merged_size, self.max_changeset_bytes) {
+            (Some(size), Some(limit)) => size <= limit,
+            _ => true,
+        };
+
+        Ok(ValidationReport {
+            merged_size,
+            descriptors_match,
+            network_matches,
+            within_limit,
+            is_empty: changeset.is_empty(),
+        })
+    }
+
+    /// Show what merging a changeset into the stored one would change, without writing
+    ///
+    /// Merges `candidate` into a copy of the stored changeset in memory and compares the
+    /// result with what is stored, so an update from an external source can be reviewed
+    /// before it is persisted. Parts of `candidate` that are already stored, or that lose
+    /// against the stored state, such as a lower revealed index, don't show up.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - `candidate` is for another wallet ([`RedbError::DescriptorConflict`]) or network
+    ///   ([`RedbError::NetworkMismatch`])
+    /// - The stored changeset cannot be read or deserialized
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::ChangeSet;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// let update = ChangeSet::default();
+    /// let diff = store.diff(&update).unwrap();
+    /// println!("{} new transactions", diff.new_txs.len());
+    /// ```
+    ///
+    pub fn diff(&self, candidate: &ChangeSet) -> Result<ChangeSetDiff, RedbError> {
+        let stored = self.get_changeset_with_log()?.unwrap_or_default();
+        check_same_wallet(&stored, candidate)?;
+        let mut merged = stored.clone();
+        merged.merge(candidate.clone());
+
+        let stored_txids: std::collections::BTreeSet<_> = stored
+            .tx_graph
+            .txs
+            .iter()
+            .map(|tx| tx.compute_txid())
+            .collect();
+        let old_tip = chain_tip(&stored.local_chain);
+        let new_tip = chain_tip(&merged.local_chain);
+
+        Ok(ChangeSetDiff {
+            sets_descriptors: (stored.descriptor.is_none() && merged.descriptor.is_some())
+                || (stored.change_descriptor.is_none() && merged.change_descriptor.is_some()),
+            sets_network: stored.network.is_none() && merged.network.is_some(),
+            revealed: merged
+                .indexer
+                .last_revealed
+                .into_iter()
+                .filter(|(id, index)| stored.indexer.last_revealed.get(id) != Some(index))
+                .collect(),
+            new_txs: merged
+                .tx_graph
+                .txs
+                .iter()
+                .map(|tx| tx.compute_txid())
+                .filter(|txid| !stored_txids.contains(txid))
+                .collect(),
+            new_txouts: merged
+                .tx_graph
+                .txouts
+                .keys()
+                .filter(|outpoint| !stored.tx_graph.txouts.contains_key(outpoint))
+                .copied()
+                .collect(),
+            new_anchors: merged
+                .tx_graph
+                .anchors
+                .difference(&stored.tx_graph.anchors)
+                .map(|(_, txid)| *txid)
+                .collect(),
+            last_seen: merged
+                .tx_graph
+                .last_seen
+                .into_iter()
+                .filter(|(txid, seen)| stored.tx_graph.last_seen.get(txid) != Some(seen))
+                .collect(),
+            blocks: merged
+                .local_chain
+                .blocks
+                .into_iter()
+                .filter(|(height, hash)| stored.local_chain.blocks.get(height) != Some(hash))
+                .collect(),
+            tip_change: (old_tip != new_tip).then_some((old_tip, new_tip)),
+        })
+    }
+
+    /// Run a read-only self-test of the store
+    ///
+    /// Meant for startup: in a single read transaction, checks that every entry of the
+    /// wallet table can be read, that the stored changeset is in a supported format,
+    /// matches the checksum recorded when it was written and deserializes, and that the
+    /// wallet's network is `expected_network`, if given. Nothing is written, so it works
+    /// on read-only stores. Failed checks are reported in the [`HealthReport`] rather than
+    /// as errors.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a read transaction cannot be started or the wallet table
+    /// cannot be opened.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bitcoin::Network;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let store = RedbStore::open("wallet.redb").unwrap();
+    /// let report = store.health_check(Some(Network::Bitcoin)).unwrap();
+    /// if !report.is_healthy() {
+    ///     eprintln!("Wallet file needs attention: {:?}", report.problems);
+    /// }
+    /// ```
+    ///
+    pub fn health_check(
+        &self,
+        expected_network: Option<Network>,
+    ) -> Result<HealthReport, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let table = read_txn.open_table(self.wallet_table())?;
+        let mut problems = Vec::new();
+
+        let mut structure_ok = true;
+        for entry in table.iter()? {
+            if let Err(e) = entry {
+                structure_ok = false;
+                problems.push(format!("unreadable entry: {}", e));
+                break;
+            }
+        }
+
+        let format_supported = match self.check_changeset_version(&table) {
+            Ok(()) => true,
+            Err(e) => {
+                problems.push(e.to_string());
+                false
+            }
+        };
+
+        let stored = StoredChangeSet::read(&table, &self.changeset_key(&table)?)?;
+        let checksum_matches = match (&stored, table.get(self.content_hash_key(&table)?.as_str())?)
+        {
+            (Some(stored), Some(hash)) => {
+                let matches = hash.value() == stored.content_hash().as_byte_array();
+                if !matches {
+                    problems.push("changeset does not match its checksum".to_string());
+                }
+                Some(matches)
+            }
+            _ => None,
+        };
+
+        let mut changeset_readable = true;
+        let mut network = None;
+        if let (Some(stored), true) = (&stored, format_supported) {
+            match stored.decode(&self.deserialize_limits) {
+                Ok(changeset) => network = changeset.network,
+                Err(e) => {
+                    changeset_readable = false;
+                    problems.push(format!("changeset does not deserialize: {}", e));
+                }
+            }
+        }
+
+        // Fall back to the network recorded by `create_for_network`
+        if network.is_none() {
+            network = table
+                .get(self.scoped_key(NETWORK_KEY).as_str())?
+                .and_then(|value| serde_json::from_slice(value.value()).ok());
+        }
+        let network_matches = expected_network.map(|expected| {
+            let matches = network.is_none_or(|stored| stored == expected);
+            if !matches {
+                problems.push(format!("wallet is not for {}", expected));
+            }
+            matches
+        });
+
+        Ok(HealthReport {
+            structure_ok,
+            format_supported,
+            checksum_matches,
+            changeset_readable,
+            network_matches,
+            problems,
+        })
+    }
+
+    /// Load the stored changeset with indexer data for only some keychains
+    ///
+    /// Works like `WalletPersister::initialize`, but leaves out the last revealed indices
+    /// of the keychains not in `keychains`, e.g. `&[KeychainKind::External]` for a
+    /// watch-only view of the receive addresses. Both descriptors are kept, so the result
+    /// loads into a `Wallet` that expects them. The whole changeset is still read and
+    /// deserialized, so this is no faster than `initialize`.
+    ///
+    /// The transaction graph and local chain are kept whole, but a filtered keychain
+    /// starts with no revealed addresses: only outputs within its lookahead are recognized
+    /// as the wallet's, and it hands out addresses again from index 0. Use the result as a
+    /// read-only view, not to reveal new addresses for filtered keychains.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as `WalletPersister::initialize`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::KeychainKind;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open("wallet.redb").unwrap();
+    /// let changeset = store.initialize_filtered(&[KeychainKind::External]).unwrap();
+    /// ```
+    ///
+    pub fn initialize_filtered(
+        &mut self,
+        keychains: &[KeychainKind],
+    ) -> Result<ChangeSet, RedbError> {
+        let mut changeset = self.initialize_changeset()?;
+
+        let descriptors = [
+            (KeychainKind::External, &changeset.descriptor),
+            (KeychainKind::Internal, &changeset.change_descriptor),
+        ];
+        let keep: Vec<_> = descriptors
+            .into_iter()
+            .filter(|(keychain, _)| keychains.contains(keychain))
+            .filter_map(|(_, descriptor)| Some(descriptor.as_ref()?.descriptor_id()))
+            .collect();
+        changeset
+            .indexer
+            .last_revealed
+            .retain(|id, _| keep.contains(id));
+        Ok(changeset)
+    }
+
+    /// Load the stored changeset, reporting progress along the way
+    ///
+    /// Works like `WalletPersister::initialize`, but calls `progress` as the stored data is
+    /// deserialized and merged, so a UI can render a progress bar for large wallets. The
+    /// data is read in segments: the stored changeset's descriptors, local chain,
+    /// transaction graph and indexer, followed by one more for the unfolded log entries
+    /// when the `wal` feature is enabled and there are any. `progress` is called once with
+    /// zero segments read, then after each segment, so the last call always reports
+    /// completion.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as `WalletPersister::initialize`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open("wallet.redb").unwrap();
+    /// let changeset = store
+    ///     .initialize_with_progress(|p| {
+    ///         println!("Loaded {}/{} segments", p.segments_read, p.total_segments)
+    ///     })
+    ///     .unwrap();
+    /// ```
+    ///
+    pub fn initialize_with_progress<F>(&mut self, mut progress: F) -> Result<ChangeSet, RedbError>
+    where
+        F: FnMut(LoadProgress),
+    {
+        // The log is merged from the transaction the changeset was read from
+        let read_txn = self.db()?.begin_read()?;
+        let (main, segmented) = {
+            let table = read_txn.open_table(self.wallet_table())?;
+            self.check_changeset_version(&table)?;
+            let stored = StoredChangeSet::read(&table, &self.changeset_key(&table)?)?;
+            match stored {
+                Some(stored) => (
+                    stored.values().into_iter().map(<[u8]>::to_vec).collect(),
+                    stored.segments().is_some(),
+                ),
+                None => (Vec::new(), false),
+            }
+        };
+        #[cfg(feature = "wal")]
+        let has_log = self.has_log(&read_txn)?;
+        #[cfg(not(feature = "wal"))]
+        let has_log = false;
+
+        let total_segments = main.len() + usize::from(has_log);
+        progress(LoadProgress {
+            segments_read: 0,
+            total_segments,
+        });
+
+        let mut changeset = ChangeSet::default();
+        for (index, bytes) in main.iter().enumerate() {
+            let decoded = if segmented {
+                codec::decode_segment_into(&mut changeset, index, bytes, &self.deserialize_limits)
+            } else {
+                self.decode(bytes).map(|stored| changeset = stored)
+            };
+            match decoded {
+                Ok(()) => {}
+                // The rest of an unreadable changeset is moved away with it, and so is the log
+                Err(e) if self.recoverable => {
+                    drop(read_txn);
+                    self.quarantine_changeset(e)?;
+                    progress(LoadProgress {
+                        segments_read: total_segments,
+                        total_segments,
+                    });
+                    return Ok(ChangeSet::default());
+                }
+                Err(e) => return Err(RedbError::Deserialization(e)),
+            }
+            progress(LoadProgress {
+                segments_read: index + 1,
+                total_segments,
+            });
+        }
+
+        let changeset = self.complete_initialize(&read_txn, changeset)?;
+        if has_log {
+            progress(LoadProgress {
+                segments_read: total_segments,
+                total_segments,
+            });
+        }
+        Ok(changeset)
+    }
+
+    /// Load the stored changeset through a shared reference
+    ///
+    /// Works like `WalletPersister::initialize`, but takes `&self`, so it can be called on
+    /// a store shared between threads. It returns an empty changeset if nothing has been
+    /// persisted yet. Recoverable stores don't recover here, because recovery has to
+    /// update the store's [`RedbStore::last_recovery_info`]; an unreadable changeset is
+    /// returned as an error instead.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be read, the changeset cannot be
+    /// deserialized, or it does not match the descriptors set with
+    /// [`RedbStore::expect_descriptors`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    /// use std::sync::Arc;
+    ///
+    /// let store = Arc::new(RedbStore::open_or_create("wallet.redb").unwrap());
+    /// let changeset = store.initialize_shared().unwrap();
+    /// ```
+    ///
+    pub fn initialize_shared(&self) -> Result<ChangeSet, RedbError> {
+        let read_txn = self.db()?.begin_read()?;
+        let changeset = self.changeset_in(&read_txn)?.unwrap_or_default();
+        self.complete_initialize(&read_txn, changeset)
+    }
+
+    /// Persist a changeset through a shared reference
+    ///
+    /// Works like `WalletPersister::persist`, but takes `&self`, so a store wrapped in an
+    /// [`Arc`] can be persisted to from several threads or tasks without an outer mutex.
+    /// Writes are serialized by redb, which allows only one write transaction at a time:
+    /// each call reads, merges and writes the stored changeset in its own transaction,
+    /// so concurrent persists never lose each other's changes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the changeset's network does not match the store's network, or
+    /// if serialization or database access fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::ChangeSet;
+    /// use redb_wallet_storage::RedbStore;
+    /// use std::sync::Arc;
+    ///
+    /// let store = Arc::new(RedbStore::open_or_create("wallet.redb").unwrap());
+    /// let handle = {
+    ///     let store = Arc::clone(&store);
+    ///     std::thread::spawn(move || store.persist_shared(&ChangeSet::default()))
+    /// };
+    /// store.persist_shared(&ChangeSet::default()).unwrap();
+    /// handle.join().unwrap().unwrap();
+    /// ```
+    ///
+    pub fn persist_shared(&self, changeset: &ChangeSet) -> Result<(), RedbError> {
+        self.persist_changeset(changeset).map(|_| ())
+    }
+
+    /// Persist a changeset on the current thread, even from async code
+    ///
+    /// With the `tokio` or `async-std` feature, `AsyncWalletPersister::persist` hands the
+    /// write to another thread, which adds scheduling latency before it starts. This runs
+    /// the synchronous `WalletPersister::persist` instead, so the write has committed by
+    /// the time it returns. Whether it is also on disk depends on the store's durability:
+    /// with [`FlushPolicy::EveryN`] or [`FlushPolicy::Never`] the commit may not be
+    /// flushed yet, and a crash can still roll it back.
+    ///
+    /// The current thread is blocked for the whole write, including any fsync. Called on
+    /// an async executor, that stalls every other task on the same worker thread, so keep
+    /// it for writes that must finish before anything else happens.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the changeset's network does not match the store's network, or
+    /// if serialization or database access fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::ChangeSet;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// async fn save_before_broadcast(store: &mut RedbStore, changeset: &ChangeSet) {
+    ///     // Must be on disk before the transaction leaves this process
+    ///     store.persist_blocking(changeset).unwrap();
+    /// }
+    /// ```
+    ///
+    pub fn persist_blocking(&mut self, changeset: &ChangeSet) -> Result<(), RedbError> {
+        self.persist_retrying(changeset).map(|_| ())
+    }
+
+    /// Persist the changes staged in a wallet
+    ///
+    /// Takes the wallet's staged changeset and persists it to this store, the same as
+    /// `wallet.persist(&mut store)` but with the error already a [`RedbError`]. If nothing
+    /// is staged, nothing is written.
+    ///
+    /// # Returns
+    ///
+    /// `true` if staged changes were persisted, `false` if there was nothing to persist.
+    ///
+    /// # Errors
+    ///
+    /// Returns the same errors as `WalletPersister::persist`. The staged changes are kept
+    /// in the wallet if persisting fails, so the call can be retried.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::{KeychainKind, LoadParams, PersistedWallet};
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open("wallet.redb").unwrap();
+    /// let mut wallet = PersistedWallet::load(&mut store, LoadParams::default())
+    ///     .unwrap()
+    ///     .unwrap();
+    /// let address = wallet.reveal_next_address(KeychainKind::External);
+    /// store.persist_wallet(&mut wallet).unwrap();
+    /// println!("Next address: {}", address.address);
+    /// ```
+    ///
+    pub fn persist_wallet(
+        &mut self,
+        wallet: &mut bdk_wallet::PersistedWallet<RedbStore>,
+    ) -> Result<bool, RedbError> {
+        wallet.persist(self)
+    }
+
+    /// Check the descriptors of a loaded changeset against the expected ones
+    ///
+    /// A changeset without a descriptor, such as the one of a wallet that hasn't been
+    /// created yet, always passes.
+    fn check_descriptors(&self, changeset: &ChangeSet) -> Result<(), RedbError> {
+        let Some((external, internal)) = &self.expected_descriptors else {
+            return Ok(());
+        };
+
+        if changeset.descriptor.as_ref().is_some_and(|d| d != external) {
+            return Err(RedbError::DescriptorMismatch(KeychainKind::External));
+        }
+        if changeset
+            .change_descriptor
+            .as_ref()
+            .is_some_and(|d| d != internal)
+        {
+            return Err(RedbError::DescriptorMismatch(KeychainKind::Internal));
+        }
+        Ok(())
+    }
+
+    /// Reject writes to a store opened read-only
+    fn check_writable(&self) -> Result<(), RedbError> {
+        if self.read_only {
+            return Err(RedbError::ReadOnly);
+        }
+        Ok(())
+    }
+
+    /// Check a changeset against the network recorded for this store
+    fn check_network(&self, changeset: &ChangeSet) -> Result<(), RedbError> {
+        if let (Some(expected), Some(found)) = (self.stored_network()?, changeset.network) {
+            if expected != found {
+                return Err(RedbError::NetworkMismatch { expected, found });
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(feature = "wal")]
+impl RedbStore {
+    /// The name of the table holding this store's unfolded log entries
+    fn log_table_name(&self) -> String {
+        format!("{}_log", self.table_name)
+    }
+
+    /// The scoped key prefix of this store's log entries
+    fn log_prefix(&self) -> String {
+        self.scoped_key(LOG_PREFIX)
+    }
+
+    /// The key range holding this store's log entries and no one else's
+    fn log_range(&self) -> (String, String) {
+        let start = self.log_prefix();
+        let end = format!("{};", &start[..start.len() - 1]);
+        (start, end)
+    }
+
+    /// Read this store's unfolded log entries as of `read_txn`, oldest first
+    fn read_log(&self, read_txn: &redb::ReadTransaction) -> Result<Vec<ChangeSet>, RedbError> {
+        self.read_log_bytes(read_txn)?
+            .iter()
+            .map(|bytes| self.decode(bytes).map_err(RedbError::Deserialization))
+            .collect()
+    }
+
+    /// Whether this store has unfolded log entries as of `read_txn`
+    fn has_log(&self, read_txn: &redb::ReadTransaction) -> Result<bool, RedbError> {
+        let log_name = self.log_table_name();
+        let table = match read_txn.open_table(TableDefinition::<&str, &[u8]>::new(&log_name)) {
+            Ok(table) => table,
+            Err(redb::TableError::TableDoesNotExist(_)) => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+
+        let (start, end) = self.log_range();
+        Ok(table.range(start.as_str()..end.as_str())?.next().is_some())
+    }
+
+    /// Read the raw bytes of this store's unfolded log entries, oldest first
+    fn read_log_bytes(&self, read_txn: &redb::ReadTransaction) -> Result<Vec<Vec<u8>>, RedbError> {
+        let log_name = self.log_table_name();
+        let table = match read_txn.open_table(TableDefinition::<&str, &[u8]>::new(&log_name)) {
+            Ok(table) => table,
+            Err(redb::TableError::TableDoesNotExist(_)) => return Ok(Vec::new()),
+            Err(e) => return Err(e.into()),
+        };
+
+        let prefix = self.log_prefix();
+        let mut entries = Vec::new();
+        for entry in table.range(prefix.as_str()..)? {
+            let (key, value) = entry?;
+            if !key.value().starts_with(prefix.as_str()) {
+                break;
+            }
+            entries.push(value.value().to_vec());
+        }
+
+        Ok(entries)
+    }
+
+    /// Remove this store's unfolded log entries within `write_txn`
+    fn clear_log(&self, write_txn: &redb::WriteTransaction) -> Result<(), RedbError> {
+        let log_name = self.log_table_name();
+        let log_table = TableDefinition::<&str, &[u8]>::new(&log_name);
+        // Opening the table in a write transaction would create it
+        let exists = write_txn
+            .list_tables()?
+            .any(|table| redb::TableHandle::name(&table) == log_name);
+        if !exists {
+            return Ok(());
+        }
+
+        let prefix = self.log_prefix();
+        let mut log = write_txn.open_table(log_table)?;
+        log.retain_in(prefix.as_str().., |key, _| {
+            !key.starts_with(prefix.as_str())
+        })?;
+        Ok(())
+    }
+
+    /// Remove this store's unfolded log entries within `write_txn`, returning them merged
+    /// oldest first, or `None` if there were none
+    fn take_log(&self, write_txn: &redb::WriteTransaction) -> Result<Option<ChangeSet>, RedbError> {
+        let log_name = self.log_table_name();
+        // Opening the table in a write transaction would create it
+        let exists = write_txn
+            .list_tables()?
+            .any(|table| redb::TableHandle::name(&table) == log_name);
+        if !exists {
+            return Ok(None);
+        }
+
+        let prefix = self.log_prefix();
+        let mut log = write_txn.open_table(TableDefinition::<&str, &[u8]>::new(&log_name))?;
+        let mut folded: Option<ChangeSet> = None;
+        let mut keys = Vec::new();
+        for entry in log.range(prefix.as_str()..)? {
+            let (key, value) = entry?;
+            if !key.value().starts_with(prefix.as_str()) {
+                break;
+            }
+            let changeset: ChangeSet = self
+                .decode(value.value())
+                .map_err(RedbError::Deserialization)?;
+            folded
+                .get_or_insert_with(ChangeSet::default)
+                .merge(changeset);
+            keys.push(key.value().to_string());
+        }
+        for key in &keys {
+            log.remove(key.as_str())?;
+        }
+
+        Ok(folded)
+    }
+
+    /// Append a changeset to the write-ahead log
+    ///
+    /// Each call commits the changeset as a separate entry in a log table next to the
+    /// wallet table, named after it with a `_log` suffix. Appending never rewrites the
+    /// stored changeset, so each write is small and a crash loses at most the increment
+    /// being appended. `initialize` merges unfolded entries into the stored changeset
+    /// transparently, and the next persist folds them in before its own changes; call
+    /// [`RedbStore::fold_log`] periodically to merge them for good.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store is read-only, the changeset's network does not match
+    /// the store's network, or serialization or database access fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::ChangeSet;
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// let updates: Vec<ChangeSet> = Vec::new();
+    /// for update in &updates {
+    ///     store.append_log(update).unwrap();
+    /// }
+    /// store.fold_log().unwrap();
+    /// ```
+    ///
+    pub fn append_log(&mut self, changeset: &ChangeSet) -> Result<(), RedbError> {
+        self.check_writable()?;
+        if changeset.is_empty() {
+            return Ok(());
+        }
+        self.check_network(changeset)?;
+
+        let bytes = self.encode_changeset(changeset)?;
+        let (prefix, end) = self.log_range();
+        let log_name = self.log_table_name();
+
+        let mut write_txn = self.db()?.begin_write()?;
+        write_txn.set_durability(self.durability);
+        {
+            let mut table = write_txn.open_table(TableDefinition::<&str, &[u8]>::new(&log_name))?;
+            let next = match table.range(prefix.as_str()..end.as_str())?.next_back() {
+                Some(entry) => {
+                    let (key, _) = entry?;
+                    key.value()
+                        .strip_prefix(prefix.as_str())
+                        .and_then(|seq| seq.parse::<u64>().ok())
+                        .map_or(0, |seq| seq + 1)
+                }
+                None => 0,
+            };
+            let key = format!("{}{:020}", prefix, next);
+            table.insert(key.as_str(), bytes.as_slice())?;
+        }
+        write_txn.commit()?;
+
+        Ok(())
+    }
+
+    /// Merge all unfolded log entries into the stored changeset and clear the log
+    ///
+    /// Runs in a single write transaction, so either every entry is folded and removed
+    /// or nothing changes. The `on_persist` callback fires if the stored changeset changed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the store is read-only, the merged changeset is too large, or
+    /// serialization or database access fails.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::RedbStore;
+    ///
+    /// let mut store = RedbStore::open_or_create("wallet.redb").unwrap();
+    /// store.fold_log().unwrap();
+    /// ```
+    ///
+    pub fn fold_log(&mut self) -> Result<(), RedbError> {
+        self.fold_log_entries()
+    }
+
+    /// Fold the log like [`RedbStore::fold_log`], through a shared reference
+    fn fold_log_entries(&self) -> Result<(), RedbError> {
+        self.check_writable()?;
+
+        let mut write_txn = self.db()?.begin_write()?;
+        write_txn.set_durability(self.durability);
+
+        let Some(folded) = self.take_log(&write_txn)? else {
+            write_txn.abort()?;
+            return Ok(());
+        };
+        let merged = {
+            let mut table = write_txn.open_table(self.wallet_table())?;
+            self.merge_into_table(&mut table, folded)?
+        };
+        write_txn.commit()?;
+
+        if let Some(merged) = merged {
+            self.notify_persisted(&merged);
+        }
+
+        Ok(())
+    }
+}
+
+/// A changeset as stored in a table, read in place
+///
+/// Changesets are stored as the segments in [`codec::SEGMENTS`]: the descriptors segment
+/// at the changeset key and the others at keys derived from it, see [`segment_keys`].
+/// Changesets written before they were segmented are a single value at the changeset key.
+enum StoredChangeSet<'a> {
+    Whole(redb::AccessGuard<'a, &'static [u8]>),
+    Segments(Box<[redb::AccessGuard<'a, &'static [u8]>; 4]>),
+}
+
+impl<'a> StoredChangeSet<'a> {
+    /// Read the changeset stored at `key`, if any
+    fn read<T>(table: &'a T, key: &str) -> Result<Option<Self>, RedbError>
+    where
+        T: ReadableTable<&'static str, &'static [u8]>,
+    {
+        let Some(descriptors) = table.get(key)? else {
+            return Ok(None);
+        };
+        let keys = segment_keys(key);
+        let (Some(local_chain), Some(tx_graph), Some(indexer)) = (
+            table.get(keys[1].as_str())?,
+            table.get(keys[2].as_str())?,
+            table.get(keys[3].as_str())?,
+        ) else {
+            return Ok(Some(Self::Whole(descriptors)));
+        };
+        Ok(Some(Self::Segments(Box::new([
+            descriptors,
+            local_chain,
+            tx_graph,
+            indexer,
+        ]))))
+    }
+
+    /// The stored values, the single one of a changeset stored whole or each segment
+    fn values(&self) -> Vec<&[u8]> {
+        match self {
+            Self::Whole(value) => vec![value.value()],
+            Self::Segments(segments) => segments.iter().map(|value| value.value()).collect(),
+        }
+    }
+
+    /// The stored segments, or `None` for a changeset stored as a single value
+    fn segments(&self) -> Option<[&[u8]; 4]> {
+        match self {
+            Self::Whole(_) => None,
+            Self::Segments(segments) => Some(segments.each_ref().map(|value| value.value())),
+        }
+    }
+
+    /// Deserialize the changeset, checking each stored value against `limits`
+    fn decode(&self, limits: &DeserializeLimits) -> Result<ChangeSet, serde_json::Error> {
+        match self {
+            Self::Whole(value) => codec::decode_json(value.value(), limits),
+            Self::Segments(segments) => {
+                codec::decode_segments(segments.each_ref().map(|value| value.value()), limits)
+            }
+        }
+    }
+
+    /// The JSON of the whole changeset, as [`codec::encode_changeset`] writes it
+    fn to_json(&self) -> Result<Vec<u8>, serde_json::Error> {
+        match self {
+            Self::Whole(value) => Ok(value.value().to_vec()),
+            Self::Segments(segments) => {
+                codec::join_segments(segments.each_ref().map(|value| value.value()))
+            }
+        }
+    }
+
+    /// The length of the changeset's JSON, see [`StoredChangeSet::to_json`]
+    fn len(&self) -> usize {
+        match self {
+            Self::Whole(value) => value.value().len(),
+            Self::Segments(segments) => {
+                codec::joined_len(segments.each_ref().map(|value| value.value().len()))
+            }
+        }
+    }
+
+    /// The SHA-256 of the stored values, in order
+    fn content_hash(&self) -> sha256::Hash {
+        let mut engine = sha256::Hash::engine();
+        for value in self.values() {
+            engine.input(value);
+        }
+        sha256::Hash::from_engine(engine)
+    }
+}
+
+/// The keys of the segments of the changeset stored at `key`, in the order of
+/// [`codec::SEGMENTS`]
+fn segment_keys(key: &str) -> [String; 4] {
+    std::array::from_fn(|index| match index {
+        0 => key.to_string(),
+        _ => format!("{}::{}", key, codec::SEGMENTS[index]),
+    })
+}
+
+/// Deserialize the changeset stored at `key` among raw table entries
+///
+/// Like [`StoredChangeSet::decode`], for entries copied out of a table. `entries` must
+/// hold `key`.
+fn decode_changeset_entries(
+    entries: &BTreeMap<String, Vec<u8>>,
+    key: &str,
+) -> Result<ChangeSet, serde_json::Error> {
+    let limits = DeserializeLimits::default();
+    let values = segment_keys(key).map(|key| entries.get(&key).map(Vec::as_slice));
+    match values {
+        [Some(descriptors), Some(local_chain), Some(tx_graph), Some(indexer)] => {
+            codec::decode_segments([descriptors, local_chain, tx_graph, indexer], &limits)
+        }
+        [whole, ..] => codec::decode_json(whole.unwrap_or_default(), &limits),
+    }
+}
+
+/// The entries of every table in a database keyed by strings with byte values, by table
+/// name, and the stored size of all other tables
+///
+/// Every table this crate writes is keyed by strings with byte values; tables of other
+/// types can't be read without knowing their types.
+type TableContents = (BTreeMap<String, BTreeMap<String, Vec<u8>>>, u64);
+
+/// Read every table in `db` in a single read transaction
+fn read_tables(db: &Database) -> Result<TableContents, RedbError> {
+    let read_txn = db.begin_read()?;
+    let mut tables = BTreeMap::new();
+    let mut other_bytes = 0;
+    for handle in read_txn.list_tables()? {
+        let name = redb::TableHandle::name(&handle).to_string();
+        match read_txn.open_table(TableDefinition::<&str, &[u8]>::new(&name)) {
+            Ok(table) => {
+                let mut entries = BTreeMap::new();
+                for entry in table.iter()? {
+                    let (key, value) = entry?;
+                    entries.insert(key.value().to_string(), value.value().to_vec());
+                }
+                tables.insert(name, entries);
+            }
+            Err(redb::TableError::TableTypeMismatch { .. }) => {
+                other_bytes += read_txn.open_untyped_table(handle)?.stats()?.stored_bytes();
+            }
+            Err(e) => return Err(e.into()),
+        }
+    }
+    for handle in read_txn.list_multimap_tables()? {
+        other_bytes += read_txn
+            .open_untyped_multimap_table(handle)?
+            .stats()?
+            .stored_bytes();
+    }
+    Ok((tables, other_bytes))
+}
+
+/// A changeset merged and written by [`RedbStore::merge_into_table`]
+struct Merged {
+    /// The merged changeset now stored
+    changeset: ChangeSet,
+    /// The version the stored changeset was written as
+    version: u64,
+}
+
+/// A changeset serialized by [`RedbStore::measure_changeset`], one segment at a time
+struct Measured {
+    /// The length of each serialized segment
+    lens: [usize; 4],
+    /// Whether each serialized segment is the stored one
+    unchanged: [bool; 4],
+    /// The SHA-256 of the serialized segments, in order
+    hash: sha256::Hash,
+}
+
+impl Measured {
+    /// The length of the changeset's JSON once its segments are joined
+    fn len(&self) -> usize {
+        codec::joined_len(self.lens)
+    }
+}
+
+/// A writer that only hashes and counts what is written to it, and compares it with
+/// the stored bytes
+struct MeasuringWriter<'a> {
+    engine: &'a mut sha256::HashEngine,
+    len: usize,
+    stored: Option<&'a [u8]>,
+    matches: bool,
+}
+
+impl std::io::Write for MeasuringWriter<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.engine.input(buf);
+        if self.matches {
+            let stored = self
+                .stored
+                .and_then(|stored| stored.get(self.len..self.len + buf.len()));
+            self.matches = stored == Some(buf);
+        }
+        self.len += buf.len();
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// The hex-encoded SHA-256 of a wallet's descriptors, see
+/// [`RedbStore::descriptor_fingerprint`]
+fn fingerprint_descriptors(
+    descriptor: Option<&Descriptor<DescriptorPublicKey>>,
+    change_descriptor: Option<&Descriptor<DescriptorPublicKey>>,
+) -> Option<String> {
+    let descriptor = descriptor?;
+    let change_descriptor = change_descriptor.map(|d| d.to_string()).unwrap_or_default();
+
+    let data = format!("{}\n{}", descriptor, change_descriptor);
+    Some(sha256::Hash::hash(data.as_bytes()).to_string())
+}
+
+/// The height of the highest block in a local chain changeset
+fn chain_tip(local_chain: &bdk_chain::local_chain::ChangeSet) -> Option<u32> {
+    // A block hash of `None` records a block that was removed from the chain
+    local_chain
+        .blocks
+        .iter()
+        .rev()
+        .find_map(|(height, hash)| hash.map(|_| *height))
+}
+
+/// Check that two changesets describe the same wallet and can be merged
+fn check_same_wallet(existing: &ChangeSet, incoming: &ChangeSet) -> Result<(), RedbError> {
+    check_same_descriptors(existing, incoming)?;
+    if let (Some(expected), Some(found)) = (existing.network, incoming.network) {
+        if expected != found {
+            return Err(RedbError::NetworkMismatch { expected, found });
+        }
+    }
+    Ok(())
+}
+
+/// Check that two changesets don't carry different descriptors for a keychain
+fn check_same_descriptors(existing: &ChangeSet, incoming: &ChangeSet) -> Result<(), RedbError> {
+    fn differ<T: PartialEq>(a: &Option<T>, b: &Option<T>) -> bool {
+        matches!((a, b), (Some(a), Some(b)) if a != b)
+    }
+
+    if differ(&existing.descriptor, &incoming.descriptor) {
+        return Err(RedbError::DescriptorConflict(KeychainKind::External));
+    }
+    if differ(&existing.change_descriptor, &incoming.change_descriptor) {
+        return Err(RedbError::DescriptorConflict(KeychainKind::Internal));
+    }
+    Ok(())
+}
+
+/// Remove old, fully spent transactions from a transaction graph changeset
+///
+/// See [`RedbStore::prune`] for which transactions qualify. Returns the number of
+/// transactions removed.
+fn prune_tx_graph(
+    graph: &mut bdk_chain::tx_graph::ChangeSet<bdk_chain::ConfirmationBlockTime>,
+    keep_after_height: u32,
+) -> usize {
+    use std::collections::{HashMap, HashSet};
+
+    let mut heights: HashMap<bitcoin::Txid, u32> = HashMap::new();
+    for (anchor, txid) in &graph.anchors {
+        let height = heights.entry(*txid).or_default();
+        *height = (*height).max(anchor.block_id.height);
+    }
+    let txs: HashMap<bitcoin::Txid, &Arc<bitcoin::Transaction>> =
+        graph.txs.iter().map(|tx| (tx.compute_txid(), tx)).collect();
+
+    // Outputs spent by confirmed transactions stay spent however the graph is resolved
+    let spent: HashSet<bitcoin::OutPoint> = txs
+        .iter()
+        .filter(|(txid, _)| heights.contains_key(*txid))
+        .flat_map(|(_, tx)| tx.input.iter().map(|input| input.previous_output))
+        .collect();
+
+    let mut pruned: HashSet<bitcoin::Txid> = txs
+        .iter()
+        .filter(|(txid, tx)| {
+            let old = heights
+                .get(*txid)
+                .is_some_and(|height| *height < keep_after_height);
+            let fully_spent = (0..tx.output.len())
+                .all(|vout| spent.contains(&bitcoin::OutPoint::new(**txid, vout as u32)));
+            old && fully_spent
+        })
+        .map(|(txid, _)| *txid)
+        .collect();
+
+    // Keep any transaction spending from a kept one, or the kept parent's spent
+    // outputs would look unspent
+    loop {
+        let before = pruned.len();
+        let kept_parent = |txid: &bitcoin::Txid| {
+            txs[txid].input.iter().any(|input| {
+                let parent = input.previous_output.txid;
+                txs.contains_key(&parent) && !pruned.contains(&parent)
+            })
+        };
+        let keep: Vec<bitcoin::Txid> = pruned.iter().filter(|t| kept_parent(t)).copied().collect();
+        for txid in keep {
+            pruned.remove(&txid);
+        }
+        if pruned.len() == before {
+            break;
+        }
+    }
+
+    graph.txs.retain(|tx| !pruned.contains(&tx.compute_txid()));
+    graph.anchors.retain(|(_, txid)| !pruned.contains(txid));
+    graph.last_seen.retain(|txid, _| !pruned.contains(txid));
+    pruned.len()
+}
+
+/// Read the changeset version stored under `key`, `0` if there is none
+fn read_version<T>(table: &T, key: &str) -> Result<u64, RedbError>
+where
+    T: ReadableTable<&'static str, &'static [u8]>,
+{
+    Ok(table
+        .get(key)?
+        .and_then(|value| <[u8; 8]>::try_from(value.value()).ok())
+        .map(u64::from_be_bytes)
+        .unwrap_or_default())
+}
+
+/// The current time in seconds since the Unix epoch
+fn unix_now() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Decode a timestamp stored as big-endian seconds since the Unix epoch
+fn decode_timestamp(bytes: &[u8]) -> Option<std::time::SystemTime> {
+    let secs = <[u8; 8]>::try_from(bytes).ok()?;
+    Some(std::time::UNIX_EPOCH + std::time::Duration::from_secs(u64::from_be_bytes(secs)))
+}
+
+/// Move the file at `from` to `to`, failing with `AlreadyExists` if `to` exists
+///
+/// Unlike [`std::fs::rename`], which replaces `to` on Unix, this never overwrites a file
+/// created at `to` concurrently. Both paths must be on the same file system.
+fn move_no_replace(from: &Path, to: &Path) -> std::io::Result<()> {
+    std::fs::hard_link(from, to)?;
+    std::fs::remove_file(from)
+}
+
+/// The key under which `key` is stored for `namespace`
+fn namespaced_key(namespace: &str, key: &str) -> String {
+    format!("{}{}::{}", NAMESPACE_PREFIX, namespace, key)
+}
+
+/// Split a stored key into its namespace, if any, and the key within that namespace
+fn split_namespace(key: &str) -> (Option<&str>, &str) {
+    match key
+        .strip_prefix(NAMESPACE_PREFIX)
+        .and_then(|rest| rest.split_once("::"))
+    {
+        Some((namespace, local)) => (Some(namespace), local),
+        None => (None, key),
+    }
+}
+
+/// The namespace whose `local` key `key` is, `""` for the default namespace
+///
+/// `None` if `key` is not `local` in any namespace.
+fn namespace_of(key: &str, local: &str) -> Option<String> {
+    if key == local {
+        return Some(String::new());
+    }
+    key.strip_prefix(NAMESPACE_PREFIX)?
+        .strip_suffix(local)?
+        .strip_suffix("::")
+        .filter(|namespace| !namespace.contains("::"))
+        .map(str::to_string)
+}
+
+/// Check that a namespace name can be used as a key prefix
+fn validate_namespace(namespace: &str) -> Result<(), RedbError> {
+    if namespace.is_empty() || namespace.contains("::") {
+        return Err(RedbError::InvalidNamespace(namespace.to_string()));
+    }
+    Ok(())
+}
+
+/// The parts of a stored [`ChangeSet`] needed for status queries
+///
+/// Fields missing here, such as the transaction graph, are skipped while parsing.
+#[derive(Default, serde::Deserialize)]
+struct ChangeSetSummary {
+    descriptor: Option<Descriptor<DescriptorPublicKey>>,
+    change_descriptor: Option<Descriptor<DescriptorPublicKey>>,
+    #[serde(default)]
+    network: Option<Network>,
+    local_chain: bdk_chain::local_chain::ChangeSet,
+    #[serde(default)]
+    tx_graph: TxGraphSummary,
+    indexer: bdk_chain::keychain_txout::ChangeSet,
+}
+
+impl ChangeSetSummary {
+    /// Merge an unfolded log entry, leaving the transaction count as it is
+    #[cfg(feature = "wal")]
+    fn merge(&mut self, entry: ChangeSet) {
+        self.descriptor = entry.descriptor.or(self.descriptor.take());
+        self.change_descriptor = entry.change_descriptor.or(self.change_descriptor.take());
+        self.network = entry.network.or(self.network);
+        self.local_chain.merge(entry.local_chain);
+        self.indexer.merge(entry.indexer);
+    }
+
+    /// The last revealed index of each keychain with revealed addresses
+    fn last_revealed(&self) -> BTreeMap<KeychainKind, u32> {
+        let keychains = [
+            (KeychainKind::External, &self.descriptor),
+            (KeychainKind::Internal, &self.change_descriptor),
+        ];
+        keychains
+            .into_iter()
+            .filter_map(|(keychain, descriptor)| {
+                let descriptor = descriptor.as_ref()?;
+                let index = self
+                    .indexer
+                    .last_revealed
+                    .get(&descriptor.descriptor_id())?;
+                Some((keychain, *index))
+            })
+            .collect()
+    }
+}
+
+/// The part of a stored transaction graph a [`ChangeSetSummary`] needs
+#[derive(Default, serde::Deserialize)]
+struct TxGraphSummary {
+    #[serde(default)]
+    txs: ElementCount,
+}
+
+/// The length of a serialized sequence, counted without deserializing its elements
+#[derive(Default)]
+struct ElementCount(usize);
+
+impl<'de> serde::Deserialize<'de> for ElementCount {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct CountVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for CountVisitor {
+            type Value = ElementCount;
+
+            fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                f.write_str("a sequence")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: serde::de::SeqAccess<'de>,
+            {
+                let mut count = 0;
+                while seq.next_element::<serde::de::IgnoredAny>()?.is_some() {
+                    count += 1;
+                }
+                Ok(ElementCount(count))
+            }
+        }
+
+        deserializer.deserialize_seq(CountVisitor)
+    }
+}
+
+/// Read counts shared between a [`CountingBackend`] and the stores using it
+#[derive(Debug, Default)]
+struct ReadCounter {
+    reads: AtomicU64,
+    bytes: AtomicU64,
+}
+
+/// A read-only view of a file that keeps redb's writes in memory
+///
+/// Lets [`RedbStore::open_latest`] open and check a candidate without copying it into
+/// memory or changing it on disk. Written blocks are copied into an overlay on their first
+/// write, so memory grows with what redb writes, not with the size of the file.
+#[derive(Debug)]
+struct ProbeBackend {
+    file: std::sync::Mutex<std::fs::File>,
+    overlay: std::sync::Mutex<ProbeOverlay>,
+}
+
+/// The state of a [`ProbeBackend`] that diverges from its file
+#[derive(Debug)]
+struct ProbeOverlay {
+    len: u64,
+    /// Bytes of the file beyond this offset are hidden, e.g. after a truncation
+    file_len: u64,
+    blocks: std::collections::HashMap<u64, Box<[u8]>>,
+}
+
+impl ProbeBackend {
+    /// The size of the blocks copied into the overlay
+    const BLOCK_SIZE: u64 = 4096;
+
+    /// Open the file at `path` for reading only
+    fn open(path: &Path) -> Result<Self, std::io::Error> {
+        let file = std::fs::File::open(path)?;
+        let len = file.metadata()?.len();
+        Ok(Self {
+            file: std::sync::Mutex::new(file),
+            overlay: std::sync::Mutex::new(ProbeOverlay {
+                len,
+                file_len: len,
+                blocks: std::collections::HashMap::new(),
+            }),
+        })
+    }
+
+    /// Read block `index` as currently seen, from the overlay or else from the file
+    fn read_block(&self, overlay: &ProbeOverlay, index: u64) -> Result<Box<[u8]>, std::io::Error> {
+        use std::io::{Read, Seek};
+
+        if let Some(block) = overlay.blocks.get(&index) {
+            return Ok(block.clone());
+        }
+        let mut block = vec![0; Self::BLOCK_SIZE as usize].into_boxed_slice();
+        let start = index * Self::BLOCK_SIZE;
+        let available = overlay.file_len.saturating_sub(start).min(Self::BLOCK_SIZE);
+        if available > 0 {
+            let mut file = self.file.lock().unwrap_or_else(|e| e.into_inner());
+            file.seek(std::io::SeekFrom::Start(start))?;
+            file.read_exact(&mut block[..available as usize])?;
+        }
+        Ok(block)
+    }
+}
+
+impl redb::StorageBackend for ProbeBackend {
+    fn len(&self) -> Result<u64, std::io::Error> {
+        Ok(self.overlay.lock().unwrap_or_else(|e| e.into_inner()).len)
+    }
+
+    fn read(&self, offset: u64, len: usize) -> Result<Vec<u8>, std::io::Error> {
+        let overlay = self.overlay.lock().unwrap_or_else(|e| e.into_inner());
+        let mut data = Vec::with_capacity(len);
+        let end = offset + len as u64;
+        let mut position = offset;
+        while position < end {
+            let index = position / Self::BLOCK_SIZE;
+            let within = (position % Self::BLOCK_SIZE) as usize;
+            let take = (Self::BLOCK_SIZE as usize - within).min((end - position) as usize);
+            let block = self.read_block(&overlay, index)?;
+            data.extend_from_slice(&block[within..within + take]);
+            position += take as u64;
+        }
+        Ok(data)
+    }
+
+    fn set_len(&self, len: u64) -> Result<(), std::io::Error> {
+        let mut overlay = self.overlay.lock().unwrap_or_else(|e| e.into_inner());
+        if len < overlay.len {
+            // Bytes cut off now read back as zeros if the file grows again
+            overlay.file_len = overlay.file_len.min(len);
+            let first_dropped = len.div_ceil(Self::BLOCK_SIZE);
+            overlay.blocks.retain(|&index, _| index < first_dropped);
+            let within = (len % Self::BLOCK_SIZE) as usize;
+            if within > 0 {
+                if let Some(block) = overlay.blocks.get_mut(&(len / Self::BLOCK_SIZE)) {
+                    block[within..].fill(0);
+                }
+            }
+        }
+        overlay.len = len;
+        Ok(())
+    }
+
+    fn sync_data(&self, _eventual: bool) -> Result<(), std::io::Error> {
+        Ok(())
+    }
+
+    fn write(&self, offset: u64, data: &[u8]) -> Result<(), std::io::Error> {
+        let mut overlay = self.overlay.lock().unwrap_or_else(|e| e.into_inner());
+        let mut written = 0;
+        while written < data.len() {
+            let position = offset + written as u64;
+            let index = position / Self::BLOCK_SIZE;
+            let within = (position % Self::BLOCK_SIZE) as usize;
+            let take = (Self::BLOCK_SIZE as usize - within).min(data.len() - written);
+            let mut block = self.read_block(&overlay, index)?;
+            block[within..within + take].copy_from_slice(&data[written..written + take]);
+            overlay.blocks.insert(index, block);
+            written += take;
+        }
+        Ok(())
+    }
+}
+
+/// A storage backend counting the reads redb makes on a cache miss
+#[derive(Debug)]
+struct CountingBackend<B> {
+    inner: B,
+    counter: Arc<ReadCounter>,
+}
+
+impl<B: redb::StorageBackend> redb::StorageBackend for CountingBackend<B> {
+    fn len(&self) -> Result<u64, std::io::Error> {
+        self.inner.len()
+    }
+
+    fn read(&self, offset: u64, len: usize) -> Result<Vec<u8>, std::io::Error> {
+        self.counter.reads.fetch_add(1, Ordering::Relaxed);
+        self.counter.bytes.fetch_add(len as u64, Ordering::Relaxed);
+        self.inner.read(offset, len)
+    }
+
+    fn set_len(&self, len: u64) -> Result<(), std::io::Error> {
+        self.inner.set_len(len)
+    }
+
+    fn sync_data(&self, eventual: bool) -> Result<(), std::io::Error> {
+        self.inner.sync_data(eventual)
+    }
+
+    fn write(&self, offset: u64, data: &[u8]) -> Result<(), std::io::Error> {
+        self.inner.write(offset, data)
+    }
+}
+
+/// Configures and opens a [`RedbStore`]
+///
+/// Created by [`RedbStore::builder`]. Every option defaults to the behavior of
+/// [`RedbStore::open_or_create`], so only the options that differ need to be set.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use redb::Durability;
+/// use redb_wallet_storage::RedbStore;
+///
+/// let store = RedbStore::builder()
+///     .table_name("wallets")
+///     .namespace("savings")
+///     .durability(Durability::Eventual)
+///     .max_changeset_bytes(Some(16 * 1024 * 1024))
+///     .open_or_create("app.redb")
+///     .unwrap();
+/// ```
+///
+#[derive(Debug, Clone, Default)]
+pub struct RedbStoreBuilder {
+    table_name: Option<String>,
+    namespace: Option<String>,
+    cache_size: Option<usize>,
+    durability: Option<redb::Durability>,
+    max_changeset_bytes: Option<usize>,
+    read_only: bool,
+    recoverable: bool,
+    track_reads: bool,
+    allow_descriptor_change: bool,
+    key_prefixes: KeyPrefixes,
+}
+
+impl RedbStoreBuilder {
+    /// Keep the wallet in a custom table, see [`RedbStore::open_or_create_with_table`]
+    pub fn table_name(mut self, table_name: &str) -> Self {
+        self.table_name = Some(table_name.to_string());
+        self
+    }
+
+    /// Keep the wallet under a namespace, see [`RedbStore::from_database_with_namespace`]
+    pub fn namespace(mut self, namespace: &str) -> Self {
+        self.namespace = Some(namespace.to_string());
+        self
+    }
+
+    /// Set redb's page cache size in bytes
+    pub fn cache_size(mut self, bytes: usize) -> Self {
+        self.cache_size = Some(bytes);
+        self
+    }
+
+    /// Set the commit durability, see [`RedbStore::set_durability`]
+    pub fn durability(mut self, durability: redb::Durability) -> Self {
+        self.durability = Some(durability);
+        self
+    }
+
+    /// Limit the stored changeset size, see [`RedbStore::set_max_changeset_bytes`]
+    pub fn max_changeset_bytes(mut self, limit: Option<usize>) -> Self {
+        self.max_changeset_bytes = limit;
+        self
+    }
+
+    /// Open the store read-only
+    ///
+    /// A read-only store never creates the file and rejects every write with
+    /// [`RedbError::ReadOnly`]. redb still locks the file exclusively while it is open.
+    pub fn read_only(mut self, read_only: bool) -> Self {
+        self.read_only = read_only;
+        self
+    }
+
+    /// Recover from an unreadable changeset, see [`RedbStore::open_or_create_recoverable`]
+    pub fn recoverable(mut self, recoverable: bool) -> Self {
+        self.recoverable = recoverable;
+        self
+    }
+
+    /// Let persists replace a wallet with different descriptors, see
+    /// [`RedbStore::set_allow_descriptor_change`]
+    pub fn allow_descriptor_change(mut self, allow: bool) -> Self {
+        self.allow_descriptor_change = allow;
+        self
+    }
+
+    /// Store the wallet under custom keys, see [`KeyPrefixes`]
+    ///
+    /// The same prefixes must be used every time the file is opened; a store opened with
+    /// other prefixes sees no wallet.
+    pub fn key_prefixes(mut self, key_prefixes: KeyPrefixes) -> Self {
+        self.key_prefixes = key_prefixes;
+        self
+    }
+
+    /// Count the reads that go to the file, see [`RedbStore::cache_stats`]
+    ///
+    /// The file is accessed through a counting wrapper around redb's file backend, which
+    /// adds a little overhead to every cache miss.
+    pub fn track_reads(mut self, track_reads: bool) -> Self {
+        self.track_reads = track_reads;
+        self
+    }
+
+    /// Open the store at `file_path`, creating the file unless the store is read-only
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// - The namespace or the key prefixes are invalid
+    /// - The file cannot be opened or created
+    /// - The store is read-only and the file doesn't exist
+    /// - The wallet table cannot be created
+    ///
+    pub fn open_or_create<P>(self, file_path: P) -> Result<RedbStore, RedbError>
+    where
+        P: AsRef<Path>,
+    {
+        if let Some(namespace) = &self.namespace {
+            validate_namespace(namespace)?;
+        }
+        self.key_prefixes.validate()?;
+
+        let mut config = redb::Builder::new();
+        if let Some(bytes) = self.cache_size {
+            config.set_cache_size(bytes);
+        }
+
+        let mut store = if self.track_reads {
+            RedbStore::open_counting(file_path.as_ref(), &config, self.read_only)?
+        } else if self.read_only || std::fs::metadata(file_path.as_ref()).is_ok_and(|m| m.len() > 0)
+        {
+            RedbStore::open_with_config(file_path, config)?
+        } else {
+            // The creation time is recorded below, once the store's scope is configured
+            let path = file_path.as_ref().to_path_buf();
+            let mut store = RedbStore::from_parts(config.create(&path)?, path);
+            store.create_table(false)?;
+            store
+        };
+
+        if let Some(table_name) = self.table_name {
+            store.table_name = table_name;
+        }
+        store.namespace = self.namespace;
+        if let Some(durability) = self.durability {
+            store.durability = durability;
+        }
+        store.max_changeset_bytes = self.max_changeset_bytes;
+        store.read_only = self.read_only;
+        store.recoverable = self.recoverable;
+        store.allow_descriptor_change = self.allow_descriptor_change;
+        store.keys = self.key_prefixes;
+        store.cache_size = self.cache_size;
+        // The builder's settings are all recorded, so the store can be reopened with them
+        store.custom_config = false;
+
+        if !store.read_only {
+            store.init_table()?;
+        }
+        Ok(store)
+    }
+}
+
+/// Sent to [`RedbStore::subscribe`] receivers after each successful commit
+#[cfg(feature = "tokio")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PersistEvent {
+    /// The namespace of the store that committed, `None` for the un-namespaced wallet
+    pub namespace: Option<String>,
+    /// The changeset version after the commit, see [`RedbStore::version`]
+    pub version: u64,
+}
+
+/// Controls the background task started by [`RedbStore::spawn_maintenance`]
+///
+/// Dropping the handle stops the task, like [`MaintenanceHandle::abort`].
+#[cfg(feature = "tokio")]
+#[derive(Debug)]
+pub struct MaintenanceHandle {
+    task: tokio::task::JoinHandle<()>,
+    errors: tokio::sync::mpsc::UnboundedReceiver<RedbError>,
+    cycles: Arc<std::sync::atomic::AtomicU64>,
+}
+
+#[cfg(feature = "tokio")]
+impl MaintenanceHandle {
+    /// Stop the maintenance task
+    ///
+    /// A cycle already running on the blocking thread pool finishes first, so no write
+    /// is cut short; no further cycle starts.
+    pub fn abort(self) {
+        self.task.abort();
+    }
+
+    /// The errors of failed cycles, in the order they happened
+    ///
+    /// Errors queue up until received. The channel closes once the task has stopped.
+    pub fn errors(&mut self) -> &mut tokio::sync::mpsc::UnboundedReceiver<RedbError> {
+        &mut self.errors
+    }
+
+    /// The number of cycles that have run, failed ones included
+    pub fn cycles(&self) -> u64 {
+        self.cycles.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(feature = "tokio")]
+impl Drop for MaintenanceHandle {
+    fn drop(&mut self) {
+        self.task.abort();
+    }
+}
+
+/// A consistent view of a store at one point in time
+///
+/// Created by [`RedbStore::snapshot`]. Every read goes through the same redb read
+/// transaction, which is held until the snapshot is dropped.
+pub struct StoreSnapshot<'a> {
+    store: &'a RedbStore,
+    read_txn: redb::ReadTransaction,
+}
+
+impl StoreSnapshot<'_> {
+    /// Get the stored changeset, or `None` if none has been persisted
+    ///
+    /// Unfolded log entries are merged in, as `initialize` does.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table cannot be read or the changeset cannot be
+    /// deserialized.
+    ///
+    pub fn changeset(&self) -> Result<Option<ChangeSet>, RedbError> {
+        let table = self.read_txn.open_table(self.store.wallet_table())?;
+        let key = self.store.changeset_key(&table)?;
+        let mut changeset = match StoredChangeSet::read(&table, &key)? {
+            Some(stored) => Some(
+                stored
+                    .decode(&self.store.deserialize_limits)
+                    .map_err(RedbError::Deserialization)?,
+            ),
+            None => None,
+        };
+        let mut unfolded = ChangeSet::default();
+        self.store.merge_log(&self.read_txn, &mut unfolded)?;
+        if !unfolded.is_empty() {
+            changeset
+                .get_or_insert_with(ChangeSet::default)
+                .merge(unfolded);
+        }
+        Ok(changeset)
+    }
+
+    /// Read a piece of application metadata, see [`RedbStore::get_metadata`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table cannot be read.
+    ///
+    pub fn metadata(&self, key: &str) -> Result<Option<Vec<u8>>, RedbError> {
+        let table = self.read_txn.open_table(self.store.wallet_table())?;
+        let metadata_key = self
+            .store
+            .scoped_key(&format!("{}{}", METADATA_PREFIX, key));
+        Ok(table
+            .get(metadata_key.as_str())?
+            .map(|value| value.value().to_vec()))
+    }
+
+    /// Get the changeset version, see [`RedbStore::version`]
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the table cannot be read.
+    ///
+    pub fn version(&self) -> Result<u64, RedbError> {
+        let table = self.read_txn.open_table(self.store.wallet_table())?;
+        read_version(&table, &self.store.scoped_key(VERSION_KEY))
+    }
+}
+
+/// A batch of persists committed as a single redb write transaction
+///
+/// Created by [`RedbStore::transaction`]. Changesets passed to [`StoreTxn::persist`]
+/// are merged in memory and written to the database when [`StoreTxn::commit`] is called.
+/// If the guard is dropped without committing, the transaction is rolled back and the
+/// stored changeset is left untouched.
+pub struct StoreTxn<'a> {
+    store: &'a mut RedbStore,
+    write_txn: redb::WriteTransaction,
+    staged: ChangeSet,
+}
+
+impl StoreTxn<'_> {
+    /// Stage a changeset in the transaction
+    ///
+    /// The changeset is validated and merged with previously staged changes, but nothing
+    /// is written until [`StoreTxn::commit`].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the changeset's network does not match the store's network.
+    ///
+    pub fn persist(&mut self, changeset: &ChangeSet) -> Result<(), RedbError> {
+        // Read through the write transaction: beginning another one on the store could
+        // wait for a compaction that is itself waiting for this transaction
+        let table = self.write_txn.open_table(self.store.wallet_table())?;
+        if let (Some(expected), Some(found)) = (self.store.network_in(&table)?, changeset.network) {
+            if expected != found {
+                return Err(RedbError::NetworkMismatch { expected, found });
+            }
+        }
+        drop(table);
+        self.staged.merge(changeset.clone());
+        Ok(())
+    }
+
+    /// Merge all staged changesets into the stored one and commit
+    ///
+    /// The `on_persist` callback, if registered, fires once for the whole transaction.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if serialization, database access or the commit fails. In that
+    /// case nothing is written.
+    ///
+    pub fn commit(self) -> Result<(), RedbError> {
+        if self.staged.is_empty() {
+            return Ok(());
+        }
+
+        let mut staged = self.staged;
+        let folded_log = self.store.fold_log_before(&self.write_txn, &mut staged)?;
+        let merged = {
+            let mut table = self.write_txn.open_table(self.store.wallet_table())?;
+            self.store.merge_into_table(&mut table, staged)?
+        };
+
+        let Some(merged) = merged else {
+            if folded_log {
+                self.write_txn.commit()?;
+            } else {
+                self.write_txn.abort()?;
+            }
+            return Ok(());
+        };
+        self.write_txn.commit()?;
+
+        // Notify only once the data is committed
+        self.store.notify_persisted(&merged);
+
+        Ok(())
+    }
+}
+
+/// Details about a changeset that could not be read and was set aside
+///
+/// Returned by [`RedbStore::last_recovery_info`] after a recoverable store discarded an
+/// unreadable changeset during `initialize`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecoveryInfo {
+    /// The deserialization error that triggered the recovery
+    pub error: String,
+    /// The key the unreadable bytes were moved to
+    pub corrupt_key: String,
+    /// The length of the unreadable bytes
+    pub corrupt_len: usize,
+}
+
+/// Progress of [`RedbStore::initialize_with_progress`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LoadProgress {
+    /// The number of segments deserialized and merged so far
+    pub segments_read: usize,
+    /// The total number of segments to load
+    pub total_segments: usize,
+}
+
+impl LoadProgress {
+    /// Whether every segment has been loaded
+    pub fn is_complete(&self) -> bool {
+        self.segments_read == self.total_segments
+    }
+}
+
+/// Storage used by one wallet, as returned by [`RedbStore::usage_report`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletUsage {
+    /// The wallet's namespace, empty for the un-namespaced wallet
+    pub namespace: String,
+    /// The size of the stored changeset in bytes
+    pub bytes: usize,
+    /// When the changeset was last changed, if recorded
+    pub last_modified: Option<std::time::SystemTime>,
+}
+
+/// A human-oriented summary of a stored wallet, as returned by [`RedbStore::describe`]
+///
+/// Every field is empty for a store that has never been persisted to.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct WalletSummary {
+    /// The wallet's network, if recorded
+    pub network: Option<Network>,
+    /// The external descriptor, with its checksum
+    pub descriptor: Option<String>,
+    /// The internal (change) descriptor, with its checksum
+    pub change_descriptor: Option<String>,
+    /// The last revealed index of each keychain with revealed addresses
+    pub last_revealed: BTreeMap<KeychainKind, u32>,
+    /// The number of transactions in the transaction graph
+    pub tx_count: usize,
+    /// The height of the local chain's tip, if any block is stored
+    pub tip_height: Option<u32>,
+}
+
+impl std::fmt::Display for WalletSummary {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let or_none = |value: Option<String>| value.unwrap_or_else(|| "none".to_string());
+        writeln!(
+            f,
+            "Network:           {}",
+            or_none(self.network.map(|n| n.to_string()))
+        )?;
+        writeln!(f, "External:          {}", or_none(self.descriptor.clone()))?;
+        writeln!(
+            f,
+            "Internal:          {}",
+            or_none(self.change_descriptor.clone())
+        )?;
+        for (keychain, label) in [
+            (KeychainKind::External, "External index:    "),
+            (KeychainKind::Internal, "Internal index:    "),
+        ] {
+            let index = self.last_revealed.get(&keychain).map(u32::to_string);
+            writeln!(f, "{}{}", label, or_none(index))?;
+        }
+        writeln!(f, "Transactions:      {}", self.tx_count)?;
+        write!(
+            f,
+            "Tip height:        {}",
+            or_none(self.tip_height.map(|h| h.to_string()))
+        )
+    }
+}
+
+/// A store's metrics, as returned by [`RedbStore::stats`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RedbStoreStats {
+    /// The size of the database file in bytes, `0` if the store has no file
+    pub file_size: u64,
+    /// The size of this store's serialized changeset in bytes, `0` if none is stored
+    pub changeset_bytes: usize,
+    /// The number of entries in the wallet table, metadata and other wallets included
+    pub entry_count: u64,
+    /// The number of wallets with a stored changeset in the table, the un-namespaced
+    /// one included
+    pub namespace_count: usize,
+    /// The format version of this store's changeset, `1` if none is recorded
+    pub changeset_format_version: u32,
+    /// When this store's changeset was last changed, if recorded
+    pub last_persisted: Option<std::time::SystemTime>,
+}
+
+/// The outcome of [`RedbStore::repair`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RepairReport {
+    /// Bytes of data carried over to the repaired database
+    pub recovered_bytes: u64,
+    /// Bytes of data that were unreadable or of a table type that can't be copied, and
+    /// were dropped, or the size of the whole file if it could not be opened at all
+    pub dropped_bytes: u64,
+}
+
+/// A checkpoint taken with [`RedbStore::create_checkpoint`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CheckpointInfo {
+    /// The id to pass to [`RedbStore::reset_to_checkpoint`]
+    pub id: u64,
+    /// When the checkpoint was taken
+    pub created_at: std::time::SystemTime,
+    /// The size of the saved changeset in bytes
+    pub bytes: usize,
+}
+
+/// Limits applied when deserializing stored JSON, see
+/// [`RedbStore::set_deserialize_limits`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DeserializeLimits {
+    /// The largest value, in bytes, that will be deserialized
+    pub max_bytes: usize,
+    /// The deepest nesting of JSON arrays and objects that will be deserialized
+    pub max_depth: usize,
+}
+
+impl Default for DeserializeLimits {
+    /// 1 GiB and 64 levels, far beyond any real changeset
+    fn default() -> Self {
+        Self {
+            max_bytes: 1 << 30,
+            max_depth: 64,
+        }
+    }
+}
+
+/// The keys a store keeps its wallet under, see [`RedbStoreBuilder::key_prefixes`]
+///
+/// Lets the wallet share a table with an application whose own keys would collide with
+/// the defaults. The prefixes must be non-empty, and none may be a prefix of another or
+/// of a key the store uses internally.
+///
+/// A few keys stay fixed whatever the prefixes are:
+/// - `ns::<namespace>::`, the prefix namespaces wrap these keys in
+/// - `fingerprint::`, the index from descriptor fingerprints to namespaces
+/// - `corrupt::wallet_changeset`, where a recoverable store moves an unreadable changeset
+/// - `log::`, the prefix of unfolded changesets with the `wal` feature, which live in a
+///   table of their own next to the wallet table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyPrefixes {
+    /// The key of the stored changeset, `"wallet_changeset"` by default
+    pub changeset_key: String,
+    /// The prefix of metadata keys, such as the network and timestamps, `"meta::"` by
+    /// default
+    pub metadata_prefix: String,
+    /// The prefix of profile changesets, `"profile::"` by default
+    pub profile_prefix: String,
+    /// The prefix of checkpoints, `"checkpoint::"` by default
+    pub checkpoint_prefix: String,
+}
+
+impl Default for KeyPrefixes {
+    fn default() -> Self {
+        Self {
+            changeset_key: CHANGESET_KEY.to_string(),
+            metadata_prefix: METADATA_PREFIX.to_string(),
+            profile_prefix: PROFILE_PREFIX.to_string(),
+            checkpoint_prefix: CHECKPOINT_PREFIX.to_string(),
+        }
+    }
+}
+
+impl KeyPrefixes {
+    /// Check that the keys are non-empty and can't be confused with each other
+    fn validate(&self) -> Result<(), RedbError> {
+        let keys = [
+            self.changeset_key.as_str(),
+            self.metadata_prefix.as_str(),
+            self.profile_prefix.as_str(),
+            self.checkpoint_prefix.as_str(),
+        ];
+        let internal = [NAMESPACE_PREFIX, FINGERPRINT_PREFIX, CORRUPT_CHANGESET_KEY];
+
+        for (i, key) in keys.iter().enumerate() {
+            if key.is_empty() {
+                return Err(RedbError::InvalidKeyPrefixes(
+                    "key prefixes must not be empty".to_string(),
+                ));
+            }
+            let others = keys[i + 1..].iter().chain(internal.iter());
+            for other in others {
+                if key.starts_with(other) || other.starts_with(key) {
+                    return Err(RedbError::InvalidKeyPrefixes(format!(
+                        "{:?} overlaps {:?}",
+                        key, other
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Whether `key` holds a changeset: the changeset key or a profile's, in any namespace
+    ///
+    /// Segments other than the descriptors segment at the changeset key itself don't count.
+    fn is_changeset_key(&self, key: &str) -> bool {
+        let (_, local) = split_namespace(key);
+        local == self.changeset_key
+            || local
+                .strip_prefix(self.profile_prefix.as_str())
+                .and_then(|rest| rest.strip_suffix(self.changeset_key.as_str()))
+                .and_then(|profile| profile.strip_suffix("::"))
+                .is_some_and(|profile| !profile.contains("::"))
+    }
+
+    /// Map a key built from the default constants to these keys
+    fn translate(&self, key: &str) -> String {
+        if key == CHANGESET_KEY {
+            return self.changeset_key.clone();
+        }
+        let prefixes = [
+            (METADATA_PREFIX, &self.metadata_prefix),
+            (PROFILE_PREFIX, &self.profile_prefix),
+            (CHECKPOINT_PREFIX, &self.checkpoint_prefix),
+        ];
+        for (default, custom) in prefixes {
+            if let Some(rest) = key.strip_prefix(default) {
+                return format!("{}{}", custom, rest);
+            }
+        }
+        key.to_string()
+    }
+}
+
+/// Describes the wallet in a bundle written by [`RedbStore::export_bundle`]
+///
+/// Timestamps are in seconds since the Unix epoch.
+#[cfg(feature = "bundle")]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+pub struct BundleManifest {
+    /// The version of the bundle format
+    pub schema_version: u32,
+    /// The wallet's network, if known
+    pub network: Option<Network>,
+    /// The wallet's [`RedbStore::descriptor_fingerprint`], if it has descriptors
+    pub descriptor_fingerprint: Option<String>,
+    /// When the wallet was first persisted, if recorded
+    pub created_at: Option<u64>,
+    /// When the wallet was last persisted, if recorded
+    pub last_persisted_at: Option<u64>,
+    /// When the bundle was written
+    pub exported_at: u64,
+}
+
+/// The contents of a bundle archive
+#[cfg(feature = "bundle")]
+struct Bundle {
+    manifest: BundleManifest,
+    changeset: ChangeSet,
+}
+
+/// A document written by [`RedbStore::dump_json`]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct Dump {
+    schema_version: u32,
+    namespaces: Vec<NamespaceDump>,
+}
+
+/// One namespace of a [`Dump`], `None` being the un-namespaced wallet
+#[derive(serde::Serialize, serde::Deserialize)]
+struct NamespaceDump {
+    namespace: Option<String>,
+    changeset: Option<ChangeSet>,
+    metadata: BTreeMap<String, Vec<u8>>,
+}
+
+impl NamespaceDump {
+    fn new(namespace: Option<String>) -> Self {
+        Self {
+            namespace,
+            changeset: None,
+            metadata: BTreeMap::new(),
+        }
+    }
+}
+
+/// How often persists are flushed to disk, see [`RedbStore::set_flush_policy`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FlushPolicy {
+    /// Flush every persist before it returns. A persist that returned is never lost.
+    Always,
+    /// Never flush persists. Fastest, but a crash can lose every persist since the last
+    /// flushed write, and the file grows until one happens, as space freed by unflushed
+    /// commits can't be reused before.
+    Never,
+    /// Flush every `n`th persist, so a crash loses at most the `n - 1` persists since
+    /// the last flush. `EveryN(0)` and `EveryN(1)` flush every persist.
+    EveryN(usize),
+}
+
+/// Page cache statistics, as returned by [`RedbStore::cache_stats`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheStats {
+    /// How many times redb evicted pages from its cache because it was full
+    pub evictions: u64,
+    /// How many reads missed the cache and went to the file, if counted
+    pub physical_reads: Option<u64>,
+    /// The number of bytes those reads fetched from the file, if counted
+    pub bytes_read: Option<u64>,
+}
+
+/// The outcome of [`RedbStore::validate`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ValidationReport {
+    /// The serialized size of the stored changeset merged with the validated one, or
+    /// `None` if they conflict and can't be merged
+    pub merged_size: Option<usize>,
+    /// Whether the descriptors agree with the stored and expected ones
+    pub descriptors_match: bool,
+    /// Whether the network agrees with the store's network
+    pub network_matches: bool,
+    /// Whether the merged changeset fits the store's size limit
+    pub within_limit: bool,
+    /// Whether the validated changeset holds no changes
+    pub is_empty: bool,
+}
+
+impl ValidationReport {
+    /// Whether persisting the changeset would succeed
+    pub fn is_valid(&self) -> bool {
+        self.descriptors_match && self.network_matches && self.within_limit
+    }
+}
+
+/// What merging a changeset would change, as returned by [`RedbStore::diff`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChangeSetDiff {
+    /// Whether descriptors would be stored for a keychain that has none yet
+    pub sets_descriptors: bool,
+    /// Whether a network would be stored where none is yet
+    pub sets_network: bool,
+    /// The descriptors whose last revealed index would advance, with the new index
+    pub revealed: BTreeMap<bdk_chain::DescriptorId, u32>,
+    /// The transactions that aren't stored yet
+    pub new_txs: Vec<bitcoin::Txid>,
+    /// The floating outputs that aren't stored yet
+    pub new_txouts: Vec<bitcoin::OutPoint>,
+    /// The transactions that would gain an anchor, once per new anchor
+    pub new_anchors: Vec<bitcoin::Txid>,
+    /// The transactions whose last-seen time would change, with the new time
+    pub last_seen: BTreeMap<bitcoin::Txid, u64>,
+    /// The blocks that would be added, replaced or removed (`None`), by height
+    pub blocks: BTreeMap<u32, Option<bitcoin::BlockHash>>,
+    /// The old and new chain tip heights, if the tip would move
+    pub tip_change: Option<(Option<u32>, Option<u32>)>,
+}
+
+impl ChangeSetDiff {
+    /// Whether merging would change nothing
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+/// The outcome of [`RedbStore::health_check`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthReport {
+    /// Whether every entry of the wallet table could be read
+    pub structure_ok: bool,
+    /// Whether the stored changeset's format is one this version can read
+    pub format_supported: bool,
+    /// Whether the stored changeset matches the checksum recorded with it, or `None` if
+    /// there is no changeset or no checksum
+    pub checksum_matches: Option<bool>,
+    /// Whether the stored changeset deserializes; `true` if there is none
+    pub changeset_readable: bool,
+    /// Whether the wallet is for the expected network, or `None` if no network was
+    /// given. A store without a network passes.
+    pub network_matches: Option<bool>,
+    /// A description of each failed check
+    pub problems: Vec<String>,
+}
+
+impl HealthReport {
+    /// Whether every check passed
+    pub fn is_healthy(&self) -> bool {
+        self.problems.is_empty()
+    }
+}
+
+/// Error type for redb storage operations
+/// This enum represents all possible errors that can occur when using the `RedbStore`.
+/// It wraps errors from the underlying redb database, serialization/deserialization errors,
+/// and I/O errors.
+#[derive(Debug)]
+pub enum RedbError {
+    /// Error from the redb database
+    Database(redb::Error),
+    /// Error serializing data
+    Serialization(serde_json::Error),
+    /// Error deserializing data
+    Deserialization(serde_json::Error),
+    /// I/O error
+    Io(std::io::Error),
+    /// Commit error
+    Commit(redb::CommitError),
+    /// Table error
+    Table(redb::TableError),
+    /// Transaction error
+    Transaction(redb::TransactionError),
+    /// Compaction error
+    Compaction(redb::CompactionError),
+    /// The changeset network does not match the network recorded for the store
+    NetworkMismatch {
+        /// The network recorded for the store
+        expected: Network,
+        /// The network found in the changeset
+        found: Network,
+    },
+    /// The namespace name is empty or contains `::`
+    InvalidNamespace(String),
+    /// The metadata key is reserved for internal use
+    ReservedKey(String),
+    /// The serialized changeset exceeds the configured size limit
+    TooLarge {
+        /// The size of the serialized changeset in bytes
+        size: usize,
+        /// The configured limit in bytes
+        limit: usize,
+    },
+    /// The store was opened read-only
+    ReadOnly,
+    /// The stored descriptor for a keychain differs from the expected one
+    DescriptorMismatch(KeychainKind),
+    /// A descriptor could not be parsed
+    InvalidDescriptor(String),
+    /// Two changesets being merged have different descriptors for a keychain
+    DescriptorConflict(KeychainKind),
+    /// The stored changeset version differs from the one a conditional persist expected
+    VersionConflict {
+        /// The version the caller expected
+        expected: u64,
+        /// The version currently stored
+        actual: u64,
+    },
+    /// The file uses a redb file format this version of redb cannot open
+    IncompatibleFileFormat(redb::Error),
+    /// No checkpoint with the given id exists
+    CheckpointNotFound(u64),
+    /// The configured key prefixes are empty or overlap
+    InvalidKeyPrefixes(String),
+    /// The stored changeset was written in a newer format than this version supports
+    UnsupportedChangeSetVersion {
+        /// The format version recorded with the stored changeset
+        found: u32,
+        /// The newest format version this version of the crate can read
+        supported: u32,
+    },
+    /// The bundle was written in a newer format than this version supports
+    #[cfg(feature = "bundle")]
+    UnsupportedBundleVersion {
+        /// The format version recorded in the bundle's manifest
+        found: u32,
+        /// The newest format version this version of the crate can read
+        supported: u32,
+    },
+    /// Another store holds the lock file of an exclusively opened wallet
+    AlreadyLocked,
+    /// The store opened but failed its health check
+    Unhealthy(Vec<String>),
+    /// The operation needs the only handle to the database, but it is shared with clones
+    /// or other stores
+    SharedDatabase,
+    /// The store no longer has a database to operate on
+    Closed,
+}
+
+impl std::fmt::Display for RedbError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Database(e) => write!(f, "Database error: {}", e),
+            Self::Serialization(e) => write!(f, "Serialization error: {}", e),
+            Self::Deserialization(e) => write!(f, "Deserialization error: {}", e),
+            Self::Io(e) => write!(f, "I/O error: {}", e),
+            Self::Commit(e) => write!(f, "Commit error: {}", e),
+            Self::Table(e) => write!(f, "Table error: {}", e),
+            Self::Transaction(e) => write!(f, "Transaction error: {}", e),
+            Self::Compaction(e) => write!(f, "Compaction error: {}", e),
+            Self::NetworkMismatch { expected, found } => write!(
+                f,
+                "Network mismatch: store is for {}, changeset is for {}",
+                expected, found
+            ),
+            Self::InvalidNamespace(namespace) => write!(f, "Invalid namespace: {:?}", namespace),
+            Self::ReservedKey(key) => write!(f, "Metadata key {:?} is reserved", key),
+            Self::TooLarge { size, limit } => write!(
+                f,
+                "Changeset too large: {} bytes exceeds the limit of {} bytes",
+                size, limit
+            ),
+            Self::ReadOnly => write!(f, "Store is read-only"),
+            Self::DescriptorMismatch(keychain) => write!(
+                f,
+                "Descriptor mismatch: stored {:?} descriptor differs from the expected one",
+                keychain
+            ),
+            Self::InvalidDescriptor(e) => write!(f, "Invalid descriptor: {}", e),
+            Self::DescriptorConflict(keychain) => write!(
+                f,
+                "Descriptor conflict: the wallets have different {:?} descriptors",
+                keychain
+            ),
+            Self::VersionConflict { expected, actual } => write!(
+                f,
+                "Version conflict: expected version {}, found {}",
+                expected, actual
+            ),
+            Self::IncompatibleFileFormat(e) => match e {
+                redb::Error::UpgradeRequired(_) => write!(
+                    f,
+                    "Incompatible file format: {}. The file was written by an older redb \
+                     version and must be upgraded first",
+                    e
+                ),
+                _ => write!(
+                    f,
+                    "Incompatible file format: {}. The file was written by a newer redb \
+                     version than this application uses",
+                    e
+                ),
+            },
+            Self::CheckpointNotFound(id) => write!(f, "Checkpoint {} not found", id),
+            Self::InvalidKeyPrefixes(reason) => write!(f, "Invalid key prefixes: {}", reason),
+            Self::UnsupportedChangeSetVersion { found, supported } => write!(
+                f,
+                "Changeset format version {} is newer than the supported version {}",
+                found, supported
+            ),
+            #[cfg(feature = "bundle")]
+            Self::UnsupportedBundleVersion { found, supported } => write!(
+                f,
+                "Bundle format version {} is newer than the supported version {}",
+                found, supported
+            ),
+            Self::AlreadyLocked => write!(f, "Wallet is locked by another store"),
+            Self::Unhealthy(problems) => write!(f, "Health check failed: {}", problems.join("; ")),
+            Self::SharedDatabase => write!(f, "Database handle is shared with other stores"),
+            Self::Closed => write!(f, "Store is closed"),
+        }
+    }
+}
+
+// impl std::error::Error for RedbError {}
+impl std::error::Error for RedbError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Database(e) => Some(e),
+            Self::Serialization(e) => Some(e),
+            Self::Deserialization(e) => Some(e),
+            Self::Io(e) => Some(e),
+            Self::Commit(e) => Some(e),
+            Self::Table(e) => Some(e),
+            Self::Transaction(e) => Some(e),
+            Self::Compaction(e) => Some(e),
+            Self::NetworkMismatch { .. } => None,
+            Self::InvalidNamespace(_) => None,
+            Self::ReservedKey(_) => None,
+            Self::TooLarge { .. } => None,
+            Self::ReadOnly => None,
+            Self::DescriptorMismatch(_) => None,
+            Self::InvalidDescriptor(_) => None,
+            Self::DescriptorConflict(_) => None,
+            Self::VersionConflict { .. } => None,
+            Self::IncompatibleFileFormat(e) => Some(e),
+            Self::CheckpointNotFound(_) => None,
+            Self::InvalidKeyPrefixes(_) => None,
+            Self::UnsupportedChangeSetVersion { .. } => None,
+            #[cfg(feature = "bundle")]
+            Self::UnsupportedBundleVersion { .. } => None,
+            Self::AlreadyLocked => None,
+            Self::Unhealthy(_) => None,
+            Self::SharedDatabase => None,
+            Self::Closed => None,
+        }
+    }
+}
+
+/// A cheap, copyable classification of a [`RedbError`]
+///
+/// The wrapped redb and serde errors are not `Clone`, which makes a `RedbError` awkward
+/// to cache or pass around. The code keeps just the kind of failure, so callers can
+/// branch on it or serialize it while the original error stays available through
+/// `std::error::Error::source`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RedbErrorCode {
+    /// See [`RedbError::Database`]
+    Database,
+    /// See [`RedbError::Serialization`]
+    Serialization,
+    /// See [`RedbError::Deserialization`]
+    Deserialization,
+    /// See [`RedbError::Io`]
+    Io,
+    /// See [`RedbError::Commit`]
+    Commit,
+    /// See [`RedbError::Table`]
+    Table,
+    /// See [`RedbError::Transaction`]
+    Transaction,
+    /// See [`RedbError::Compaction`]
+    Compaction,
+    /// See [`RedbError::NetworkMismatch`]
+    NetworkMismatch,
+    /// See [`RedbError::InvalidNamespace`]
+    InvalidNamespace,
+    /// See [`RedbError::ReservedKey`]
+    ReservedKey,
+    /// See [`RedbError::TooLarge`]
+    TooLarge,
+    /// See [`RedbError::ReadOnly`]
+    ReadOnly,
+    /// See [`RedbError::DescriptorMismatch`]
+    DescriptorMismatch,
+    /// See [`RedbError::InvalidDescriptor`]
+    InvalidDescriptor,
+    /// See [`RedbError::DescriptorConflict`]
+    DescriptorConflict,
+    /// See [`RedbError::VersionConflict`]
+    VersionConflict,
+    /// See [`RedbError::IncompatibleFileFormat`]
+    IncompatibleFileFormat,
+    /// See [`RedbError::CheckpointNotFound`]
+    CheckpointNotFound,
+    /// See [`RedbError::InvalidKeyPrefixes`]
+    InvalidKeyPrefixes,
+    /// See [`RedbError::UnsupportedChangeSetVersion`]
+    UnsupportedChangeSetVersion,
+    /// See [`RedbError::UnsupportedBundleVersion`]
+    #[cfg(feature = "bundle")]
+    UnsupportedBundleVersion,
+    /// See [`RedbError::AlreadyLocked`]
+    AlreadyLocked,
+    /// See [`RedbError::Unhealthy`]
+    Unhealthy,
+    /// See [`RedbError::SharedDatabase`]
+    SharedDatabase,
+    /// See [`RedbError::Closed`]
+    Closed,
+}
+
+impl RedbError {
+    /// Whether this error means the file is damaged or not a redb file at all
+    fn is_corruption(&self) -> bool {
+        let damaged = |e: &std::io::Error| {
+            matches!(
+                e.kind(),
+                std::io::ErrorKind::InvalidData | std::io::ErrorKind::UnexpectedEof
+            )
+        };
+        let storage = match self {
+            Self::Database(redb::Error::Corrupted(_)) => return true,
+            Self::Database(redb::Error::Io(e)) | Self::Io(e) => return damaged(e),
+            Self::Commit(redb::CommitError::Storage(e))
+            | Self::Transaction(redb::TransactionError::Storage(e))
+            | Self::Table(redb::TableError::Storage(e)) => e,
+            _ => return false,
+        };
+        match storage {
+            redb::StorageError::Corrupted(_) => true,
+            redb::StorageError::Io(e) => damaged(e),
+            _ => false,
+        }
+    }
+
+    /// Whether this is an I/O-class storage error that may succeed on retry
+    fn is_transient(&self) -> bool {
+        let storage = match self {
+            Self::Database(redb::Error::Io(_) | redb::Error::PreviousIo) => return true,
+            Self::Commit(redb::CommitError::Storage(e))
+            | Self::Transaction(redb::TransactionError::Storage(e))
+            | Self::Table(redb::TableError::Storage(e)) => e,
+            _ => return false,
+        };
+        matches!(
+            storage,
+            redb::StorageError::Io(_) | redb::StorageError::PreviousIo
+        )
+    }
+
+    /// Get the [`RedbErrorCode`] classifying this error
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use redb_wallet_storage::{RedbErrorCode, RedbStore};
+    ///
+    /// match RedbStore::open("wallet.redb") {
+    ///     Ok(_) => println!("Opened"),
+    ///     Err(e) if e.code() == RedbErrorCode::Io => println!("Could not access the file"),
+    ///     Err(e) => println!("Error: {}", e),
+    /// }
+    /// ```
+    ///
+    pub fn code(&self) -> RedbErrorCode {
+        match self {
+            Self::Database(_) => RedbErrorCode::Database,
+            Self::Serialization(_) => RedbErrorCode::Serialization,
+            Self::Deserialization(_) => RedbErrorCode::Deserialization,
+            Self::Io(_) => RedbErrorCode::Io,
+            Self::Commit(_) => RedbErrorCode::Commit,
+            Self::Table(_) => RedbErrorCode::Table,
+            Self::Transaction(_) => RedbErrorCode::Transaction,
+            Self::Compaction(_) => RedbErrorCode::Compaction,
+            Self::NetworkMismatch { .. } => RedbErrorCode::NetworkMismatch,
+            Self::InvalidNamespace(_) => RedbErrorCode::InvalidNamespace,
+            Self::ReservedKey(_) => RedbErrorCode::ReservedKey,
+            Self::TooLarge { .. } => RedbErrorCode::TooLarge,
+            Self::ReadOnly => RedbErrorCode::ReadOnly,
+            Self::DescriptorMismatch(_) => RedbErrorCode::DescriptorMismatch,
+            Self::InvalidDescriptor(_) => RedbErrorCode::InvalidDescriptor,
+            Self::DescriptorConflict(_) => RedbErrorCode::DescriptorConflict,
+            Self::VersionConflict { .. } => RedbErrorCode::VersionConflict,
+            Self::IncompatibleFileFormat(_) => RedbErrorCode::IncompatibleFileFormat,
+            Self::CheckpointNotFound(_) => RedbErrorCode::CheckpointNotFound,
+            Self::InvalidKeyPrefixes(_) => RedbErrorCode::InvalidKeyPrefixes,
+            Self::UnsupportedChangeSetVersion { .. } => RedbErrorCode::UnsupportedChangeSetVersion,
+            #[cfg(feature = "bundle")]
+            Self::UnsupportedBundleVersion { .. } => RedbErrorCode::UnsupportedBundleVersion,
+            Self::AlreadyLocked => RedbErrorCode::AlreadyLocked,
+            Self::Unhealthy(_) => RedbErrorCode::Unhealthy,
+            Self::SharedDatabase => RedbErrorCode::SharedDatabase,
+            Self::Closed => RedbErrorCode::Closed,
+        }
+    }
+}
+
+impl From<redb::DatabaseError> for RedbError {
+    fn from(e: redb::DatabaseError) -> Self {
+        let e = redb::Error::from(e);
+        // redb reports a file from a newer redb as corrupted, naming the version it found
+        let incompatible = match &e {
+            redb::Error::UpgradeRequired(_) => true,
+            redb::Error::Corrupted(message) => message.starts_with("Expected file format version"),
+            _ => false,
+        };
+        if incompatible {
+            Self::IncompatibleFileFormat(e)
+        } else {
+            Self::Database(e)
+        }
+    }
+}
+
+impl From<redb::StorageError> for RedbError {
+    fn from(e: redb::StorageError) -> Self {
+        Self::Database(e.into())
+    }
+}
+
+impl From<redb::Error> for RedbError {
+    fn from(e: redb::Error) -> Self {
+        Self::Database(e)
+    }
+}
+
+impl From<redb::CompactionError> for RedbError {
+    fn from(e: redb::CompactionError) -> Self {
+        Self::Compaction(e)
+    }
+}
+
+impl From<serde_json::Error> for RedbError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::Serialization(e)
+    }
+}
+
+impl From<std::io::Error> for RedbError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+impl From<redb::CommitError> for RedbError {
+    fn from(e: redb::CommitError) -> Self {
+        Self::Commit(e)
+    }
+}
+
+impl From<redb::TableError> for RedbError {
+    fn from(e: redb::TableError) -> Self {
+        Self::Table(e)
+    }
+}
+
+impl From<redb::TransactionError> for RedbError {
+    fn from(e: redb::TransactionError) -> Self {
+        Self::Transaction(e)
+    }
+}
+
+impl From<RedbError> for bdk_wallet::LoadWithPersistError<RedbError> {
+    fn from(e: RedbError) -> Self {
+        Self::Persist(e)
+    }
+}
+
+impl From<RedbError> for bdk_wallet::CreateWithPersistError<RedbError> {
+    fn from(e: RedbError) -> Self {
+        Self::Persist(e)
+    }
+}
+
+/// Type alias for a pinned, boxed future that can be returned by async methods
+type FutureResult<'a, T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'a>>;
+
+impl WalletPersister for RedbStore {
+    type Error = RedbError;
+
+    /// Initialize the wallet persister by loading the stored changeset
+    ///
+    /// This method is called by BDK when a wallet is being loaded.
+    /// It retrieves the stored wallet changeset from the database or returns
+    /// an empty changeset if none exists.
+    ///
+    /// Everything is read in a single redb read transaction: the stored changeset and,
+    /// with the `wal` feature, the unfolded log entries in their own table. A concurrent
+    /// write that spans both tables, such as `RedbStore::fold_log`, is seen either
+    /// completely or not at all.
+    ///
+    /// # Returns
+    ///
+    /// - The stored wallet changeset, or an empty changeset if none exists
+    /// - An error if database access or deserialization fails
+    ///
+    fn initialize(persister: &mut Self) -> Result<ChangeSet, Self::Error> {
+        // Get changeset or return empty if none exists
+        persister.initialize_changeset()
+    }
+
+    /// Persist a wallet changeset to the database
+    ///
+    /// This method is called by BDK when wallet changes need to be saved.
+    /// It merges the new changeset with any existing one and stores the result.
+    ///
+    /// # Arguments
+    ///
+    /// * `changeset` - The wallet changeset to persist
+    ///
+    /// # Returns
+    ///
+    /// - `Ok(())` if the changeset was successfully stored
+    /// - An error if serialization or database access fails
+    ///
+    fn persist(persister: &mut Self, changeset: &ChangeSet) -> Result<(), Self::Error> {
+        persister.persist_retrying(changeset).map(|_| ())
+    }
+}
+
+impl AsyncWalletPersister for RedbStore {
+    type Error = RedbError;
+
+    /// Initialize the wallet persister asynchronously by loading the stored changeset
+    ///
+    /// This method is called by BDK when a wallet is being loaded asynchronously.
+    /// It retrieves the stored wallet changeset from the database or returns
+    /// an empty changeset if none exists.
+    ///
+    /// # Returns
+    ///
+    /// - A future that resolves to the stored wallet changeset, or an empty changeset if none exists
+    /// - An error if database access or deserialization fails
+    ///
+    fn initialize<'a>(persister: &'a mut Self) -> FutureResult<'a, ChangeSet, Self::Error>
+    where
+        Self: 'a,
+    {
+        Box::pin(async move {
+            // Get changeset or return empty if none exists
+            persister.initialize_changeset()
+        })
+    }
+
+    /// Persist a wallet changeset to the database asynchronously
+    ///
+    /// This method is called by BDK when wallet changes need to be saved asynchronously.
+    /// It merges the new changeset with any existing one and stores the result.
+    ///
+    /// # Arguments
+    ///
+    /// * `changeset` - The wallet changeset to persist
+    ///
+    /// # Returns
+    ///
+    /// - A future that resolves to `Ok(())` if the changeset was successfully stored
+    /// - An error if serialization or database access fails
+    ///
+    /// # Cancellation
+    ///
+    /// Dropping the future never leaves a partial write behind. By default the whole
+    /// read-merge-commit runs within a single poll, with no await point between starting
+    /// and committing the write transaction. With the `tokio` feature the write runs on
+    /// a `spawn_blocking` task instead, so the executor thread isn't blocked; once the
+    /// future has been polled, the write completes even if the future is dropped. Polled
+    /// outside a tokio runtime, it writes within the poll as by default.
+    ///
+    /// The `async-std` feature offloads the write the same way, to async-std's
+    /// `spawn_blocking` thread pool, which runs without an async-std executor too. If
+    /// both features are enabled, `tokio` is used.
+    fn persist<'a>(
+        persister: &'a mut Self,
+        changeset: &'a ChangeSet,
+    ) -> FutureResult<'a, (), Self::Error>
+    where
+        Self: 'a,
+    {
+        #[cfg(feature = "tokio")]
+        return Box::pin(async move {
+            let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+                return persister.persist_retrying(changeset).map(|_| ());
+            };
+            let offload = |store: RedbStore, changeset: ChangeSet| {
+                let task = runtime.spawn_blocking(move || store.persist_changeset(&changeset));
+                async move {
+                    task.await
+                        .map_err(|e| RedbError::Io(std::io::Error::other(e)))?
+                }
+            };
+            persister
+                .persist_offloaded(changeset, offload, tokio::time::sleep)
+                .await
+                .map(|_| ())
+        });
+
+        #[cfg(all(feature = "async-std", not(feature = "tokio")))]
+        return Box::pin(async move {
+            let offload = |store: RedbStore, changeset: ChangeSet| {
+                async_std::task::spawn_blocking(move || store.persist_changeset(&changeset))
+            };
+            persister
+                .persist_offloaded(changeset, offload, async_std::task::sleep)
+                .await
+                .map(|_| ())
+        });
+
+        #[cfg(not(any(feature = "tokio", feature = "async-std")))]
+        Box::pin(async move { persister.persist_retrying(changeset).map(|_| ()) })
+    }
+}
+
+/// Changeset (de)serialization, independent of redb
+///
+/// Everything here needs only `serde` and `serde_json`: no database, file or other I/O
+/// is involved, so the functions can be reused and tested without a [`RedbStore`]. The
+/// crate itself needs `std` because redb does; within this module, only the JSON
+/// reader and writer used with redb's value buffers rely on `std::io`.
+pub mod codec {
+    use crate::DeserializeLimits;
+    use bdk_wallet::descriptor::{Descriptor, DescriptorPublicKey};
+    use bdk_wallet::ChangeSet;
+    use bitcoin::Network;
+    use std::borrow::Cow;
+
+    /// The segments a stored changeset is split into, in the order they are stored
+    ///
+    /// The descriptors segment holds the descriptors and the network; the others hold the
+    /// `ChangeSet` field of the same name.
+    pub(crate) const SEGMENTS: [&str; 4] = ["descriptors", "local_chain", "tx_graph", "indexer"];
+
+    /// The descriptors segment of a changeset
+    #[derive(serde::Serialize, serde::Deserialize)]
+    struct DescriptorSegment<'a> {
+        descriptor: Cow<'a, Option<Descriptor<DescriptorPublicKey>>>,
+        change_descriptor: Cow<'a, Option<Descriptor<DescriptorPublicKey>>>,
+        network: Cow<'a, Option<Network>>,
+    }
+
+    /// Serialize a changeset to JSON, indented if `pretty` is set
+    ///
+    /// [`crate::RedbStore`] stores changesets split into segments, which join back into this
+    /// format.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the changeset cannot be serialized.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::ChangeSet;
+    /// use redb_wallet_storage::codec;
+    ///
+    /// let bytes = codec::encode_changeset(&ChangeSet::default(), false).unwrap();
+    /// ```
+    ///
+    pub fn encode_changeset(
+        changeset: &ChangeSet,
+        pretty: bool,
+    ) -> Result<Vec<u8>, serde_json::Error> {
+        let mut bytes = Vec::new();
+        write_json(changeset, pretty, &mut bytes)?;
+        Ok(bytes)
+    }
+
+    /// Serialize the segment at `index` in [`SEGMENTS`] of a changeset into a writer
+    pub(crate) fn encode_segment_to<W: std::io::Write>(
+        changeset: &ChangeSet,
+        index: usize,
+        pretty: bool,
+        writer: W,
+    ) -> Result<(), serde_json::Error> {
+        match index {
+            0 => {
+                let segment = DescriptorSegment {
+                    descriptor: Cow::Borrowed(&changeset.descriptor),
+                    change_descriptor: Cow::Borrowed(&changeset.change_descriptor),
+                    network: Cow::Borrowed(&changeset.network),
+                };
+                write_json(&segment, pretty, writer)
+            }
+            1 => write_json(&changeset.local_chain, pretty, writer),
+            2 => write_json(&changeset.tx_graph, pretty, writer),
+            _ => write_json(&changeset.indexer, pretty, writer),
+        }
+    }
+
+    /// Serialize a value to JSON, indented if `pretty` is set
+    fn write_json<T, W>(value: &T, pretty: bool, writer: W) -> Result<(), serde_json::Error>
+    where
+        T: serde::Serialize,
+        W: std::io::Write,
+    {
+        if pretty {
+            serde_json::to_writer_pretty(writer, value)
+        } else {
+            serde_json::to_writer(writer, value)
+        }
+    }
+
+    /// Deserialize a changeset from its stored segments, each checked against `limits`
+    pub(crate) fn decode_segments(
+        segments: [&[u8]; 4],
+        limits: &DeserializeLimits,
+    ) -> Result<ChangeSet, serde_json::Error> {
+        let mut changeset = ChangeSet::default();
+        for (index, segment) in segments.into_iter().enumerate() {
+            decode_segment_into(&mut changeset, index, segment, limits)?;
+        }
+        Ok(changeset)
+    }
+
+    /// Deserialize the segment at `index` in [`SEGMENTS`] into the fields of `changeset`
+    /// it holds
+    pub(crate) fn decode_segment_into(
+        changeset: &mut ChangeSet,
+        index: usize,
+        segment: &[u8],
+        limits: &DeserializeLimits,
+    ) -> Result<(), serde_json::Error> {
+        match index {
+            0 => {
+                let descriptors: DescriptorSegment = decode_json(segment, limits)?;
+                changeset.descriptor = descriptors.descriptor.into_owned();
+                changeset.change_descriptor = descriptors.change_descriptor.into_owned();
+                changeset.network = descriptors.network.into_owned();
+            }
+            1 => changeset.local_chain = decode_json(segment, limits)?,
+            2 => changeset.tx_graph = decode_json(segment, limits)?,
+            _ => changeset.indexer = decode_json(segment, limits)?,
+        }
+        Ok(())
+    }
+
+    /// The length of the JSON [`join_segments`] makes of segments of these lengths
+    pub(crate) fn joined_len(segment_lens: [usize; 4]) -> usize {
+        // Each segment after the first is added as `,"name":segment`
+        let fields: usize = SEGMENTS[1..].iter().map(|name| name.len() + 4).sum();
+        segment_lens.iter().sum::<usize>() + fields
+    }
+
+    /// Join stored segments into the JSON of the whole changeset
+    ///
+    /// The result is what [`encode_changeset`] writes for the same changeset, byte for
+    /// byte unless the segments were indented.
+    pub(crate) fn join_segments(segments: [&[u8]; 4]) -> Result<Vec<u8>, serde_json::Error> {
+        use serde::de::Error;
+
+        // The descriptors segment is an object; the other segments become its last fields
+        let mut json = segments[0].trim_ascii_end().to_vec();
+        if json.pop() != Some(b'}') {
+            return Err(serde_json::Error::custom(
+                "stored descriptors segment is not a JSON object",
+            ));
+        }
+        for (name, segment) in SEGMENTS.iter().zip(segments).skip(1) {
+            json.extend_from_slice(format!(",\"{}\":", name).as_bytes());
+            json.extend_from_slice(segment);
+        }
+        json.push(b'}');
+        Ok(json)
+    }
+
+    /// Deserialize a changeset written by [`encode_changeset`], checking it against
+    /// `limits` first
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the bytes exceed `limits` or are not a valid changeset.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use bdk_wallet::ChangeSet;
+    /// use redb_wallet_storage::{codec, DeserializeLimits};
+    ///
+    /// let bytes = codec::encode_changeset(&ChangeSet::default(), false).unwrap();
+    /// let changeset = codec::decode_changeset(&bytes, &DeserializeLimits::default()).unwrap();
+    /// assert_eq!(changeset, ChangeSet::default());
+    /// ```
+    ///
+    pub fn decode_changeset(
+        bytes: &[u8],
+        limits: &DeserializeLimits,
+    ) -> Result<ChangeSet, serde_json::Error> {
+        decode_json(bytes, limits)
+    }
+
+    /// Deserialize JSON after checking it against `limits`
+    pub(crate) fn decode_json<T>(
+        bytes: &[u8],
+        limits: &DeserializeLimits,
+    ) -> Result<T, serde_json::Error>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        use serde::de::Error;
+
+        if bytes.len() > limits.max_bytes {
+            return Err(serde_json::Error::custom(format!(
+                "stored JSON is {} bytes, exceeding the limit of {} bytes",
+                bytes.len(),
+                limits.max_bytes
+            )));
+        }
+
+        // Scan the nesting depth without parsing, skipping brackets inside strings
+        let mut depth = 0usize;
+        let mut in_string = false;
+        let mut escaped = false;
+        for &byte in bytes {
+            if in_string {
+                match byte {
+                    _ if escaped => escaped = false,
+                    b'\\' => escaped = true,
+                    b'"' => in_string = false,
+                    _ => {}
+                }
+                continue;
+            }
+            match byte {
+                b'"' => in_string = true,
+                b'[' | b'{' => {
+                    depth += 1;
+                    if depth > limits.max_depth {
+                        return Err(serde_json::Error::custom(format!(
+                            "stored JSON nests deeper than the limit of {} levels",
+                            limits.max_depth
+                        )));
+                    }
+                }
+                b']' | b'}' => depth = depth.saturating_sub(1),
+                _ => {}
+            }
+        }
+
+        serde_json::from_slice(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bdk_wallet::{CreateParams, KeychainKind, LoadParams, PersistedWallet};
+    use bitcoin::hashes::Hash;
+    use bitcoin::{
+        absolute, transaction, Amount, Network, OutPoint, ScriptBuf, Transaction, TxIn, TxOut, Txid,
+    };
+    use futures::future::join_all;
+    use std::fs;
+    use std::fs::OpenOptions;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::sync::Arc;
+    use tempfile::tempdir;
+    use tokio::sync::Mutex;
+
+    // Example descriptor for testing
+    const TEST_DESCRIPTOR: &str = "wpkh(tprv8ZgxMBicQKsPdcAqYBpzAFwU5yxBUo88ggoBqu1qPcHUfSbKK1sKMLmC7EAk438btHQrSdu3jGGQa6PA71nvH5nkDexhLteJqkM4dQmWF9g/84'/1'/0'/0/*)";
+    const TEST_CHANGE_DESCRIPTOR: &str = "wpkh(tprv8ZgxMBicQKsPdcAqYBpzAFwU5yxBUo88ggoBqu1qPcHUfSbKK1sKMLmC7EAk438btHQrSdu3jGGQa6PA71nvH5nkDexhLteJqkM4dQmWF9g/84'/1'/0'/1/*)";
+
+    // Build a changeset holding `tx_count` distinct dummy transactions
+    fn large_changeset(tx_count: u32) -> ChangeSet {
+        let mut changeset = ChangeSet::default();
+        for i in 0..tx_count {
+            let tx = Transaction {
+                version: transaction::Version::TWO,
+                lock_time: absolute::LockTime::from_consensus(i),
+                input: vec![TxIn {
+                    previous_output: OutPoint::new(Txid::all_zeros(), i),
+                    ..Default::default()
+                }],
+                output: vec![TxOut {
+                    value: Amount::from_sat(u64::from(i) + 1),
+                    script_pubkey: ScriptBuf::new(),
+                }],
+            };
+            changeset.tx_graph.txs.insert(Arc::new(tx));
+        }
+        changeset
+    }
+
+    #[test]
+    fn test_create_and_persist() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("wallet.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+
+        // Create params with descriptors
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+
+        let mut wallet = PersistedWallet::create(&mut store, create_params).unwrap();
+
+        // Make a change to the wallet - reveal an address which will create a change
+        let _address = wallet.reveal_next_address(KeychainKind::External);
+
+        // Now persist should return true because we've made changes
+        let persisted = wallet.persist(&mut store).unwrap();
+        assert!(persisted);
+
+        // Check that we can load the wallet back
+        let load_params = LoadParams::default();
+        let loaded_wallet = PersistedWallet::load(&mut store, load_params).unwrap();
+        assert!(loaded_wallet.is_some());
+    }
+
+    #[test]
+    fn test_empty_store() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("empty.redb");
+
+        // Create an empty store
+        let mut store = RedbStore::create(&db_path).unwrap();
+
+        // Initialize should return an empty changeset
+        let changeset = WalletPersister::initialize(&mut store).unwrap();
+        assert!(changeset.is_empty());
+    }
+
+    #[test]
+    fn test_open_nonexistent_file() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("nonexistent.redb");
+
+        // Attempt to open a non-existent database file
+        let result = RedbStore::open(&db_path);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_open_or_create() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("open_or_create.redb");
+
+        // File doesn't exist, should create it
+        let store = RedbStore::open_or_create(&db_path).unwrap();
+        drop(store);
+
+        // File now exists, should open it
+        let store = RedbStore::open_or_create(&db_path).unwrap();
+        drop(store);
+
+        // Verify the file exists
+        assert!(db_path.exists());
+    }
+
+    #[test]
+    fn test_file_size() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("file_size.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+
+        // A fresh file already holds redb's header and table metadata
+        let initial_size = store.file_size().unwrap();
+        assert!(initial_size > 0);
+        assert_eq!(initial_size, fs::metadata(&db_path).unwrap().len());
+
+        // Persisting a large changeset should grow the file
+        WalletPersister::persist(&mut store, &large_changeset(5_000)).unwrap();
+        assert!(store.file_size().unwrap() > initial_size);
+    }
+
+    #[test]
+    fn test_clear_and_compact() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("compact.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(5_000)).unwrap();
+        let grown_size = store.file_size().unwrap();
+
+        // Clearing removes the changeset but leaves the file size untouched
+        store.clear().unwrap();
+        assert!(WalletPersister::initialize(&mut store).unwrap().is_empty());
+
+        assert!(store.compact().unwrap());
+        assert!(store.file_size().unwrap() < grown_size);
+
+        // The store is still usable after compaction
+        WalletPersister::persist(&mut store, &large_changeset(10)).unwrap();
+        assert_eq!(
+            WalletPersister::initialize(&mut store)
+                .unwrap()
+                .tx_graph
+                .txs
+                .len(),
+            10
+        );
+    }
+
+    #[test]
+    fn test_create_for_network() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("network.redb");
+
+        let mut store = RedbStore::create_for_network(&db_path, Network::Testnet).unwrap();
+        assert_eq!(store.stored_network().unwrap(), Some(Network::Testnet));
+
+        // A wallet on the same network persists normally
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        let _wallet = PersistedWallet::create(&mut store, create_params).unwrap();
+
+        // The network survives a reopen
+        drop(store);
+        let mut store = RedbStore::open(&db_path).unwrap();
+        assert_eq!(store.stored_network().unwrap(), Some(Network::Testnet));
+
+        // A changeset for another network is rejected and nothing is written
+        let before = WalletPersister::initialize(&mut store).unwrap();
+        let mismatched = ChangeSet {
+            network: Some(Network::Bitcoin),
+            ..Default::default()
+        };
+        let result = WalletPersister::persist(&mut store, &mismatched);
+        assert!(matches!(
+            result,
+            Err(RedbError::NetworkMismatch {
+                expected: Network::Testnet,
+                found: Network::Bitcoin,
+            })
+        ));
+        assert_eq!(WalletPersister::initialize(&mut store).unwrap(), before);
+    }
+
+    #[test]
+    fn test_stored_network_absent() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("no_network.redb");
+
+        let store = RedbStore::create(&db_path).unwrap();
+        assert_eq!(store.stored_network().unwrap(), None);
+    }
+
+    #[test]
+    fn test_on_persist_callback() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("callback.redb");
+
+        let mut store = RedbStore::create_for_network(&db_path, Network::Testnet).unwrap();
+
+        let counter = Arc::new(AtomicUsize::new(0));
+        let callback_counter = Arc::clone(&counter);
+        store.on_persist(Box::new(move |changeset| {
+            assert!(!changeset.is_empty());
+            callback_counter.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        // Each successful persist fires the callback exactly once
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        let mut wallet = PersistedWallet::create(&mut store, create_params).unwrap();
+        assert_eq!(counter.load(Ordering::SeqCst), 1);
+
+        wallet.reveal_next_address(KeychainKind::External);
+        wallet.persist(&mut store).unwrap();
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+
+        // A failed persist never reaches the callback
+        let mismatched = ChangeSet {
+            network: Some(Network::Bitcoin),
+            ..Default::default()
+        };
+        assert!(WalletPersister::persist(&mut store, &mismatched).is_err());
+        assert_eq!(counter.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn test_transaction_batches_persists() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("transaction.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+
+        // Count commits through the persist callback
+        let commits = Arc::new(AtomicUsize::new(0));
+        let callback_commits = Arc::clone(&commits);
+        store.on_persist(Box::new(move |_| {
+            callback_commits.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        let changeset = large_changeset(100);
+        let mut txn = store.transaction().unwrap();
+        for tx in &changeset.tx_graph.txs {
+            let mut update = ChangeSet::default();
+            update.tx_graph.txs.insert(Arc::clone(tx));
+            txn.persist(&update).unwrap();
+        }
+        txn.commit().unwrap();
+
+        // 100 persists resulted in a single commit containing all of them
+        assert_eq!(commits.load(Ordering::SeqCst), 1);
+        let stored = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 100);
+
+        // Regular auto-commit persists still work next to transactions
+        WalletPersister::persist(&mut store, &large_changeset(150)).unwrap();
+        assert_eq!(commits.load(Ordering::SeqCst), 2);
+        let stored = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 150);
+    }
+
+    #[test]
+    fn test_transaction_rollback_on_drop() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("transaction_rollback.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(5)).unwrap();
+
+        {
+            let mut txn = store.transaction().unwrap();
+            txn.persist(&large_changeset(50)).unwrap();
+            // dropped without commit
+        }
+
+        let stored = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 5);
+    }
+
+    #[test]
+    fn test_persist_reporting() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("persist_reporting.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+
+        let commits = Arc::new(AtomicUsize::new(0));
+        let callback_commits = Arc::clone(&commits);
+        store.on_persist(Box::new(move |_| {
+            callback_commits.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        // New data changes the stored bytes
+        assert!(store.persist_reporting(&large_changeset(10)).unwrap());
+        assert_eq!(commits.load(Ordering::SeqCst), 1);
+
+        // An empty changeset against existing data is a no-op
+        assert!(!store.persist_reporting(&ChangeSet::default()).unwrap());
+
+        // Re-persisting data that is already stored is a no-op as well
+        assert!(!store.persist_reporting(&large_changeset(5)).unwrap());
+        assert_eq!(commits.load(Ordering::SeqCst), 1);
+
+        // Additional data is reported again
+        assert!(store.persist_reporting(&large_changeset(20)).unwrap());
+        assert_eq!(commits.load(Ordering::SeqCst), 2);
+        let stored = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 20);
+    }
+
+    #[test]
+    fn test_open_or_create_with_config() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("open_or_create_config.redb");
+
+        let config = || {
+            let mut config = redb::Builder::new();
+            config.set_cache_size(1024 * 1024);
+            config
+        };
+
+        // File doesn't exist, should create it
+        let mut store = RedbStore::open_or_create_with_config(&db_path, config()).unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(3)).unwrap();
+        drop(store);
+        assert!(db_path.exists());
+
+        // File now exists, should open it with the same configuration
+        let mut store = RedbStore::open_or_create_with_config(&db_path, config()).unwrap();
+        let stored = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 3);
+    }
+
+    #[test]
+    fn test_open_or_create_zero_byte_file() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("zero_byte.redb");
+
+        // Simulate a crash that left an empty file behind
+        fs::File::create(&db_path).unwrap();
+        let mut store = RedbStore::open_or_create(&db_path).unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(2)).unwrap();
+        drop(store);
+
+        // The recreated file is a regular database now
+        let (mut store, repaired) = RedbStore::open_or_create_repair(&db_path).unwrap();
+        assert!(!repaired);
+        let stored = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 2);
+        drop(store);
+
+        let repaired_path = temp_dir.path().join("zero_byte_repair.redb");
+        fs::File::create(&repaired_path).unwrap();
+        let (_store, repaired) = RedbStore::open_or_create_repair(&repaired_path).unwrap();
+        assert!(repaired);
+    }
+
+    #[test]
+    fn test_open_or_create_garbage_file() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("garbage.redb");
+
+        let garbage = vec![0xAB; 4096];
+        fs::write(&db_path, &garbage).unwrap();
+
+        // A non-empty invalid file must error instead of being recreated
+        assert!(RedbStore::open_or_create(&db_path).is_err());
+        assert!(RedbStore::open_or_create_repair(&db_path).is_err());
+
+        // The user's data is left untouched
+        assert_eq!(fs::read(&db_path).unwrap(), garbage);
+    }
+
+    #[test]
+    fn test_load_changeset() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("load_changeset.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+
+        // Nothing persisted yet
+        assert!(store.load_changeset().unwrap().is_none());
+
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        let mut wallet = PersistedWallet::create(&mut store, create_params).unwrap();
+        wallet.reveal_next_address(KeychainKind::External);
+        wallet.persist(&mut store).unwrap();
+
+        let changeset = store.load_changeset().unwrap().unwrap();
+        assert!(changeset.descriptor.is_some());
+        assert!(changeset.change_descriptor.is_some());
+        assert_eq!(changeset.network, Some(Network::Testnet));
+        assert!(!changeset.indexer.last_revealed.is_empty());
+    }
+
+    #[test]
+    fn test_close() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("close.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(4)).unwrap();
+        assert!(store.close().is_ok());
+
+        // The file is released and the data is still there
+        let mut store = RedbStore::open(&db_path).unwrap();
+        let stored = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 4);
+    }
+
+    #[test]
+    fn test_custom_table_name() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("custom_table.redb");
+
+        let mut store = RedbStore::open_or_create_with_table(&db_path, "my_wallet").unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(3)).unwrap();
+        drop(store);
+
+        // Reopening with the same table name finds the data
+        let mut store = RedbStore::open_or_create_with_table(&db_path, "my_wallet").unwrap();
+        let stored = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 3);
+        drop(store);
+
+        // A different table name, including the default one, sees an empty wallet
+        let mut store = RedbStore::open_or_create_with_table(&db_path, "other_wallet").unwrap();
+        assert!(WalletPersister::initialize(&mut store).unwrap().is_empty());
+        drop(store);
+
+        let mut store = RedbStore::open(&db_path).unwrap();
+        assert!(WalletPersister::initialize(&mut store).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_shared_database_namespaces() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("shared.redb");
+        let db = Arc::new(Database::create(&db_path).unwrap());
+
+        let alice = RedbStore::from_database_with_namespace(db.clone(), "alice").unwrap();
+        let bob = RedbStore::from_database_with_namespace(db.clone(), "bob").unwrap();
+
+        // Persist from both stores at the same time
+        let handles: Vec<_> = [(alice, 2), (bob, 5)]
+            .into_iter()
+            .map(|(mut store, tx_count)| {
+                std::thread::spawn(move || {
+                    for i in 1..=tx_count {
+                        WalletPersister::persist(&mut store, &large_changeset(i)).unwrap();
+                    }
+                    store
+                })
+            })
+            .collect();
+        let mut stores: Vec<_> = handles.into_iter().map(|h| h.join().unwrap()).collect();
+
+        // Each namespace only sees its own wallet
+        let alice_changeset = WalletPersister::initialize(&mut stores[0]).unwrap();
+        let bob_changeset = WalletPersister::initialize(&mut stores[1]).unwrap();
+        assert_eq!(alice_changeset.tx_graph.txs.len(), 2);
+        assert_eq!(bob_changeset.tx_graph.txs.len(), 5);
+
+        // The un-namespaced store sharing the handle is untouched
+        let mut default_store = RedbStore::from_database(db.clone()).unwrap();
+        assert!(WalletPersister::initialize(&mut default_store)
+            .unwrap()
+            .is_empty());
+
+        // Compaction needs the only handle to the database
+        assert!(matches!(
+            default_store.compact(),
+            Err(RedbError::SharedDatabase)
+        ));
+
+        assert!(matches!(
+            RedbStore::from_database_with_namespace(db, "a::b"),
+            Err(RedbError::InvalidNamespace(_))
+        ));
+    }
+
+    #[test]
+    fn test_set_durability() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("durability.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        store.set_durability(redb::Durability::Eventual);
+        for i in 1..=20 {
+            WalletPersister::persist(&mut store, &large_changeset(i)).unwrap();
+        }
+
+        store.set_durability(redb::Durability::Immediate);
+        WalletPersister::persist(&mut store, &large_changeset(21)).unwrap();
+        drop(store);
+
+        let mut store = RedbStore::open(&db_path).unwrap();
+        let stored = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 21);
+    }
+
+    #[test]
+    fn test_compaction_error() {
+        let err = RedbError::from(redb::CompactionError::TransactionInProgress);
+        assert!(matches!(err, RedbError::Compaction(_)));
+        assert_eq!(
+            err.to_string(),
+            "Compaction error: A transaction is still in progress. Operation cannot be performed."
+        );
+
+        let err: &dyn std::error::Error = &err;
+        assert!(err.source().is_some());
+    }
+
+    #[test]
+    fn test_error_source_chain() {
+        use std::error::Error;
+
+        let err = RedbError::Io(std::io::Error::other("disk"));
+        let source = err.source().unwrap();
+        assert_eq!(source.to_string(), "disk");
+        assert!(source.downcast_ref::<std::io::Error>().is_some());
+
+        let json_err = serde_json::from_slice::<ChangeSet>(b"{").unwrap_err();
+        let err = RedbError::Deserialization(json_err);
+        assert!(err
+            .source()
+            .unwrap()
+            .downcast_ref::<serde_json::Error>()
+            .is_some());
+
+        let err = RedbError::from(redb::TableError::TableDoesNotExist("labels".to_string()));
+        assert!(err
+            .source()
+            .unwrap()
+            .downcast_ref::<redb::TableError>()
+            .is_some());
+
+        let err = RedbError::from(redb::DatabaseError::DatabaseAlreadyOpen);
+        assert!(err
+            .source()
+            .unwrap()
+            .downcast_ref::<redb::Error>()
+            .is_some());
+
+        // Errors raised by the store itself have no underlying cause
+        assert!(RedbError::ReadOnly.source().is_none());
+        assert!(RedbError::InvalidNamespace("a::b".to_string())
+            .source()
+            .is_none());
+    }
+
+    #[test]
+    fn test_iter_entries() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("entries.redb");
+
+        let mut store = RedbStore::create_for_network(&db_path, Network::Testnet).unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(2)).unwrap();
+
+        let entries = store.iter_entries().unwrap();
+        let keys: Vec<&str> = entries.iter().map(|(key, _)| key.as_str()).collect();
+        assert_eq!(
+            keys,
+            vec![
+                CHANGESET_VERSION_KEY,
+                CONTENT_HASH_KEY,
+                CREATED_AT_KEY,
+                HAS_DATA_KEY,
+                LAST_ACCESSED_KEY,
+                LAST_PERSISTED_KEY,
+                NETWORK_KEY,
+                VERSION_KEY,
+                CHANGESET_KEY,
+                "wallet_changeset::indexer",
+                "wallet_changeset::local_chain",
+                "wallet_changeset::tx_graph"
+            ]
+        );
+
+        let (_, changeset_bytes) = &entries[8];
+        assert!(!changeset_bytes.is_empty());
+    }
+
+    #[test]
+    fn test_open_or_create_with_backups() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("backups.redb");
+        let backup_path = |n: usize| temp_dir.path().join(format!("backups.redb.bak{}", n));
+
+        // Each open persists one more transaction
+        for i in 1..=5 {
+            let mut store = RedbStore::open_or_create_with_backups(&db_path, 3).unwrap();
+            WalletPersister::persist(&mut store, &large_changeset(i)).unwrap();
+        }
+
+        // The first open created the file, so only four backups were ever taken
+        assert!(!backup_path(4).exists());
+        for (n, expected_txs) in [(1, 4), (2, 3), (3, 2)] {
+            let mut backup = RedbStore::open(backup_path(n)).unwrap();
+            let stored = WalletPersister::initialize(&mut backup).unwrap();
+            assert_eq!(stored.tx_graph.txs.len(), expected_txs);
+        }
+        assert!(!temp_dir.path().join("backups.redb.bak.tmp").exists());
+
+        // A backup that can't be written leaves the existing ones where they were
+        let blocked = temp_dir.path().join("backups.redb.bak.tmp");
+        fs::create_dir(&blocked).unwrap();
+        assert!(RedbStore::open_or_create_with_backups(&db_path, 3).is_err());
+        fs::remove_dir(&blocked).unwrap();
+        for (n, expected_txs) in [(1, 4), (2, 3), (3, 2)] {
+            let mut backup = RedbStore::open(backup_path(n)).unwrap();
+            let stored = WalletPersister::initialize(&mut backup).unwrap();
+            assert_eq!(stored.tx_graph.txs.len(), expected_txs);
+        }
+    }
+
+    #[test]
+    fn test_backup_to_existing_file() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("wallet.redb");
+        let backup_path = temp_dir.path().join("backup.redb");
+
+        let store = RedbStore::create(&db_path).unwrap();
+        fs::write(&backup_path, b"keep me").unwrap();
+
+        assert!(matches!(
+            store.backup_to(&backup_path),
+            Err(RedbError::Io(_))
+        ));
+        assert_eq!(fs::read(&backup_path).unwrap(), b"keep me");
+    }
+
+    #[test]
+    fn test_clone_shares_database() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("clone.redb");
+
+        let store = RedbStore::create(&db_path).unwrap();
+        let handles: Vec<_> = [1, 2]
+            .into_iter()
+            .map(|offset| {
+                let mut store = store.clone();
+                std::thread::spawn(move || {
+                    for i in (offset..=10).step_by(2) {
+                        WalletPersister::persist(&mut store, &large_changeset(i)).unwrap();
+                    }
+                })
+            })
+            .collect();
+        for handle in handles {
+            handle.join().unwrap();
+        }
+
+        // Both clones see the merged result of every persist
+        let mut clone = store.clone();
+        let stored = WalletPersister::initialize(&mut clone).unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 10);
+        assert_eq!(store.load_changeset().unwrap(), Some(stored));
+    }
+
+    #[test]
+    fn test_metadata_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("metadata.redb");
+
+        let mut store = RedbStore::create_for_network(&db_path, Network::Testnet).unwrap();
+        assert_eq!(store.get_metadata("label").unwrap(), None);
+
+        store.put_metadata("label", b"Savings").unwrap();
+        store.put_metadata("label", b"Spending").unwrap();
+        drop(store);
+
+        let mut store = RedbStore::open(&db_path).unwrap();
+        assert_eq!(
+            store.get_metadata("label").unwrap(),
+            Some(b"Spending".to_vec())
+        );
+
+        // Internal names are off limits
+        assert!(matches!(
+            store.put_metadata("network", b"bitcoin"),
+            Err(RedbError::ReservedKey(_))
+        ));
+        assert_eq!(store.stored_network().unwrap(), Some(Network::Testnet));
+    }
+
+    #[test]
+    fn test_metadata_survives_clear() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("metadata_clear.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(2)).unwrap();
+        store
+            .put_metadata("last_sync", &42u64.to_be_bytes())
+            .unwrap();
+
+        store.clear().unwrap();
+        assert!(WalletPersister::initialize(&mut store).unwrap().is_empty());
+        assert_eq!(
+            store.get_metadata("last_sync").unwrap(),
+            Some(42u64.to_be_bytes().to_vec())
+        );
+    }
+
+    #[test]
+    fn test_recover_corrupt_changeset() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("corrupt_blob.redb");
+
+        // Write a truncated changeset
+        {
+            let store = RedbStore::create(&db_path).unwrap();
+            let bytes = serde_json::to_vec(&large_changeset(2)).unwrap();
+            let write_txn = store.db().unwrap().begin_write().unwrap();
+            {
+                let mut table = write_txn.open_table(store.wallet_table()).unwrap();
+                table
+                    .insert(CHANGESET_KEY, &bytes[..bytes.len() / 2])
+                    .unwrap();
+            }
+            write_txn.commit().unwrap();
+        }
+
+        // A regular store refuses to initialize
+        {
+            let mut store = RedbStore::open(&db_path).unwrap();
+            assert!(matches!(
+                WalletPersister::initialize(&mut store),
+                Err(RedbError::Deserialization(_))
+            ));
+        }
+
+        // So does a read-only one, which can't move the changeset aside
+        {
+            let mut store = RedbStore::builder()
+                .read_only(true)
+                .recoverable(true)
+                .open_or_create(&db_path)
+                .unwrap();
+            assert!(matches!(
+                WalletPersister::initialize(&mut store),
+                Err(RedbError::Deserialization(_))
+            ));
+            assert!(store.last_recovery_info().is_none());
+            assert!(matches!(
+                store.initialize_with_progress(|_| {}),
+                Err(RedbError::Deserialization(_))
+            ));
+        }
+
+        let mut store = RedbStore::open_or_create_recoverable(&db_path).unwrap();
+        assert!(store.last_recovery_info().is_none());
+        assert!(WalletPersister::initialize(&mut store).unwrap().is_empty());
+
+        let info = store.last_recovery_info().unwrap().clone();
+        assert_eq!(info.corrupt_key, CORRUPT_CHANGESET_KEY);
+        assert!(info.corrupt_len > 0);
+        let entries = store.iter_entries().unwrap();
+        assert!(entries
+            .iter()
+            .any(|(key, value)| key == CORRUPT_CHANGESET_KEY && value.len() == info.corrupt_len));
+
+        // The wallet is usable again
+        WalletPersister::persist(&mut store, &large_changeset(1)).unwrap();
+        let stored = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_persist_shared_without_mutex() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("shared_persist.redb");
+        let store = Arc::new(RedbStore::create(&db_path).unwrap());
+
+        // Every task persists a different transaction
+        let handles: Vec<_> = (1..=8)
+            .map(|i| {
+                let store = Arc::clone(&store);
+                let mut changeset = large_changeset(i);
+                changeset
+                    .tx_graph
+                    .txs
+                    .retain(|tx| tx.lock_time == absolute::LockTime::from_consensus(i - 1));
+                tokio::task::spawn_blocking(move || store.persist_shared(&changeset))
+            })
+            .collect();
+        for handle in handles {
+            handle.await.unwrap().unwrap();
+        }
+
+        let stored = store.load_changeset().unwrap().unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 8);
+    }
+
+    #[tokio::test]
+    async fn test_concurrent_reads_during_persists() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("concurrent_reads.redb");
+        let store = Arc::new(RedbStore::create(&db_path).unwrap());
+
+        let writers: Vec<_> = (1..=4)
+            .map(|i| {
+                let store = Arc::clone(&store);
+                tokio::task::spawn_blocking(move || {
+                    let mut changeset = large_changeset(i);
+                    changeset
+                        .tx_graph
+                        .txs
+                        .retain(|tx| tx.lock_time == absolute::LockTime::from_consensus(i - 1));
+                    store.persist_shared(&changeset)
+                })
+            })
+            .collect();
+
+        // Readers only ever see whole commits, and never go back in time
+        let readers: Vec<_> = (0..16)
+            .map(|_| {
+                let store = Arc::clone(&store);
+                tokio::task::spawn_blocking(move || {
+                    let mut seen = 0;
+                    for _ in 0..25 {
+                        let changeset = store.initialize_shared().unwrap();
+                        let count = changeset.tx_graph.txs.len();
+                        assert!(count >= seen && count <= 4);
+                        seen = count;
+                    }
+                })
+            })
+            .collect();
+
+        for writer in writers {
+            writer.await.unwrap().unwrap();
+        }
+        for reader in readers {
+            reader.await.unwrap();
+        }
+
+        let stored = store.initialize_shared().unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 4);
+    }
+
+    #[test]
+    fn test_serialized_size() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("serialized_size.redb");
+
+        let changeset = large_changeset(10);
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let size = store.serialized_size(&changeset).unwrap();
+        WalletPersister::persist(&mut store, &changeset).unwrap();
+
+        // The stored segments join into exactly the serialized changeset
+        assert_eq!(size, store.stats().unwrap().changeset_bytes);
+        assert_eq!(size, store.get_changeset_bytes().unwrap().unwrap().len());
+
+        // Pretty JSON is measured as it will be stored
+        store.set_json_pretty(true);
+        let pretty = large_changeset(11);
+        let pretty_size = store.serialized_size(&pretty).unwrap();
+        assert!(pretty_size > size);
+        WalletPersister::persist(&mut store, &pretty).unwrap();
+        assert_eq!(pretty_size, store.stats().unwrap().changeset_bytes);
+    }
+
+    #[test]
+    fn test_max_changeset_bytes() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("max_bytes.redb");
+
+        let small = large_changeset(2);
+        let big = large_changeset(20);
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let limit = store.serialized_size(&small).unwrap() + 10;
+        store.set_max_changeset_bytes(Some(limit));
+
+        // Below the limit
+        WalletPersister::persist(&mut store, &small).unwrap();
+
+        // Above the limit
+        match WalletPersister::persist(&mut store, &big) {
+            Err(RedbError::TooLarge { size, limit: l }) => {
+                assert_eq!(l, limit);
+                assert!(size > limit);
+            }
+            other => panic!("expected TooLarge, got {:?}", other),
+        }
+        assert_eq!(store.load_changeset().unwrap(), Some(small.clone()));
+
+        // Batched persists are checked too
+        let mut txn = store.transaction().unwrap();
+        txn.persist(&big).unwrap();
+        assert!(matches!(txn.commit(), Err(RedbError::TooLarge { .. })));
+        assert_eq!(store.load_changeset().unwrap(), Some(small));
+    }
+
+    #[test]
+    fn test_flush_after_eventual_durability() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("flush.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        store.set_durability(redb::Durability::Eventual);
+        for i in 1..=5 {
+            WalletPersister::persist(&mut store, &large_changeset(i)).unwrap();
+        }
+        store.flush().unwrap();
+        drop(store);
+
+        let mut store = RedbStore::open(&db_path).unwrap();
+        let stored = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 5);
+    }
+
+    #[test]
+    fn test_find_by_fingerprint() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("fingerprint.redb");
+        let db = Arc::new(Database::create(&db_path).unwrap());
+
+        let mut alice = RedbStore::from_database_with_namespace(db.clone(), "alice").unwrap();
+        let mut bob = RedbStore::from_database_with_namespace(db.clone(), "bob").unwrap();
+
+        // A wallet without descriptors has no fingerprint and is not indexed
+        let mut carol = RedbStore::from_database_with_namespace(db, "carol").unwrap();
+        WalletPersister::persist(&mut carol, &large_changeset(1)).unwrap();
+        assert_eq!(
+            RedbStore::descriptor_fingerprint(&carol.load_changeset().unwrap().unwrap()),
+            None
+        );
+        assert!(!carol
+            .iter_entries()
+            .unwrap()
+            .iter()
+            .any(|(key, _)| key.starts_with(FINGERPRINT_PREFIX)));
+
+        let params =
+            |external, internal| CreateParams::new(external, internal).network(Network::Testnet);
+        PersistedWallet::create(&mut alice, params(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR))
+            .unwrap();
+        PersistedWallet::create(&mut bob, params(TEST_CHANGE_DESCRIPTOR, TEST_DESCRIPTOR)).unwrap();
+
+        let fingerprint = |store: &RedbStore| {
+            RedbStore::descriptor_fingerprint(&store.load_changeset().unwrap().unwrap()).unwrap()
+        };
+        let alice_fp = fingerprint(&alice);
+        let bob_fp = fingerprint(&bob);
+        assert_ne!(alice_fp, bob_fp);
+
+        assert_eq!(alice.find_by_fingerprint(&alice_fp).unwrap(), vec!["alice"]);
+        assert_eq!(alice.find_by_fingerprint(&bob_fp).unwrap(), vec!["bob"]);
+        assert!(alice.find_by_fingerprint("unknown").unwrap().is_empty());
+
+        // Replacing a wallet's descriptors moves it to the new fingerprint
+        bob.set_allow_descriptor_change(true);
+        let replacement = alice.load_changeset().unwrap().unwrap();
+        WalletPersister::persist(&mut bob, &replacement).unwrap();
+        assert!(alice.find_by_fingerprint(&bob_fp).unwrap().is_empty());
+        assert_eq!(
+            alice.find_by_fingerprint(&alice_fp).unwrap(),
+            vec!["alice", "bob"]
+        );
+
+        // A cleared wallet is no longer found
+        alice.clear().unwrap();
+        assert_eq!(alice.find_by_fingerprint(&alice_fp).unwrap(), vec!["bob"]);
+    }
+
+    #[test]
+    fn test_error_codes() {
+        let json_error = || serde_json::from_str::<u8>("not json").unwrap_err();
+        let storage_error = || redb::StorageError::Corrupted("test".to_string());
+
+        let cases = vec![
+            (
+                RedbError::Database(redb::Error::DatabaseAlreadyOpen),
+                RedbErrorCode::Database,
+            ),
+            (
+                RedbError::Serialization(json_error()),
+                RedbErrorCode::Serialization,
+            ),
+            (
+                RedbError::Deserialization(json_error()),
+                RedbErrorCode::Deserialization,
+            ),
+            (
+                RedbError::Io(std::io::Error::other("test")),
+                RedbErrorCode::Io,
+            ),
+            (
+                RedbError::Commit(redb::CommitError::Storage(storage_error())),
+                RedbErrorCode::Commit,
+            ),
+            (
+                RedbError::Table(redb::TableError::TableDoesNotExist("test".to_string())),
+                RedbErrorCode::Table,
+            ),
+            (
+                RedbError::Transaction(redb::TransactionError::Storage(storage_error())),
+                RedbErrorCode::Transaction,
+            ),
+            (
+                RedbError::Compaction(redb::CompactionError::TransactionInProgress),
+                RedbErrorCode::Compaction,
+            ),
+            (
+                RedbError::NetworkMismatch {
+                    expected: Network::Bitcoin,
+                    found: Network::Testnet,
+                },
+                RedbErrorCode::NetworkMismatch,
+            ),
+            (
+                RedbError::InvalidNamespace("a::b".to_string()),
+                RedbErrorCode::InvalidNamespace,
+            ),
+            (
+                RedbError::ReservedKey("network".to_string()),
+                RedbErrorCode::ReservedKey,
+            ),
+            (
+                RedbError::TooLarge { size: 2, limit: 1 },
+                RedbErrorCode::TooLarge,
+            ),
+            (RedbError::ReadOnly, RedbErrorCode::ReadOnly),
+            (
+                RedbError::DescriptorMismatch(KeychainKind::External),
+                RedbErrorCode::DescriptorMismatch,
+            ),
+            (
+                RedbError::InvalidDescriptor("test".to_string()),
+                RedbErrorCode::InvalidDescriptor,
+            ),
+            (
+                RedbError::DescriptorConflict(KeychainKind::Internal),
+                RedbErrorCode::DescriptorConflict,
+            ),
+            (
+                RedbError::VersionConflict {
+                    expected: 1,
+                    actual: 2,
+                },
+                RedbErrorCode::VersionConflict,
+            ),
+            (
+                RedbError::IncompatibleFileFormat(redb::Error::UpgradeRequired(1)),
+                RedbErrorCode::IncompatibleFileFormat,
+            ),
+            (
+                RedbError::CheckpointNotFound(1),
+                RedbErrorCode::CheckpointNotFound,
+            ),
+            (
+                RedbError::InvalidKeyPrefixes("empty".to_string()),
+                RedbErrorCode::InvalidKeyPrefixes,
+            ),
+            (
+                RedbError::UnsupportedChangeSetVersion {
+                    found: 2,
+                    supported: 1,
+                },
+                RedbErrorCode::UnsupportedChangeSetVersion,
+            ),
+            #[cfg(feature = "bundle")]
+            (
+                RedbError::UnsupportedBundleVersion {
+                    found: 2,
+                    supported: 1,
+                },
+                RedbErrorCode::UnsupportedBundleVersion,
+            ),
+            (RedbError::AlreadyLocked, RedbErrorCode::AlreadyLocked),
+            (
+                RedbError::Unhealthy(vec!["changeset does not match its checksum".to_string()]),
+                RedbErrorCode::Unhealthy,
+            ),
+            (RedbError::SharedDatabase, RedbErrorCode::SharedDatabase),
+            (RedbError::Closed, RedbErrorCode::Closed),
+        ];
+
+        for (error, code) in cases {
+            assert_eq!(error.code(), code, "wrong code for {}", error);
+        }
+    }
+
+    #[test]
+    fn test_custom_backend() {
+        let mut store =
+            RedbStore::create_with_backend(redb::backends::InMemoryBackend::new()).unwrap();
+        assert_eq!(store.file_size().unwrap(), 0);
+
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        let mut wallet = PersistedWallet::create(&mut store, create_params).unwrap();
+        wallet.reveal_next_address(KeychainKind::External);
+        wallet.persist(&mut store).unwrap();
+
+        let loaded = PersistedWallet::load(&mut store, LoadParams::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            loaded.derivation_index(KeychainKind::External),
+            wallet.derivation_index(KeychainKind::External)
+        );
+
+        assert!(matches!(
+            RedbStore::open_with_backend(redb::backends::InMemoryBackend::new()),
+            Err(RedbError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_repair_truncated_file() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("repair.redb");
+
+        {
+            let mut store = RedbStore::create(&db_path).unwrap();
+            WalletPersister::persist(&mut store, &large_changeset(50)).unwrap();
+        }
+
+        let len = fs::metadata(&db_path).unwrap().len();
+        let file = OpenOptions::new().write(true).open(&db_path).unwrap();
+        file.set_len(len / 2).unwrap();
+        drop(file);
+
+        let (mut store, report) = RedbStore::repair(&db_path).unwrap();
+        assert!(report.recovered_bytes + report.dropped_bytes > 0);
+        WalletPersister::initialize(&mut store).unwrap();
+    }
+
+    #[test]
+    fn test_repair_drops_invalid_changeset() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("repair_blob.redb");
+
+        {
+            let mut store = RedbStore::create(&db_path).unwrap();
+            store.put_metadata("label", b"Savings").unwrap();
+            let write_txn = store.db().unwrap().begin_write().unwrap();
+            {
+                let mut table = write_txn.open_table(store.wallet_table()).unwrap();
+                table.insert(CHANGESET_KEY, b"{\"trunc".as_slice()).unwrap();
+                // Only real changeset keys are checked
+                table
+                    .insert("label::wallet_changeset", b"kept".as_slice())
+                    .unwrap();
+                let app_table = TableDefinition::<&str, &[u8]>::new("app_data");
+                let mut table = write_txn.open_table(app_table).unwrap();
+                table.insert("contacts", b"alice".as_slice()).unwrap();
+            }
+            write_txn.commit().unwrap();
+        }
+        // A repair interrupted before its result was moved into place
+        fs::write(temp_dir.path().join("repair_blob.redb.repair"), b"partial").unwrap();
+
+        let (mut store, report) = RedbStore::repair(&db_path).unwrap();
+        assert_eq!(
+            report,
+            RepairReport {
+                recovered_bytes: 24,
+                dropped_bytes: 7,
+            }
+        );
+        assert!(WalletPersister::initialize(&mut store).unwrap().is_empty());
+        assert_eq!(
+            store.get_metadata("label").unwrap(),
+            Some(b"Savings".to_vec())
+        );
+        let read_txn = store.db().unwrap().begin_read().unwrap();
+        let table = read_txn
+            .open_table(TableDefinition::<&str, &[u8]>::new("app_data"))
+            .unwrap();
+        assert_eq!(table.get("contacts").unwrap().unwrap().value(), b"alice");
+    }
+
+    #[test]
+    fn test_repair_keeps_earlier_corrupt_files() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("unreadable.redb");
+        let corrupt_path =
+            |suffix: &str| temp_dir.path().join(format!("unreadable.redb{}", suffix));
+
+        for garbage in [b"first".as_slice(), b"second".as_slice()] {
+            fs::write(&db_path, garbage).unwrap();
+            let (_, report) = RedbStore::repair(&db_path).unwrap();
+            assert_eq!(report.dropped_bytes, garbage.len() as u64);
+        }
+        assert_eq!(fs::read(corrupt_path(".corrupt")).unwrap(), b"first");
+        assert_eq!(fs::read(corrupt_path(".corrupt.1")).unwrap(), b"second");
+    }
+
+    #[test]
+    fn test_repair_leaves_locked_file_alone() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("locked.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(3)).unwrap();
+
+        // A file held open elsewhere isn't damaged, so it is neither moved nor replaced
+        assert!(RedbStore::repair(&db_path).is_err());
+        assert!(!temp_dir.path().join("locked.redb.corrupt").exists());
+        drop(store);
+        let mut store = RedbStore::open(&db_path).unwrap();
+        let stored = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 3);
+    }
+
+    // Without a tokio runtime, the `tokio` offload writes in place
+    #[test]
+    fn test_async_persist_without_runtime() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("executor.redb");
+        let changeset = large_changeset(5);
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        futures::executor::block_on(async {
+            AsyncWalletPersister::persist(&mut store, &changeset)
+                .await
+                .unwrap();
+            let stored = AsyncWalletPersister::initialize(&mut store).await.unwrap();
+            assert_eq!(stored, changeset);
+        });
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_async_persist_cancelled() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("cancelled.redb");
+        let changeset = large_changeset(20);
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        {
+            // Poll the persist once, then drop it
+            let persist = AsyncWalletPersister::persist(&mut store, &changeset);
+            let _ = tokio::time::timeout(std::time::Duration::ZERO, persist).await;
+        }
+        drop(store);
+
+        // A write still in flight keeps the database open until it finishes
+        let mut store = None;
+        for _ in 0..100 {
+            match RedbStore::open(&db_path) {
+                Ok(s) => {
+                    store = Some(s);
+                    break;
+                }
+                Err(_) => tokio::time::sleep(std::time::Duration::from_millis(50)).await,
+            }
+        }
+        let mut store = store.expect("database was never released");
+
+        // The write either happened completely or not at all
+        let stored = AsyncWalletPersister::initialize(&mut store).await.unwrap();
+        assert!(stored.is_empty() || stored == changeset);
+    }
+
+    #[test]
+    fn test_usage_report() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("usage.redb");
+        let db = Arc::new(Database::create(&db_path).unwrap());
+
+        let mut small = RedbStore::from_database_with_namespace(db.clone(), "small").unwrap();
+        let mut big = RedbStore::from_database_with_namespace(db, "big").unwrap();
+        let before = std::time::SystemTime::now() - std::time::Duration::from_secs(1);
+        WalletPersister::persist(&mut small, &large_changeset(1)).unwrap();
+        WalletPersister::persist(&mut big, &large_changeset(30)).unwrap();
+
+        let report = small.usage_report().unwrap();
+        assert_eq!(report.len(), 2);
+        let usage = |namespace: &str| report.iter().find(|u| u.namespace == namespace).unwrap();
+
+        assert_eq!(
+            usage("small").bytes,
+            small.serialized_size(&large_changeset(1)).unwrap()
+        );
+        assert_eq!(
+            usage("big").bytes,
+            big.serialized_size(&large_changeset(30)).unwrap()
+        );
+        assert!(usage("big").bytes > usage("small").bytes);
+        assert!(usage("small").last_modified.unwrap() >= before);
+    }
+
+    #[test]
+    fn test_builder_options() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("builder.redb");
+
+        let builder = RedbStore::builder()
+            .table_name("wallets")
+            .namespace("alpha")
+            .cache_size(1024 * 1024)
+            .durability(redb::Durability::Eventual);
+
+        let mut store = builder
+            .clone()
+            .max_changeset_bytes(Some(1))
+            .open_or_create(&db_path)
+            .unwrap();
+        assert_eq!(store.table_name, "wallets");
+        assert!(matches!(store.durability, redb::Durability::Eventual));
+        assert!(matches!(
+            WalletPersister::persist(&mut store, &large_changeset(1)),
+            Err(RedbError::TooLarge { .. })
+        ));
+
+        store.set_max_changeset_bytes(None);
+        WalletPersister::persist(&mut store, &large_changeset(2)).unwrap();
+        store.close().unwrap();
+
+        // The namespace isolates the data within the custom table
+        let mut other = RedbStore::builder()
+            .table_name("wallets")
+            .open_or_create(&db_path)
+            .unwrap();
+        assert!(WalletPersister::initialize(&mut other).unwrap().is_empty());
+        drop(other);
+
+        // A read-only store reads the data but rejects writes
+        let mut store = builder.read_only(true).open_or_create(&db_path).unwrap();
+        let stored = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 2);
+        assert!(matches!(
+            WalletPersister::persist(&mut store, &large_changeset(3)),
+            Err(RedbError::ReadOnly)
+        ));
+        assert!(matches!(store.clear(), Err(RedbError::ReadOnly)));
+        assert!(matches!(store.transaction(), Err(RedbError::ReadOnly)));
+
+        // Read-only never creates a file
+        let missing = temp_dir.path().join("missing.redb");
+        assert!(RedbStore::builder()
+            .read_only(true)
+            .open_or_create(&missing)
+            .is_err());
+        assert!(!missing.exists());
+    }
+
+    #[test]
+    fn test_has_wallet() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("has_wallet.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        assert!(!store.has_wallet().unwrap());
+
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        PersistedWallet::create(&mut store, create_params).unwrap();
+        assert!(store.has_wallet().unwrap());
+
+        store.clear().unwrap();
+        assert!(!store.has_wallet().unwrap());
+    }
+
+    #[test]
+    fn test_has_wallet_namespaced() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("has_wallet_ns.redb");
+        let db = Arc::new(Database::create(&db_path).unwrap());
+
+        let mut alice = RedbStore::from_database_with_namespace(db.clone(), "alice").unwrap();
+        let bob = RedbStore::from_database_with_namespace(db, "bob").unwrap();
+        WalletPersister::persist(&mut alice, &large_changeset(1)).unwrap();
+
+        assert!(alice.has_wallet().unwrap());
+        assert!(!bob.has_wallet().unwrap());
+        assert!(bob.has_wallet_in("alice").unwrap());
+        assert!(!alice.has_wallet_in("bob").unwrap());
+        assert!(matches!(
+            alice.has_wallet_in(""),
+            Err(RedbError::InvalidNamespace(_))
+        ));
+    }
+
+    #[test]
+    fn test_expect_descriptors() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("expect_descriptors.redb");
+
+        // No wallet yet, so any expectation passes
+        let mut store = RedbStore::create(&db_path).unwrap();
+        store
+            .expect_descriptors(TEST_CHANGE_DESCRIPTOR, TEST_DESCRIPTOR)
+            .unwrap();
+        assert!(WalletPersister::initialize(&mut store).unwrap().is_empty());
+
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        PersistedWallet::create(&mut store, create_params).unwrap();
+        drop(store);
+
+        // Matching descriptors load
+        let mut store = RedbStore::open(&db_path).unwrap();
+        store
+            .expect_descriptors(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR)
+            .unwrap();
+        assert!(WalletPersister::initialize(&mut store).is_ok());
+
+        // Swapped descriptors are rejected
+        store
+            .expect_descriptors(TEST_CHANGE_DESCRIPTOR, TEST_DESCRIPTOR)
+            .unwrap();
+        assert!(matches!(
+            WalletPersister::initialize(&mut store),
+            Err(RedbError::DescriptorMismatch(KeychainKind::External))
+        ));
+
+        assert!(matches!(
+            store.expect_descriptors("not a descriptor", TEST_DESCRIPTOR),
+            Err(RedbError::InvalidDescriptor(_))
+        ));
+    }
+
+    #[cfg(feature = "wal")]
+    #[test]
+    fn test_initialize_during_fold() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("initialize_during_fold.redb");
+        let store = RedbStore::create(&db_path).unwrap();
+
+        let mut writer = store.clone();
+        let done = Arc::new(AtomicBool::new(false));
+        let writer_done = Arc::clone(&done);
+        let handle = std::thread::spawn(move || {
+            // Each fold moves entries from the log table into the wallet table
+            for i in 1..=40 {
+                let mut changeset = large_changeset(i);
+                changeset
+                    .tx_graph
+                    .txs
+                    .retain(|tx| tx.lock_time == absolute::LockTime::from_consensus(i - 1));
+                writer.append_log(&changeset).unwrap();
+                if i % 3 == 0 {
+                    writer.fold_log().unwrap();
+                }
+            }
+            writer_done.store(true, Ordering::SeqCst);
+        });
+
+        // A torn read would miss folded entries and go back in time
+        let mut seen = 0;
+        while !done.load(Ordering::SeqCst) {
+            let count = store.initialize_shared().unwrap().tx_graph.txs.len();
+            assert!(count >= seen);
+            seen = count;
+        }
+        handle.join().unwrap();
+        assert_eq!(store.initialize_shared().unwrap().tx_graph.txs.len(), 40);
+    }
+
+    #[cfg(feature = "wal")]
+    #[test]
+    fn test_append_log_survives_crash() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("log.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        for i in 1..=5 {
+            store.append_log(&large_changeset(i)).unwrap();
+        }
+        // Crash before folding
+        drop(store);
+
+        let mut store = RedbStore::open(&db_path).unwrap();
+        let stored = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 5);
+        assert_eq!(store.load_changeset().unwrap(), Some(stored.clone()));
+
+        store.fold_log().unwrap();
+        let read_txn = store.db().unwrap().begin_read().unwrap();
+        assert!(store.read_log(&read_txn).unwrap().is_empty());
+        drop(read_txn);
+        assert_eq!(store.load_changeset().unwrap(), Some(stored.clone()));
+        assert_eq!(WalletPersister::initialize(&mut store).unwrap(), stored);
+    }
+
+    #[cfg(feature = "wal")]
+    #[test]
+    fn test_has_wallet_sees_log() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("has_wallet_log.redb");
+
+        let store = RedbStore::create(&db_path).unwrap();
+        let mut savings = store.clone();
+        savings.namespace = Some("savings".to_string());
+        savings
+            .append_log(&ChangeSet {
+                network: Some(Network::Bitcoin),
+                ..Default::default()
+            })
+            .unwrap();
+
+        assert!(savings.has_wallet().unwrap());
+        assert!(store.has_wallet_in("savings").unwrap());
+        assert!(!store.has_wallet().unwrap());
+    }
+
+    #[cfg(feature = "wal")]
+    #[test]
+    fn test_is_empty_sees_log() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("is_empty_log.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        assert!(store.is_empty().unwrap());
+        store
+            .append_log(&ChangeSet {
+                network: Some(Network::Bitcoin),
+                ..Default::default()
+            })
+            .unwrap();
+        assert!(!store.is_empty().unwrap());
+    }
+
+    #[cfg(feature = "wal")]
+    #[test]
+    fn test_load_changeset_includes_log() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("load_log.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        store.append_log(&large_changeset(2)).unwrap();
+        assert_eq!(store.load_changeset().unwrap(), Some(large_changeset(2)));
+
+        WalletPersister::persist(&mut store, &large_changeset(1)).unwrap();
+        store.append_log(&large_changeset(3)).unwrap();
+        assert_eq!(
+            store.load_changeset().unwrap(),
+            Some(WalletPersister::initialize(&mut store).unwrap())
+        );
+    }
+
+    #[cfg(feature = "wal")]
+    #[test]
+    fn test_persist_folds_log() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("persist_folds_log.redb");
+
+        let block = |hash: u8| ChangeSet {
+            local_chain: [(100, Some(bitcoin::BlockHash::from_byte_array([hash; 32])))].into(),
+            ..Default::default()
+        };
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        store.append_log(&block(1)).unwrap();
+        WalletPersister::persist(&mut store, &block(2)).unwrap();
+        let read_txn = store.db().unwrap().begin_read().unwrap();
+        assert!(store.read_log(&read_txn).unwrap().is_empty());
+        drop(read_txn);
+
+        // A batch folds the log the same way
+        store.append_log(&block(3)).unwrap();
+        let mut txn = store.transaction().unwrap();
+        txn.persist(&block(4)).unwrap();
+        txn.commit().unwrap();
+        drop(store);
+
+        // The older log entries must not be merged over the newer persists
+        let mut store = RedbStore::open(&db_path).unwrap();
+        let stored = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(
+            stored.local_chain.blocks[&100],
+            Some(bitcoin::BlockHash::from_byte_array([4; 32]))
+        );
+    }
+
+    #[cfg(feature = "wal")]
+    #[test]
+    fn test_append_log_namespaces() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("log_namespaces.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let mut other = store.clone();
+        other.namespace = Some("b".to_string());
+
+        let block = |height: u32| ChangeSet {
+            local_chain: [(height, Some(bitcoin::BlockHash::all_zeros()))].into(),
+            ..Default::default()
+        };
+
+        // Namespace b's entries sort after the default ones in the shared log table
+        store.append_log(&block(1)).unwrap();
+        other.append_log(&block(5)).unwrap();
+        store.append_log(&block(2)).unwrap();
+
+        let snapshot = store.snapshot().unwrap();
+        let blocks = snapshot.changeset().unwrap().unwrap().local_chain.blocks;
+        assert_eq!(blocks.keys().copied().collect::<Vec<_>>(), [1, 2]);
+        drop(snapshot);
+        let blocks = other.initialize_shared().unwrap().local_chain.blocks;
+        assert_eq!(blocks.keys().copied().collect::<Vec<_>>(), [5]);
+    }
+
+    #[cfg(feature = "wal")]
+    #[test]
+    fn test_log_entries_follow_the_changeset() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("log_entries.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(1)).unwrap();
+        store.append_log(&large_changeset(3)).unwrap();
+
+        // Reads of the whole wallet see the unfolded entries
+        let snapshot = store.snapshot().unwrap();
+        assert_eq!(snapshot.changeset().unwrap().unwrap().tx_graph.txs.len(), 3);
+        drop(snapshot);
+
+        let mut dest = RedbStore::create(temp_dir.path().join("log_dest.redb")).unwrap();
+        store.copy_wallet_to("alice", &mut dest, "alice").unwrap();
+        let mut alice = store.clone();
+        alice.namespace = Some("alice".to_string());
+        alice.append_log(&large_changeset(2)).unwrap();
+        store.copy_wallet_to("alice", &mut dest, "alice").unwrap();
+        dest.namespace = Some("alice".to_string());
+        assert_eq!(dest.initialize_shared().unwrap().tx_graph.txs.len(), 2);
+
+        #[cfg(feature = "bundle")]
+        {
+            let bundle_path = temp_dir.path().join("log_bundle.tar");
+            store.export_bundle(&bundle_path).unwrap();
+            let restored =
+                RedbStore::import_bundle(&temp_dir.path().join("restored.redb"), &bundle_path)
+                    .unwrap();
+            assert_eq!(
+                restored
+                    .load_changeset()
+                    .unwrap()
+                    .unwrap()
+                    .tx_graph
+                    .txs
+                    .len(),
+                3
+            );
+        }
+
+        // A checkpoint holds the appended entries, and resetting to it drops later ones
+        let id = store.create_checkpoint().unwrap();
+        store.append_log(&large_changeset(5)).unwrap();
+        store.reset_to_checkpoint(id).unwrap();
+        assert_eq!(
+            WalletPersister::initialize(&mut store)
+                .unwrap()
+                .tx_graph
+                .txs
+                .len(),
+            3
+        );
+
+        // Clearing removes the log with the changeset
+        store.append_log(&large_changeset(4)).unwrap();
+        store.clear().unwrap();
+        assert!(WalletPersister::initialize(&mut store).unwrap().is_empty());
+        let read_txn = store.db().unwrap().begin_read().unwrap();
+        assert!(store.read_log(&read_txn).unwrap().is_empty());
+        drop(read_txn);
+
+        // Other namespaces keep their entries
+        assert_eq!(alice.initialize_shared().unwrap().tx_graph.txs.len(), 2);
+    }
+
+    #[cfg(feature = "wal")]
+    #[test]
+    fn test_readers_include_log_entries() {
+        let temp_dir = tempdir().unwrap();
+        let mut store = RedbStore::create(temp_dir.path().join("log_readers.redb")).unwrap();
+        store.append_log(&large_changeset(2)).unwrap();
+
+        // Already in the log, so nothing is new
+        assert!(store.diff(&large_changeset(2)).unwrap().new_txs.is_empty());
+        let report = store.validate(&large_changeset(2)).unwrap();
+        assert_eq!(
+            report.merged_size,
+            Some(store.serialized_size(&large_changeset(2)).unwrap())
+        );
+
+        // A store holding only log entries still has a wallet to merge
+        let mut dest = RedbStore::create(temp_dir.path().join("log_readers_dest.redb")).unwrap();
+        dest.merge_from(&store).unwrap();
+        assert_eq!(
+            dest.load_changeset().unwrap().unwrap().tx_graph.txs.len(),
+            2
+        );
+    }
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_store_is_send_sync() {
+        assert_send_sync::<RedbStore>();
+        assert_send_sync::<RedbError>();
+
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("send_sync.redb");
+        let global = std::sync::OnceLock::new();
+
+        std::thread::scope(|scope| {
+            for i in 1..=4 {
+                let global = &global;
+                let db_path = &db_path;
+                scope.spawn(move || {
+                    let store: &RedbStore =
+                        global.get_or_init(|| RedbStore::create(db_path).unwrap());
+                    store.persist_shared(&large_changeset(i)).unwrap();
+                    assert!(!store.initialize_shared().unwrap().is_empty());
+                });
+            }
+        });
+
+        let store = global.get().unwrap();
+        assert_eq!(store.initialize_shared().unwrap().tx_graph.txs.len(), 4);
+    }
+
+    #[test]
+    fn test_merge_from() {
+        let temp_dir = tempdir().unwrap();
+        let device_path = temp_dir.path().join("device.redb");
+        let backup_path = temp_dir.path().join("backup.redb");
+
+        {
+            let mut store = RedbStore::create(&device_path).unwrap();
+            let create_params = CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR)
+                .network(Network::Testnet);
+            PersistedWallet::create(&mut store, create_params).unwrap();
+        }
+        fs::copy(&device_path, &backup_path).unwrap();
+
+        // Complementary updates in the two copies
+        let reveal = |path: &Path, keychain: KeychainKind, count: u32| {
+            let mut store = RedbStore::open(path).unwrap();
+            let mut wallet = PersistedWallet::load(&mut store, LoadParams::default())
+                .unwrap()
+                .unwrap();
+            let _ = wallet.reveal_addresses_to(keychain, count).count();
+            wallet.persist(&mut store).unwrap();
+        };
+        reveal(&device_path, KeychainKind::External, 5);
+        reveal(&backup_path, KeychainKind::Internal, 3);
+
+        let mut device = RedbStore::open(&device_path).unwrap();
+        let backup = RedbStore::open(&backup_path).unwrap();
+        device.merge_from(&backup).unwrap();
+
+        let wallet = PersistedWallet::load(&mut device, LoadParams::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(wallet.derivation_index(KeychainKind::External), Some(5));
+        assert_eq!(wallet.derivation_index(KeychainKind::Internal), Some(3));
+    }
+
+    #[test]
+    fn test_merge_from_other_wallet() {
+        let temp_dir = tempdir().unwrap();
+        let create = |name: &str, external, internal| {
+            let mut store = RedbStore::create(temp_dir.path().join(name)).unwrap();
+            let create_params = CreateParams::new(external, internal).network(Network::Testnet);
+            PersistedWallet::create(&mut store, create_params).unwrap();
+            store
+        };
+
+        let mut first = create("first.redb", TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR);
+        let second = create("second.redb", TEST_CHANGE_DESCRIPTOR, TEST_DESCRIPTOR);
+        let before = first.load_changeset().unwrap();
+
+        assert!(matches!(
+            first.merge_from(&second),
+            Err(RedbError::DescriptorConflict(KeychainKind::External))
+        ));
+        assert_eq!(first.load_changeset().unwrap(), before);
+    }
+
+    #[test]
+    fn test_initialize_with_progress() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("progress.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let mut reports = Vec::new();
+        let empty = store.initialize_with_progress(|p| reports.push(p)).unwrap();
+        assert!(empty.is_empty());
+        assert_eq!(reports.len(), 1);
+        assert!(reports[0].is_complete());
+
+        WalletPersister::persist(&mut store, &large_changeset(10)).unwrap();
+        #[cfg(feature = "wal")]
+        for i in 11..=13 {
+            store.append_log(&large_changeset(i)).unwrap();
+        }
+
+        let mut reports = Vec::new();
+        let loaded = store.initialize_with_progress(|p| reports.push(p)).unwrap();
+        assert_eq!(loaded, WalletPersister::initialize(&mut store).unwrap());
+
+        assert_eq!(reports.first().unwrap().segments_read, 0);
+        assert!(reports
+            .windows(2)
+            .all(|w| w[1].segments_read > w[0].segments_read));
+        assert!(reports.last().unwrap().is_complete());
+        assert!(reports
+            .iter()
+            .all(|p| p.total_segments == reports.len() - 1));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_with_retry() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("retry.redb");
+        let backoff = std::time::Duration::from_millis(20);
+
+        let store = RedbStore::create(&db_path).unwrap();
+
+        // Without enough retries the lock is still held
+        assert!(matches!(
+            RedbStore::open_with_retry(&db_path, 1, backoff),
+            Err(RedbError::Database(redb::Error::DatabaseAlreadyOpen))
+        ));
+
+        let holder = std::thread::spawn(move || {
+            std::thread::sleep(std::time::Duration::from_millis(200));
+            drop(store);
+        });
+        RedbStore::open_with_retry(&db_path, 10, backoff).unwrap();
+        holder.join().unwrap();
+
+        // Errors other than a held lock are not retried
+        let missing = temp_dir.path().join("missing.redb");
+        let started = std::time::Instant::now();
+        assert!(
+            RedbStore::open_with_retry(&missing, 10, std::time::Duration::from_secs(1)).is_err()
+        );
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_errors_compose_with_bdk() {
+        fn assert_static_error<T: std::error::Error + Send + Sync + 'static>() {}
+        assert_static_error::<RedbError>();
+        assert_static_error::<bdk_wallet::LoadWithPersistError<RedbError>>();
+        assert_static_error::<bdk_wallet::CreateWithPersistError<RedbError>>();
+
+        // Store errors convert into the BDK wrappers with `?`
+        fn load(
+            path: &Path,
+        ) -> Result<Option<PersistedWallet<RedbStore>>, bdk_wallet::LoadWithPersistError<RedbError>>
+        {
+            let mut store = RedbStore::open(path)?;
+            PersistedWallet::load(&mut store, LoadParams::default())
+        }
+
+        fn create_and_persist(path: &Path) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            let mut store = RedbStore::create(path)?;
+            let create_params = CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR)
+                .network(Network::Testnet);
+            let mut wallet = PersistedWallet::create(&mut store, create_params)?;
+            wallet.reveal_next_address(KeychainKind::External);
+            wallet.persist(&mut store)?;
+            Ok(())
+        }
+
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("compose.redb");
+
+        assert!(matches!(
+            load(&db_path),
+            Err(bdk_wallet::LoadWithPersistError::Persist(_))
+        ));
+        create_and_persist(&db_path).unwrap();
+        assert!(load(&db_path).unwrap().is_some());
+    }
+
+    #[test]
+    fn test_create_and_open_from_file() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("from_file.redb");
+        let open_file = |create: bool| {
+            OpenOptions::new()
+                .read(true)
+                .write(true)
+                .create(create)
+                .truncate(false)
+                .open(&db_path)
+                .unwrap()
+        };
+
+        // An empty file holds no database yet
+        assert!(matches!(
+            RedbStore::open_from_file(open_file(true)),
+            Err(RedbError::Io(_))
+        ));
+
+        let mut store = RedbStore::create_from_file(open_file(false)).unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(4)).unwrap();
+        assert!(store.file_size().unwrap() > 0);
+        drop(store);
+
+        let mut store = RedbStore::open_from_file(open_file(false)).unwrap();
+        let stored = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(stored.tx_graph.txs.len(), 4);
+        assert_eq!(
+            store.file_size().unwrap(),
+            fs::metadata(&db_path).unwrap().len()
+        );
+    }
+
+    #[test]
+    fn test_validate() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("validate.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        PersistedWallet::create(&mut store, create_params).unwrap();
+        let stored = store.load_changeset().unwrap().unwrap();
+
+        let compatible = large_changeset(3);
+        let report = store.validate(&compatible).unwrap();
+        assert!(report.is_valid());
+        assert!(!report.is_empty);
+        let mut merged = stored.clone();
+        merged.merge(compatible);
+        assert_eq!(
+            report.merged_size,
+            Some(store.serialized_size(&merged).unwrap())
+        );
+
+        let mut incompatible = stored.clone();
+        incompatible.descriptor = stored.change_descriptor.clone();
+        let report = store.validate(&incompatible).unwrap();
+        assert!(!report.is_valid());
+        assert!(!report.descriptors_match);
+        // A descriptor conflict says nothing about the network
+        assert!(report.network_matches);
+        assert_eq!(report.merged_size, None);
+
+        let other_network = ChangeSet {
+            network: Some(Network::Bitcoin),
+            ..Default::default()
+        };
+        let report = store.validate(&other_network).unwrap();
+        assert!(report.descriptors_match);
+        assert!(!report.network_matches);
+        assert_eq!(report.merged_size, None);
+
+        // Nothing was written
+        assert_eq!(store.load_changeset().unwrap(), Some(stored));
+
+        store.set_max_changeset_bytes(Some(1));
+        let report = store.validate(&ChangeSet::default()).unwrap();
+        assert!(report.is_empty);
+        assert!(!report.within_limit);
+    }
+
+    #[test]
+    fn test_timestamps() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("timestamps.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let created = store.created_at().unwrap().unwrap();
+
+        // Namespaced stores record their own creation time when created
+        let db = Arc::new(Database::create(temp_dir.path().join("shared.redb")).unwrap());
+        let savings = RedbStore::from_database_with_namespace(db, "savings").unwrap();
+        assert!(savings.created_at().unwrap().is_some());
+        assert_eq!(store.last_persisted_at().unwrap(), None);
+
+        WalletPersister::persist(&mut store, &large_changeset(1)).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(10));
+        WalletPersister::persist(&mut store, &large_changeset(2)).unwrap();
+
+        // Persists don't move the creation time
+        assert_eq!(store.created_at().unwrap(), Some(created));
+        assert!(store.last_persisted_at().unwrap().unwrap() >= created);
+
+        assert!(matches!(
+            store.put_metadata("created_at", b"0"),
+            Err(RedbError::ReservedKey(_))
+        ));
+    }
+
+    #[test]
+    fn test_cache_stats() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("cache_stats.redb");
+
+        let mut store = RedbStore::builder()
+            .track_reads(true)
+            .open_or_create(&db_path)
+            .unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(50)).unwrap();
+        drop(store);
+
+        let store = RedbStore::builder()
+            .track_reads(true)
+            .open_or_create(&db_path)
+            .unwrap();
+        for _ in 0..100 {
+            store.load_changeset().unwrap();
+        }
+        let stats = store.cache_stats().unwrap();
+        assert!(stats.physical_reads.unwrap() > 0);
+        assert!(stats.bytes_read.unwrap() > 0);
+        drop(store);
+
+        // Counting reads works on a read-only store
+        let mut store = RedbStore::builder()
+            .track_reads(true)
+            .read_only(true)
+            .open_or_create(&db_path)
+            .unwrap();
+        assert_eq!(
+            store.load_changeset().unwrap().unwrap().tx_graph.txs.len(),
+            50
+        );
+        assert!(store.cache_stats().unwrap().physical_reads.unwrap() > 0);
+        assert!(matches!(
+            WalletPersister::persist(&mut store, &large_changeset(51)),
+            Err(RedbError::ReadOnly)
+        ));
+        drop(store);
+        let missing = temp_dir.path().join("missing.redb");
+        assert!(RedbStore::builder()
+            .track_reads(true)
+            .read_only(true)
+            .open_or_create(&missing)
+            .is_err());
+        assert!(!missing.exists());
+
+        // Nor does it turn an empty file into a database
+        let empty = temp_dir.path().join("empty.redb");
+        fs::write(&empty, b"").unwrap();
+        assert!(RedbStore::builder()
+            .track_reads(true)
+            .read_only(true)
+            .open_or_create(&empty)
+            .is_err());
+        assert_eq!(fs::metadata(&empty).unwrap().len(), 0);
+
+        // Reads are only counted when asked for
+        let store = RedbStore::open(&db_path).unwrap();
+        let stats = store.cache_stats().unwrap();
+        assert_eq!(stats.physical_reads, None);
+        assert_eq!(stats.bytes_read, None);
+    }
+
+    #[test]
+    fn test_persist_wallet() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("persist_wallet.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        let mut wallet = PersistedWallet::create(&mut store, create_params).unwrap();
+
+        let address = wallet.reveal_next_address(KeychainKind::External);
+        assert!(store.persist_wallet(&mut wallet).unwrap());
+        let persisted_at = store.last_persisted_at().unwrap();
+
+        // Nothing staged, nothing written
+        assert!(!store.persist_wallet(&mut wallet).unwrap());
+        assert_eq!(store.last_persisted_at().unwrap(), persisted_at);
+
+        let loaded = PersistedWallet::load(&mut store, LoadParams::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            loaded.derivation_index(KeychainKind::External),
+            Some(address.index)
+        );
+    }
+
+    #[test]
+    fn test_json_pretty() {
+        let temp_dir = tempdir().unwrap();
+        let changeset = large_changeset(5);
+        let stored_bytes = |name: &str, pretty: bool| {
+            let mut store = RedbStore::create(temp_dir.path().join(name)).unwrap();
+            store.set_json_pretty(pretty);
+            WalletPersister::persist(&mut store, &changeset).unwrap();
+            assert_eq!(WalletPersister::initialize(&mut store).unwrap(), changeset);
+            let bytes = store.get_changeset_bytes().unwrap().unwrap();
+            assert_eq!(store.serialized_size(&changeset).unwrap(), bytes.len());
+            bytes
+        };
+
+        let compact = stored_bytes("compact.redb", false);
+        let pretty = stored_bytes("pretty.redb", true);
+        assert!(pretty.len() > compact.len());
+        assert!(pretty.contains(&b'\n'));
+        assert!(!compact.contains(&b'\n'));
+    }
+
+    #[test]
+    fn test_replace_file() {
+        let temp_dir = tempdir().unwrap();
+        let target_path = temp_dir.path().join("target.redb");
+        let source_path = temp_dir.path().join("source.redb");
+
+        let mut target = RedbStore::create(&target_path).unwrap();
+        WalletPersister::persist(&mut target, &large_changeset(1)).unwrap();
+        let mut source = RedbStore::create(&source_path).unwrap();
+        WalletPersister::persist(&mut source, &large_changeset(7)).unwrap();
+        drop(source);
+
+        // The target is still open
+        assert!(RedbStore::replace_file(&target_path, &source_path).is_err());
+        drop(target);
+
+        RedbStore::replace_file(&target_path, &source_path).unwrap();
+        assert!(!source_path.exists());
+
+        let mut target = RedbStore::open(&target_path).unwrap();
+        let stored = WalletPersister::initialize(&mut target).unwrap();
+        assert_eq!(stored, large_changeset(7));
+        drop(target);
+
+        // A target that isn't a readable database is replaced untouched
+        fs::write(&target_path, b"not a database").unwrap();
+        let mut source = RedbStore::create(&source_path).unwrap();
+        WalletPersister::persist(&mut source, &large_changeset(3)).unwrap();
+        drop(source);
+        RedbStore::replace_file(&target_path, &source_path).unwrap();
+        let mut target = RedbStore::open(&target_path).unwrap();
+        assert_eq!(
+            WalletPersister::initialize(&mut target).unwrap(),
+            large_changeset(3)
+        );
+    }
+
+    #[test]
+    fn test_last_revealed_indices_and_tip() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("status.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        assert!(store.last_revealed_indices().unwrap().is_empty());
+        assert_eq!(store.tip_height().unwrap(), None);
+
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        let mut wallet = PersistedWallet::create(&mut store, create_params).unwrap();
+        let _ = wallet
+            .reveal_addresses_to(KeychainKind::External, 4)
+            .collect::<Vec<_>>();
+        wallet.reveal_next_address(KeychainKind::Internal);
+        wallet.persist(&mut store).unwrap();
+
+        let indices = store.last_revealed_indices().unwrap();
+        assert_eq!(indices.get(&KeychainKind::External), Some(&4));
+        assert_eq!(indices.get(&KeychainKind::Internal), Some(&0));
+        // A new wallet's chain holds the genesis block
+        assert_eq!(store.tip_height().unwrap(), Some(0));
+    }
+
+    #[cfg(feature = "wal")]
+    #[test]
+    fn test_status_includes_log_entries() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("status_log.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        let mut wallet = PersistedWallet::create(&mut store, create_params).unwrap();
+        let _ = wallet
+            .reveal_addresses_to(KeychainKind::External, 4)
+            .collect::<Vec<_>>();
+        let mut staged = wallet.take_staged().unwrap();
+        staged.local_chain = [(10, Some(bitcoin::BlockHash::all_zeros()))].into();
+        store.append_log(&staged).unwrap();
+
+        // Unfolded entries count before fold_log runs
+        let indices = store.last_revealed_indices().unwrap();
+        assert_eq!(indices.get(&KeychainKind::External), Some(&4));
+        assert_eq!(store.tip_height().unwrap(), Some(10));
+        let summary = store.describe().unwrap();
+        assert_eq!(summary.tip_height, Some(10));
+        assert_eq!(summary.last_revealed, indices);
+    }
+
+    #[test]
+    fn test_persist_if_version() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("cas.redb");
+
+        let mut first = RedbStore::create(&db_path).unwrap();
+        assert_eq!(first.version().unwrap(), 0);
+        WalletPersister::persist(&mut first, &large_changeset(1)).unwrap();
+        let mut second = first.clone();
+
+        // Both writers read the same version
+        let read_first = first.version().unwrap();
+        let read_second = second.version().unwrap();
+        assert_eq!(read_first, 1);
+        assert_eq!(read_second, 1);
+
+        let updated = first
+            .persist_if_version(read_first, &large_changeset(2))
+            .unwrap();
+        assert_eq!(updated, 2);
+
+        // The second writer's view is stale
+        assert!(matches!(
+            second.persist_if_version(read_second, &large_changeset(3)),
+            Err(RedbError::VersionConflict {
+                expected: 1,
+                actual: 2
+            })
+        ));
+        assert_eq!(first.load_changeset().unwrap(), Some(large_changeset(2)));
+
+        // Retrying with the fresh version succeeds
+        let updated = second
+            .persist_if_version(updated, &large_changeset(3))
+            .unwrap();
+        assert_eq!(updated, 3);
+        assert_eq!(
+            second.persist_if_version(3, &ChangeSet::default()).unwrap(),
+            3
+        );
+    }
+
+    #[cfg(feature = "bundle")]
+    #[test]
+    fn test_bundle_round_trip() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("exported.redb");
+        let bundle_path = temp_dir.path().join("wallet.tar");
+        let restored_path = temp_dir.path().join("restored.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        let mut wallet = PersistedWallet::create(&mut store, create_params).unwrap();
+        wallet.reveal_next_address(KeychainKind::External);
+        wallet.persist(&mut store).unwrap();
+        store.export_bundle(&bundle_path).unwrap();
+
+        // Bundles are never overwritten
+        assert!(store.export_bundle(&bundle_path).is_err());
+
+        let bundle = RedbStore::read_bundle(&bundle_path).unwrap();
+        assert_eq!(bundle.manifest.schema_version, BUNDLE_SCHEMA_VERSION);
+        assert_eq!(bundle.manifest.network, Some(Network::Testnet));
+        assert!(bundle.manifest.descriptor_fingerprint.is_some());
+        assert_eq!(
+            bundle.manifest.last_persisted_at,
+            store
+                .last_persisted_at()
+                .unwrap()
+                .map(|t| t.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs())
+        );
+
+        // The bundle is a plain tar archive with the manifest and the changeset
+        let mut archive = tar::Archive::new(fs::File::open(&bundle_path).unwrap());
+        let names: Vec<_> = archive
+            .entries()
+            .unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().into_owned())
+            .collect();
+        assert_eq!(
+            names,
+            [
+                PathBuf::from(BUNDLE_MANIFEST_FILE),
+                PathBuf::from(BUNDLE_CHANGESET_FILE)
+            ]
+        );
+
+        let mut restored = RedbStore::import_bundle(&restored_path, &bundle_path).unwrap();
+        assert_eq!(
+            WalletPersister::initialize(&mut restored).unwrap(),
+            WalletPersister::initialize(&mut store).unwrap()
+        );
+        assert_eq!(restored.stored_network().unwrap(), Some(Network::Testnet));
+        assert_eq!(restored.created_at().unwrap(), store.created_at().unwrap());
+        // The import was written aside and moved into place
+        assert!(!temp_dir.path().join("restored.redb.import").exists());
+
+        // Importing never writes into an existing file
+        assert!(RedbStore::import_bundle(&db_path, &bundle_path).is_err());
+
+        // A bundle from a newer format version is refused before anything is written
+        let newer_path = temp_dir.path().join("newer.tar");
+        let mut manifest = bundle.manifest.clone();
+        manifest.schema_version = BUNDLE_SCHEMA_VERSION + 1;
+        let manifest = serde_json::to_vec(&manifest).unwrap();
+        let mut archive = tar::Builder::new(fs::File::create(&newer_path).unwrap());
+        let mut header = tar::Header::new_gnu();
+        header.set_size(manifest.len() as u64);
+        header.set_cksum();
+        archive
+            .append_data(&mut header, BUNDLE_MANIFEST_FILE, manifest.as_slice())
+            .unwrap();
+        archive.finish().unwrap();
+        let newer_restored = temp_dir.path().join("newer.redb");
+        let error = RedbStore::import_bundle(&newer_restored, &newer_path).unwrap_err();
+        assert!(matches!(
+            error,
+            RedbError::UnsupportedBundleVersion {
+                found,
+                supported: BUNDLE_SCHEMA_VERSION,
+            } if found == BUNDLE_SCHEMA_VERSION + 1
+        ));
+        assert!(!newer_restored.exists());
+
+        // A failed import leaves nothing behind
+        let failed_path = temp_dir.path().join("failed.redb");
+        fs::create_dir(temp_dir.path().join("failed.redb.import")).unwrap();
+        assert!(RedbStore::import_bundle(&failed_path, &bundle_path).is_err());
+        assert!(!failed_path.exists());
+
+        // Moving the import into place never replaces a wallet that appeared meanwhile
+        let pending = temp_dir.path().join("pending.redb.import");
+        let raced = temp_dir.path().join("pending.redb");
+        fs::write(&pending, b"import").unwrap();
+        fs::write(&raced, b"wallet").unwrap();
+        let error = move_no_replace(&pending, &raced).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::AlreadyExists);
+        assert_eq!(fs::read(&raced).unwrap(), b"wallet");
+    }
+
+    #[test]
+    fn test_incompatible_file_format() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("newer.redb");
+        drop(RedbStore::create(&db_path).unwrap());
+
+        // Stamp both commit slots of the header with a file format from the future
+        let mut bytes = fs::read(&db_path).unwrap();
+        bytes[64] = 99;
+        bytes[64 + 128] = 99;
+        fs::write(&db_path, bytes).unwrap();
+
+        let error = RedbStore::open(&db_path).unwrap_err();
+        assert_eq!(error.code(), RedbErrorCode::IncompatibleFileFormat);
+        assert!(error.to_string().contains("newer redb"));
+
+        let error = RedbError::from(redb::DatabaseError::UpgradeRequired(1));
+        assert_eq!(error.code(), RedbErrorCode::IncompatibleFileFormat);
+        assert!(error.to_string().contains("older redb"));
+
+        let error = RedbError::from(redb::DatabaseError::DatabaseAlreadyOpen);
+        assert_eq!(error.code(), RedbErrorCode::Database);
+    }
+
+    #[test]
+    fn test_prune() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("prune.redb");
+
+        let tx = |id: u32, spends: Option<Txid>| {
+            Arc::new(Transaction {
+                version: transaction::Version::TWO,
+                lock_time: absolute::LockTime::from_consensus(id),
+                input: vec![TxIn {
+                    previous_output: OutPoint::new(spends.unwrap_or_else(Txid::all_zeros), 0),
+                    ..Default::default()
+                }],
+                output: vec![TxOut {
+                    value: Amount::from_sat(1_000),
+                    script_pubkey: ScriptBuf::new(),
+                }],
+            })
+        };
+        let anchor = |height: u32| bdk_chain::ConfirmationBlockTime {
+            block_id: bdk_chain::BlockId {
+                height,
+                hash: bitcoin::BlockHash::all_zeros(),
+            },
+            confirmation_time: 0,
+        };
+
+        // A chain of spends: old_spent -> old_middle -> recent
+        let old_spent = tx(1, None);
+        let old_middle = tx(2, Some(old_spent.compute_txid()));
+        let recent = tx(3, Some(old_middle.compute_txid()));
+        // An old transaction whose output is still unspent
+        let old_unspent = tx(4, None);
+        // An old transaction spent only by an unconfirmed one
+        let old_spent_unconfirmed = tx(5, None);
+        let unconfirmed = tx(6, Some(old_spent_unconfirmed.compute_txid()));
+
+        let mut changeset = ChangeSet::default();
+        for (tx, height) in [
+            (&old_spent, Some(10)),
+            (&old_middle, Some(20)),
+            (&recent, Some(500)),
+            (&old_unspent, Some(30)),
+            (&old_spent_unconfirmed, Some(40)),
+            (&unconfirmed, None),
+        ] {
+            changeset.tx_graph.txs.insert(Arc::clone(tx));
+            if let Some(height) = height {
+                changeset
+                    .tx_graph
+                    .anchors
+                    .insert((anchor(height), tx.compute_txid()));
+            }
+        }
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        WalletPersister::persist(&mut store, &changeset).unwrap();
+
+        let reclaimed = store.prune(100).unwrap();
+        assert!(reclaimed > 0);
+
+        let stored = store.load_changeset().unwrap().unwrap();
+        let mut txids: Vec<Txid> = stored
+            .tx_graph
+            .txs
+            .iter()
+            .map(|tx| tx.compute_txid())
+            .collect();
+        txids.sort();
+        let mut expected: Vec<Txid> = [&recent, &old_unspent, &old_spent_unconfirmed, &unconfirmed]
+            .iter()
+            .map(|tx| tx.compute_txid())
+            .collect();
+        expected.sort();
+        assert_eq!(txids, expected);
+        assert_eq!(stored.tx_graph.anchors.len(), 3);
+
+        // Pruning again finds nothing
+        assert_eq!(store.prune(100).unwrap(), 0);
+    }
+
+    #[cfg(feature = "wal")]
+    #[test]
+    fn test_prune_folds_log() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("prune_log.redb");
+
+        let tx = |id: u32, spends: Option<Txid>| {
+            Arc::new(Transaction {
+                version: transaction::Version::TWO,
+                lock_time: absolute::LockTime::from_consensus(id),
+                input: vec![TxIn {
+                    previous_output: OutPoint::new(spends.unwrap_or_else(Txid::all_zeros), 0),
+                    ..Default::default()
+                }],
+                output: vec![TxOut {
+                    value: Amount::from_sat(1_000),
+                    script_pubkey: ScriptBuf::new(),
+                }],
+            })
+        };
+        let spent = tx(1, None);
+        let spending = tx(2, Some(spent.compute_txid()));
+        let anchor = |tx: &Arc<Transaction>| {
+            let block_time = bdk_chain::ConfirmationBlockTime {
+                block_id: bdk_chain::BlockId {
+                    height: 10,
+                    hash: bitcoin::BlockHash::all_zeros(),
+                },
+                confirmation_time: 0,
+            };
+            (block_time, tx.compute_txid())
+        };
+
+        let mut changeset = ChangeSet::default();
+        for tx in [&spent, &spending] {
+            changeset.tx_graph.txs.insert(Arc::clone(tx));
+            changeset.tx_graph.anchors.insert(anchor(tx));
+        }
+        let mut store = RedbStore::create(&db_path).unwrap();
+        WalletPersister::persist(&mut store, &changeset).unwrap();
+
+        // The spent transaction is also waiting in the log
+        let mut logged = ChangeSet::default();
+        logged.tx_graph.txs.insert(Arc::clone(&spent));
+        store.append_log(&logged).unwrap();
+
+        assert!(store.prune(100).unwrap() > 0);
+        let read_txn = store.db().unwrap().begin_read().unwrap();
+        assert!(store.read_log(&read_txn).unwrap().is_empty());
+        drop(read_txn);
+
+        let stored = WalletPersister::initialize(&mut store).unwrap();
+        let txids: Vec<Txid> = stored
+            .tx_graph
+            .txs
+            .iter()
+            .map(|tx| tx.compute_txid())
+            .collect();
+        assert_eq!(txids, vec![spending.compute_txid()]);
+    }
+
+    #[test]
+    fn test_profiles() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("profiles.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        assert_eq!(store.active_profile().unwrap(), None);
+
+        store.set_active_profile("a").unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(2)).unwrap();
+
+        store.set_active_profile("b").unwrap();
+        assert!(!store.has_wallet().unwrap());
+        WalletPersister::persist(&mut store, &large_changeset(5)).unwrap();
+
+        store.set_active_profile("a").unwrap();
+        assert_eq!(
+            WalletPersister::initialize(&mut store).unwrap(),
+            large_changeset(2)
+        );
+        assert!(matches!(
+            store.set_active_profile("a::b"),
+            Err(RedbError::InvalidNamespace(_))
+        ));
+        drop(store);
+
+        // Reopening restores the active profile
+        let mut store = RedbStore::open(&db_path).unwrap();
+        assert_eq!(store.active_profile().unwrap().as_deref(), Some("a"));
+        assert_eq!(
+            WalletPersister::initialize(&mut store).unwrap(),
+            large_changeset(2)
+        );
+        store.set_active_profile("b").unwrap();
+        assert_eq!(
+            WalletPersister::initialize(&mut store).unwrap(),
+            large_changeset(5)
+        );
+    }
+
+    #[test]
+    fn test_deserialize_limits() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("limits.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let nested = format!("{}{}", "[".repeat(100_000), "]".repeat(100_000));
+        let write_txn = store.db().unwrap().begin_write().unwrap();
+        {
+            let mut table = write_txn.open_table(store.wallet_table()).unwrap();
+            table.insert(CHANGESET_KEY, nested.as_bytes()).unwrap();
+        }
+        write_txn.commit().unwrap();
+
+        let error = WalletPersister::initialize(&mut store).unwrap_err();
+        assert!(matches!(error, RedbError::Deserialization(_)));
+        assert!(error.to_string().contains("nests deeper"));
+
+        // Brackets inside strings don't count
+        let quoted = format!("\"{}\"", "[".repeat(1_000));
+        assert!(
+            codec::decode_json::<String>(quoted.as_bytes(), &DeserializeLimits::default()).is_ok()
+        );
+
+        // A real changeset is well within the defaults, but not within a tiny size limit
+        store.clear().unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(10)).unwrap();
+        assert!(WalletPersister::initialize(&mut store).is_ok());
+        store.set_deserialize_limits(DeserializeLimits {
+            max_bytes: 16,
+            ..DeserializeLimits::default()
+        });
+        assert!(matches!(
+            WalletPersister::initialize(&mut store),
+            Err(RedbError::Deserialization(_))
+        ));
+    }
+
+    #[test]
+    fn test_checkpoints() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("checkpoints.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        let mut wallet = PersistedWallet::create(&mut store, create_params).unwrap();
+        let mut reveal_to = |store: &mut RedbStore, index: u32| {
+            let _ = wallet
+                .reveal_addresses_to(KeychainKind::External, index)
+                .collect::<Vec<_>>();
+            wallet.persist(store).unwrap();
+        };
+
+        reveal_to(&mut store, 2);
+        let first = store.create_checkpoint().unwrap();
+        reveal_to(&mut store, 5);
+        let second = store.create_checkpoint().unwrap();
+        assert_eq!((first, second), (1, 2));
+
+        // A bad update
+        reveal_to(&mut store, 50);
+
+        let checkpoints = store.list_checkpoints().unwrap();
+        let ids: Vec<u64> = checkpoints.iter().map(|c| c.id).collect();
+        assert_eq!(ids, vec![first, second]);
+        assert!(checkpoints.iter().all(|c| c.bytes > 0));
+
+        store.reset_to_checkpoint(first).unwrap();
+        let indices = store.last_revealed_indices().unwrap();
+        assert_eq!(indices.get(&KeychainKind::External), Some(&2));
+        let loaded = PersistedWallet::load(&mut store, LoadParams::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.derivation_index(KeychainKind::External), Some(2));
+
+        assert!(matches!(
+            store.reset_to_checkpoint(99),
+            Err(RedbError::CheckpointNotFound(99))
+        ));
+        assert_eq!(store.list_checkpoints().unwrap().len(), 2);
+
+        // Deleting the newest checkpoint doesn't free its id
+        store.delete_checkpoint(second).unwrap();
+        let third = store.create_checkpoint().unwrap();
+        assert_eq!(third, 3);
+        assert!(matches!(
+            store.reset_to_checkpoint(second),
+            Err(RedbError::CheckpointNotFound(2))
+        ));
+
+        // Checkpoints belong to the active profile
+        store.set_active_profile("other").unwrap();
+        assert!(store.list_checkpoints().unwrap().is_empty());
+        assert_eq!(store.create_checkpoint().unwrap(), 1);
+        assert!(matches!(
+            store.reset_to_checkpoint(second),
+            Err(RedbError::CheckpointNotFound(_))
+        ));
+        store.delete_checkpoint(1).unwrap();
+        assert!(matches!(
+            store.delete_checkpoint(1),
+            Err(RedbError::CheckpointNotFound(1))
+        ));
+
+        // Only the newest checkpoints are kept
+        store.set_max_checkpoints(Some(2));
+        for _ in 0..4 {
+            store.create_checkpoint().unwrap();
+        }
+        let ids: Vec<u64> = store
+            .list_checkpoints()
+            .unwrap()
+            .iter()
+            .map(|c| c.id)
+            .collect();
+        assert_eq!(ids, vec![4, 5]);
+    }
+
+    #[test]
+    fn test_all_table_stats() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("table_stats.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(3)).unwrap();
+
+        let log_name = format!("{}_log", DEFAULT_TABLE_NAME);
+        let write_txn = store.db().unwrap().begin_write().unwrap();
+        {
+            let mut log = write_txn
+                .open_table(TableDefinition::<&str, &[u8]>::new(&log_name))
+                .unwrap();
+            log.insert("log::00000000000000000000", b"{}".as_slice())
+                .unwrap();
+        }
+        write_txn.commit().unwrap();
+
+        let all = store.all_table_stats().unwrap();
+        let names: Vec<&str> = all.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec![DEFAULT_TABLE_NAME, log_name.as_str()]);
+
+        let wallet = store.table_stats().unwrap();
+        assert_eq!(wallet.stored_bytes(), all[0].1.stored_bytes());
+        assert_eq!(
+            store.table_stats_for(&log_name).unwrap().stored_bytes(),
+            all[1].1.stored_bytes()
+        );
+        assert!(store.table_stats_for("missing").is_err());
+    }
+
+    #[test]
+    fn test_touch() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("touch.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        assert_eq!(store.last_accessed_at().unwrap(), None);
+
+        // An empty persist writes nothing
+        WalletPersister::persist(&mut store, &ChangeSet::default()).unwrap();
+        assert_eq!(store.last_accessed_at().unwrap(), None);
+
+        store.touch().unwrap();
+        let first = store.last_accessed_at().unwrap().unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        store.touch().unwrap();
+        assert!(store.last_accessed_at().unwrap().unwrap() > first);
+
+        assert_eq!(store.load_changeset().unwrap(), None);
+        assert_eq!(store.last_persisted_at().unwrap(), None);
+    }
+
+    #[test]
+    fn test_open_or_create_reporting() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("reporting.redb");
+
+        let (store, created) = RedbStore::open_or_create_reporting(&db_path).unwrap();
+        assert!(created);
+        assert!(store.file_size().unwrap() > 0);
+        drop(store);
+
+        let (_store, created) = RedbStore::open_or_create_reporting(&db_path).unwrap();
+        assert!(!created);
+    }
+
+    #[test]
+    fn test_copy_wallet_to() {
+        let temp_dir = tempdir().unwrap();
+        let source_path = temp_dir.path().join("shard_a.redb");
+        let dest_path = temp_dir.path().join("shard_b.redb");
+
+        let changeset = ChangeSet {
+            network: Some(Network::Testnet),
+            ..Default::default()
+        };
+
+        let db = Arc::new(Database::create(&source_path).unwrap());
+        let mut alice = RedbStore::from_database_with_namespace(Arc::clone(&db), "alice").unwrap();
+        WalletPersister::persist(&mut alice, &changeset).unwrap();
+        let source = RedbStore::from_database(db).unwrap();
+
+        let mut dest = RedbStore::create(&dest_path).unwrap();
+        source.copy_wallet_to("alice", &mut dest, "bob").unwrap();
+
+        // The source is untouched and the destination loads the copy
+        assert!(source.has_wallet_in("alice").unwrap());
+        assert!(!dest.has_wallet_in("alice").unwrap());
+        drop(dest);
+
+        let db = Arc::new(Database::open(&dest_path).unwrap());
+        let mut bob = RedbStore::from_database_with_namespace(db, "bob").unwrap();
+        assert_eq!(WalletPersister::initialize(&mut bob).unwrap(), changeset);
+
+        // A namespace without a wallet copies nothing
+        let mut dest = RedbStore::create(temp_dir.path().join("empty.redb")).unwrap();
+        source.copy_wallet_to("carol", &mut dest, "carol").unwrap();
+        assert!(!dest.has_wallet_in("carol").unwrap());
+
+        assert!(matches!(
+            source.copy_wallet_to("a::b", &mut dest, "carol"),
+            Err(RedbError::InvalidNamespace(_))
+        ));
+    }
+
+    #[test]
+    fn test_is_empty() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("is_empty.redb");
+
+        // Pristine
+        let db = Arc::new(Database::create(&db_path).unwrap());
+        let mut store = RedbStore::from_database(Arc::clone(&db)).unwrap();
+        assert!(store.is_empty().unwrap());
+
+        // Metadata only, in the default and another namespace
+        store.put_metadata("label", b"Savings").unwrap();
+        store.touch().unwrap();
+        let mut other = RedbStore::from_database_with_namespace(db, "other").unwrap();
+        other.touch().unwrap();
+        assert!(store.is_empty().unwrap());
+
+        // Only a store's own changeset makes it non-empty
+        let changeset = ChangeSet {
+            network: Some(Network::Testnet),
+            ..Default::default()
+        };
+        WalletPersister::persist(&mut other, &changeset).unwrap();
+        assert!(store.is_empty().unwrap());
+        assert!(!other.is_empty().unwrap());
+
+        WalletPersister::persist(&mut store, &changeset).unwrap();
+        assert!(!store.is_empty().unwrap());
+        other.clear().unwrap();
+        assert!(other.is_empty().unwrap());
+
+        // Another namespace's wallet with descriptors, index entry included, doesn't count
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        PersistedWallet::create(&mut other, create_params).unwrap();
+        store.clear().unwrap();
+        assert!(store.is_empty().unwrap());
+        assert!(!other.is_empty().unwrap());
+    }
+
+    #[test]
+    fn test_dump_and_load_json() {
+        let temp_dir = tempdir().unwrap();
+
+        let db = Arc::new(Database::create(temp_dir.path().join("source.redb")).unwrap());
+        let mut default_store = RedbStore::from_database(Arc::clone(&db)).unwrap();
+        let mut alice = RedbStore::from_database_with_namespace(db, "alice").unwrap();
+
+        let testnet = ChangeSet {
+            network: Some(Network::Testnet),
+            ..Default::default()
+        };
+        let signet = ChangeSet {
+            network: Some(Network::Signet),
+            ..Default::default()
+        };
+        WalletPersister::persist(&mut default_store, &testnet).unwrap();
+        WalletPersister::persist(&mut alice, &signet).unwrap();
+        alice.put_metadata("label", b"Alice").unwrap();
+
+        let mut dump = Vec::new();
+        default_store.dump_json(&mut dump).unwrap();
+
+        let mut restored = RedbStore::create(temp_dir.path().join("restored.redb")).unwrap();
+        restored.load_json(dump.as_slice()).unwrap();
+
+        assert_eq!(
+            restored.iter_entries().unwrap(),
+            default_store.iter_entries().unwrap()
+        );
+        assert_eq!(restored.load_changeset().unwrap(), Some(testnet.clone()));
+
+        let mut restored_alice = restored.clone();
+        restored_alice.namespace = Some("alice".to_string());
+        assert_eq!(restored_alice.load_changeset().unwrap(), Some(signet));
+        assert_eq!(
+            restored_alice.get_metadata("label").unwrap(),
+            Some(b"Alice".to_vec())
+        );
+
+        // The active profile's changeset is dumped and restored under that profile
+        let mut profiled = RedbStore::create(temp_dir.path().join("profiled.redb")).unwrap();
+        WalletPersister::persist(&mut profiled, &testnet).unwrap();
+        profiled.set_active_profile("watch-only").unwrap();
+        let watch_only = ChangeSet {
+            network: Some(Network::Testnet),
+            local_chain: [(0, Some(bitcoin::BlockHash::all_zeros()))].into(),
+            ..Default::default()
+        };
+        WalletPersister::persist(&mut profiled, &watch_only).unwrap();
+        let mut dump = Vec::new();
+        profiled.dump_json(&mut dump).unwrap();
+        let mut restored =
+            RedbStore::create(temp_dir.path().join("restored_profile.redb")).unwrap();
+        restored.load_json(dump.as_slice()).unwrap();
+        assert_eq!(
+            restored.active_profile().unwrap(),
+            Some("watch-only".to_string())
+        );
+        assert!(restored.has_wallet().unwrap());
+        assert_eq!(
+            WalletPersister::initialize(&mut restored).unwrap(),
+            watch_only
+        );
+
+        // Garbage is rejected without writing anything
+        let mut fresh = RedbStore::create(temp_dir.path().join("fresh.redb")).unwrap();
+        assert!(matches!(
+            fresh.load_json(b"not json".as_slice()),
+            Err(RedbError::Deserialization(_))
+        ));
+        assert!(fresh.is_empty().unwrap());
+    }
+
+    #[test]
+    fn test_descriptor_change() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("descriptor_change.redb");
+
+        let wallet_changeset = |external: &str, internal: &str| {
+            let mut store = RedbStore::create(temp_dir.path().join("scratch.redb")).unwrap();
+            let create_params = CreateParams::new(external.to_string(), internal.to_string())
+                .network(Network::Testnet);
+            PersistedWallet::create(&mut store, create_params).unwrap();
+            let changeset = store.load_changeset().unwrap().unwrap();
+            drop(store);
+            fs::remove_file(temp_dir.path().join("scratch.redb")).unwrap();
+            changeset
+        };
+        let original = wallet_changeset(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR);
+        let swapped = wallet_changeset(TEST_CHANGE_DESCRIPTOR, TEST_DESCRIPTOR);
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        WalletPersister::persist(&mut store, &original).unwrap();
+
+        // The same descriptors merge fine
+        WalletPersister::persist(&mut store, &original).unwrap();
+        assert_eq!(store.load_changeset().unwrap(), Some(original.clone()));
+
+        // Different descriptors are refused and nothing changes
+        assert!(matches!(
+            WalletPersister::persist(&mut store, &swapped),
+            Err(RedbError::DescriptorConflict(KeychainKind::External))
+        ));
+        assert_eq!(store.load_changeset().unwrap(), Some(original));
+
+        // The escape hatch replaces the stored wallet
+        store.set_allow_descriptor_change(true);
+        WalletPersister::persist(&mut store, &swapped).unwrap();
+        assert_eq!(store.load_changeset().unwrap(), Some(swapped));
+    }
+
+    #[test]
+    fn test_debug_is_redacted() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("debug.redb");
+
+        let mut store = RedbStore::builder()
+            .namespace("savings")
+            .open_or_create(&db_path)
+            .unwrap();
+        store
+            .expect_descriptors(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR)
+            .unwrap();
+
+        let debug = format!("{:?}", store);
+        assert!(debug.contains("savings"));
+        assert!(debug.contains("<redacted>"));
+        assert!(!debug.contains(db_path.to_str().unwrap()));
+        assert!(!debug.contains("tpub"));
+        assert!(!debug.contains("tprv"));
+    }
+
+    /// A file backend whose next `sync_data` fails once armed
+    #[derive(Debug)]
+    struct FaultyBackend {
+        inner: redb::backends::FileBackend,
+        fail_next_sync: Arc<AtomicBool>,
+    }
+
+    impl redb::StorageBackend for FaultyBackend {
+        fn len(&self) -> Result<u64, std::io::Error> {
+            self.inner.len()
+        }
+
+        fn read(&self, offset: u64, len: usize) -> Result<Vec<u8>, std::io::Error> {
+            self.inner.read(offset, len)
+        }
+
+        fn set_len(&self, len: u64) -> Result<(), std::io::Error> {
+            self.inner.set_len(len)
+        }
+
+        fn sync_data(&self, eventual: bool) -> Result<(), std::io::Error> {
+            if self.fail_next_sync.swap(false, Ordering::SeqCst) {
+                return Err(std::io::Error::other("injected fault"));
+            }
+            self.inner.sync_data(eventual)
+        }
+
+        fn write(&self, offset: u64, data: &[u8]) -> Result<(), std::io::Error> {
+            self.inner.write(offset, data)
+        }
+    }
+
+    /// Open a store at `db_path` over a [`FaultyBackend`], returning its arming flag
+    fn open_faulty(db_path: PathBuf) -> (RedbStore, Arc<AtomicBool>) {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(&db_path)
+            .unwrap();
+        let fail_next_sync = Arc::new(AtomicBool::new(false));
+        let backend = FaultyBackend {
+            inner: redb::backends::FileBackend::new(file).unwrap(),
+            fail_next_sync: Arc::clone(&fail_next_sync),
+        };
+        let db = redb::Builder::new().create_with_backend(backend).unwrap();
+        let mut store = RedbStore::from_database(Arc::new(db)).unwrap();
+        store.path = Some(db_path);
+        (store, fail_next_sync)
+    }
+
+    #[test]
+    fn test_write_retry() {
+        let temp_dir = tempdir().unwrap();
+        let open_faulty = |name: &str| open_faulty(temp_dir.path().join(name));
+        let changeset = ChangeSet {
+            network: Some(Network::Testnet),
+            ..Default::default()
+        };
+
+        // Without retry the injected fault fails the persist
+        let (mut store, fail_next_sync) = open_faulty("no_retry.redb");
+        fail_next_sync.store(true, Ordering::SeqCst);
+        assert!(WalletPersister::persist(&mut store, &changeset)
+            .unwrap_err()
+            .is_transient());
+        drop(store);
+
+        // With retry the second attempt succeeds on the reopened file
+        let (mut store, fail_next_sync) = open_faulty("retry.redb");
+        store.set_write_retry(2, std::time::Duration::from_millis(10));
+        fail_next_sync.store(true, Ordering::SeqCst);
+        WalletPersister::persist(&mut store, &changeset).unwrap();
+        assert!(!fail_next_sync.load(Ordering::SeqCst));
+        assert_eq!(store.load_changeset().unwrap(), Some(changeset.clone()));
+
+        // Logic errors are never retried
+        store.set_max_changeset_bytes(Some(1));
+        let error = WalletPersister::persist(
+            &mut store,
+            &ChangeSet {
+                local_chain: [(0, Some(bitcoin::BlockHash::all_zeros()))].into(),
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(matches!(error, RedbError::TooLarge { .. }));
+        assert!(!error.is_transient());
+        drop(store);
+
+        // A failed reopen returns the original error and leaves the store closed
+        let (mut store, fail_next_sync) = open_faulty("reopen_fails.redb");
+        store.set_write_retry(2, std::time::Duration::from_millis(10));
+        let holder = Arc::new(std::sync::Mutex::new(None));
+        store.before_reopen = Some({
+            let (holder, path) = (Arc::clone(&holder), store.path.clone().unwrap());
+            Arc::new(move || *holder.lock().unwrap() = Some(Database::open(&path).unwrap()))
+        });
+        fail_next_sync.store(true, Ordering::SeqCst);
+        let error = WalletPersister::persist(&mut store, &changeset).unwrap_err();
+        assert!(error.is_transient(), "unexpected error: {}", error);
+        assert!(matches!(store.load_changeset(), Err(RedbError::Closed)));
+
+        // The next persist reopens the closed store once the file is free again
+        store.before_reopen = None;
+        drop(holder.lock().unwrap().take());
+        WalletPersister::persist(&mut store, &changeset).unwrap();
+        assert_eq!(store.load_changeset().unwrap(), Some(changeset));
+    }
+
+    // Offloaded async persists retry like the sync one, whichever runtime feature is on
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_async_write_retry() {
+        let temp_dir = tempdir().unwrap();
+        let changeset = large_changeset(3);
+
+        let (mut store, fail_next_sync) = open_faulty(temp_dir.path().join("async_retry.redb"));
+        store.set_write_retry(2, std::time::Duration::from_millis(10));
+        fail_next_sync.store(true, Ordering::SeqCst);
+        AsyncWalletPersister::persist(&mut store, &changeset)
+            .await
+            .unwrap();
+        assert!(!fail_next_sync.load(Ordering::SeqCst));
+        assert_eq!(
+            AsyncWalletPersister::initialize(&mut store).await.unwrap(),
+            changeset
+        );
+    }
+
+    #[test]
+    fn test_snapshot() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("snapshot.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let testnet = ChangeSet {
+            network: Some(Network::Testnet),
+            ..Default::default()
+        };
+        WalletPersister::persist(&mut store, &testnet).unwrap();
+        store.put_metadata("label", b"Before").unwrap();
+
+        let snapshot = store.snapshot().unwrap();
+
+        // Another handle writes while the snapshot is open
+        let mut writer = store.clone();
+        std::thread::spawn(move || {
+            let update = ChangeSet {
+                local_chain: [(0, Some(bitcoin::BlockHash::all_zeros()))].into(),
+                ..Default::default()
+            };
+            WalletPersister::persist(&mut writer, &update).unwrap();
+            writer.put_metadata("label", b"After").unwrap();
+        })
+        .join()
+        .unwrap();
+
+        assert_eq!(snapshot.changeset().unwrap(), Some(testnet));
+        assert_eq!(
+            snapshot.metadata("label").unwrap(),
+            Some(b"Before".to_vec())
+        );
+        assert_eq!(snapshot.version().unwrap(), 1);
+        drop(snapshot);
+
+        let fresh = store.snapshot().unwrap();
+        assert_eq!(fresh.version().unwrap(), 2);
+        assert_eq!(fresh.metadata("label").unwrap(), Some(b"After".to_vec()));
+    }
+
+    #[test]
+    fn test_database_accessor() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("accessor.redb");
+        const LABELS: TableDefinition<&str, &str> = TableDefinition::new("labels");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let write_txn = store.database().unwrap().begin_write().unwrap();
+        write_txn
+            .open_table(LABELS)
+            .unwrap()
+            .insert("tx1", "Rent")
+            .unwrap();
+        write_txn.commit().unwrap();
+
+        // The wallet is unaffected by the application's table
+        let changeset = ChangeSet {
+            network: Some(Network::Testnet),
+            ..Default::default()
+        };
+        WalletPersister::persist(&mut store, &changeset).unwrap();
+        assert_eq!(store.load_changeset().unwrap(), Some(changeset.clone()));
+
+        // A shared handle can't be taken out
+        let clone = store.clone();
+        let store = store.into_inner().unwrap_err();
+        drop(clone);
+
+        let db = store.into_inner().unwrap();
+        let read_txn = db.begin_read().unwrap();
+        let labels = read_txn.open_table(LABELS).unwrap();
+        assert_eq!(labels.get("tx1").unwrap().unwrap().value(), "Rent");
+        drop(labels);
+        drop(read_txn);
+
+        let mut store = RedbStore::from_database(Arc::new(db)).unwrap();
+        assert_eq!(WalletPersister::initialize(&mut store).unwrap(), changeset);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_subscribe() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("subscribe.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let mut events = store.subscribe();
+        // A dropped receiver doesn't hold up the writer
+        drop(store.subscribe());
+
+        let testnet = ChangeSet {
+            network: Some(Network::Testnet),
+            ..Default::default()
+        };
+        let update = ChangeSet {
+            local_chain: [(0, Some(bitcoin::BlockHash::all_zeros()))].into(),
+            ..Default::default()
+        };
+        WalletPersister::persist(&mut store, &testnet).unwrap();
+        // A persist through a clone is reported too
+        WalletPersister::persist(&mut store.clone(), &update).unwrap();
+
+        for version in [1, 2] {
+            assert_eq!(
+                events.try_recv().unwrap(),
+                PersistEvent {
+                    namespace: None,
+                    version,
+                }
+            );
+        }
+        assert!(events.try_recv().is_err());
+
+        // Each event carries the version its own commit wrote, even when another writer
+        // commits before it is sent
+        let other = std::sync::Mutex::new(Some(store.clone()));
+        store.on_persist(Box::new(move |_| {
+            if let Some(mut other) = other.lock().unwrap().take() {
+                let update = ChangeSet {
+                    local_chain: [(2, Some(bitcoin::BlockHash::all_zeros()))].into(),
+                    ..Default::default()
+                };
+                WalletPersister::persist(&mut other, &update).unwrap();
+            }
+        }));
+        let update = ChangeSet {
+            local_chain: [(1, Some(bitcoin::BlockHash::all_zeros()))].into(),
+            ..Default::default()
+        };
+        WalletPersister::persist(&mut store, &update).unwrap();
+        let versions: Vec<u64> = std::iter::from_fn(|| events.try_recv().ok())
+            .map(|event| event.version)
+            .collect();
+        assert_eq!(versions, [4, 3]);
+    }
+
+    #[test]
+    fn test_key_prefixes() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("prefixes.redb");
+
+        let prefixes = KeyPrefixes {
+            changeset_key: "bdk/changeset".to_string(),
+            metadata_prefix: "bdk/meta/".to_string(),
+            profile_prefix: "bdk/profile/".to_string(),
+            checkpoint_prefix: "bdk/checkpoint/".to_string(),
+        };
+        let changeset = ChangeSet {
+            network: Some(Network::Testnet),
+            ..Default::default()
+        };
+
+        {
+            let mut store = RedbStore::builder()
+                .key_prefixes(prefixes.clone())
+                .open_or_create(&db_path)
+                .unwrap();
+            WalletPersister::persist(&mut store, &changeset).unwrap();
+            store.put_metadata("label", b"Savings").unwrap();
+
+            // Nothing is written under the default keys
+            let keys: Vec<String> = store
+                .iter_entries()
+                .unwrap()
+                .into_iter()
+                .map(|(k, _)| k)
+                .collect();
+            assert!(keys.contains(&"bdk/changeset".to_string()));
+            assert!(keys.contains(&"bdk/meta/label".to_string()));
+            assert!(keys
+                .iter()
+                .all(|key| !key.starts_with(METADATA_PREFIX) && key != CHANGESET_KEY));
+        }
+
+        {
+            let mut store = RedbStore::builder()
+                .key_prefixes(prefixes.clone())
+                .open_or_create(&db_path)
+                .unwrap();
+            assert_eq!(WalletPersister::initialize(&mut store).unwrap(), changeset);
+            assert_eq!(
+                store.get_metadata("label").unwrap(),
+                Some(b"Savings".to_vec())
+            );
+        }
+
+        // Other prefixes see no wallet
+        let mut store = RedbStore::open(&db_path).unwrap();
+        assert!(!store.has_wallet().unwrap());
+        assert_eq!(
+            WalletPersister::initialize(&mut store).unwrap(),
+            ChangeSet::default()
+        );
+        drop(store);
+
+        let overlapping = KeyPrefixes {
+            metadata_prefix: "bdk/".to_string(),
+            ..prefixes
+        };
+        assert!(matches!(
+            RedbStore::builder()
+                .key_prefixes(overlapping)
+                .open_or_create(&db_path),
+            Err(RedbError::InvalidKeyPrefixes(_))
+        ));
+    }
+
+    #[test]
+    fn test_try_open() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("try_open.redb");
+
+        assert!(RedbStore::try_open(&db_path).unwrap().is_none());
+
+        drop(RedbStore::create(&db_path).unwrap());
+        assert!(RedbStore::try_open(&db_path).unwrap().is_some());
+
+        let garbage_path = temp_dir.path().join("garbage.redb");
+        fs::write(&garbage_path, vec![0xAB; 4096]).unwrap();
+        assert!(RedbStore::try_open(&garbage_path).is_err());
+    }
+
+    #[test]
+    fn test_has_data_flag() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("has_data.redb");
+        let changeset = ChangeSet {
+            network: Some(Network::Testnet),
+            ..Default::default()
+        };
+        let flag = |store: &RedbStore| {
+            store
+                .iter_entries()
+                .unwrap()
+                .iter()
+                .any(|(key, _)| key == HAS_DATA_KEY)
+        };
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        assert!(!flag(&store));
+        assert!(!store.has_wallet().unwrap());
+
+        WalletPersister::persist(&mut store, &changeset).unwrap();
+        assert!(flag(&store));
+        assert!(store.has_wallet().unwrap());
+        assert!(!store.is_empty().unwrap());
+        drop(store);
+
+        let mut store = RedbStore::open(&db_path).unwrap();
+        assert!(store.has_wallet().unwrap());
+
+        store.clear().unwrap();
+        assert!(!flag(&store));
+        assert!(!store.has_wallet().unwrap());
+        assert!(store.is_empty().unwrap());
+        drop(store);
+
+        let store = RedbStore::open(&db_path).unwrap();
+        assert!(!store.has_wallet().unwrap());
+
+        // Files written before the flag existed are still detected
+        let write_txn = store.db().unwrap().begin_write().unwrap();
+        {
+            let mut table = write_txn.open_table(store.wallet_table()).unwrap();
+            let bytes = serde_json::to_vec(&changeset).unwrap();
+            table.insert(CHANGESET_KEY, bytes.as_slice()).unwrap();
+        }
+        write_txn.commit().unwrap();
+        assert!(store.has_wallet().unwrap());
+
+        // Profiles keep their own flag
+        let mut store = store;
+        store.set_active_profile("watch_only").unwrap();
+        assert!(!store.has_wallet().unwrap());
+        WalletPersister::persist(&mut store, &changeset).unwrap();
+        assert!(store.has_wallet().unwrap());
+        store.clear().unwrap();
+        assert!(!store.has_wallet().unwrap());
+    }
+
+    #[test]
+    fn test_codec_round_trip() {
+        let changeset = ChangeSet {
+            network: Some(Network::Signet),
+            local_chain: [(0, Some(bitcoin::BlockHash::all_zeros()))].into(),
+            ..Default::default()
+        };
+        let limits = DeserializeLimits::default();
+
+        for pretty in [false, true] {
+            let bytes = codec::encode_changeset(&changeset, pretty).unwrap();
+            assert_eq!(codec::decode_changeset(&bytes, &limits).unwrap(), changeset);
+        }
+
+        let compact = codec::encode_changeset(&changeset, false).unwrap();
+        let tight = DeserializeLimits {
+            max_bytes: compact.len() - 1,
+            ..limits
+        };
+        assert!(codec::decode_changeset(&compact, &tight).is_err());
+        assert!(codec::decode_changeset(b"not json", &limits).is_err());
+    }
+
+    #[test]
+    fn test_persist_dedup() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("dedup.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let commits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&commits);
+        store.on_persist(Box::new(move |_| {
+            counter.fetch_add(1, Ordering::SeqCst);
+        }));
+
+        let changeset = ChangeSet {
+            network: Some(Network::Testnet),
+            local_chain: [(0, Some(bitcoin::BlockHash::all_zeros()))].into(),
+            ..Default::default()
+        };
+        assert!(store.persist_changeset(&changeset).unwrap());
+        let stored_hash = store
+            .iter_entries()
+            .unwrap()
+            .into_iter()
+            .find(|(key, _)| key == CONTENT_HASH_KEY)
+            .map(|(_, value)| value)
+            .unwrap();
+        let segments_hash = {
+            let read_txn = store.db().unwrap().begin_read().unwrap();
+            let table = read_txn.open_table(store.wallet_table()).unwrap();
+            let hash = StoredChangeSet::read(&table, CHANGESET_KEY)
+                .unwrap()
+                .unwrap()
+                .content_hash();
+            hash
+        };
+        assert_eq!(stored_hash, segments_hash.as_byte_array().to_vec());
+
+        // Re-applying the same state writes nothing
+        assert!(!store.persist_changeset(&changeset).unwrap());
+        assert_eq!(commits.load(Ordering::SeqCst), 1);
+        assert_eq!(store.version().unwrap(), 1);
+
+        // A real change is still written
+        let update = ChangeSet {
+            local_chain: [(1, Some(bitcoin::BlockHash::all_zeros()))].into(),
+            ..Default::default()
+        };
+        assert!(store.persist_changeset(&update).unwrap());
+        assert_eq!(commits.load(Ordering::SeqCst), 2);
+        assert_eq!(store.version().unwrap(), 2);
+
+        // A write through the database leaves the hash stale; the stored bytes still count
+        let network_only = ChangeSet {
+            network: Some(Network::Testnet),
+            ..Default::default()
+        };
+        let write_txn = store.database().unwrap().begin_write().unwrap();
+        {
+            let mut table = write_txn.open_table(store.wallet_table()).unwrap();
+            let mut bytes = Vec::new();
+            codec::encode_segment_to(&network_only, 1, false, &mut bytes).unwrap();
+            table
+                .insert("wallet_changeset::local_chain", bytes.as_slice())
+                .unwrap();
+        }
+        write_txn.commit().unwrap();
+        let mut merged = changeset.clone();
+        merged.merge(update);
+        assert!(store.persist_changeset(&merged).unwrap());
+        assert_eq!(store.load_changeset().unwrap(), Some(merged));
+    }
+
+    #[test]
+    fn test_assert_network() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("assert_network.redb");
+        let mut store = RedbStore::create(&db_path).unwrap();
+
+        // Nothing persisted yet
+        store.assert_network(Network::Bitcoin).unwrap();
+
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        PersistedWallet::create(&mut store, create_params).unwrap();
+
+        store.assert_network(Network::Testnet).unwrap();
+        assert!(matches!(
+            store.assert_network(Network::Bitcoin),
+            Err(RedbError::NetworkMismatch {
+                expected: Network::Bitcoin,
+                found: Network::Testnet,
+            })
+        ));
+
+        // Only the descriptors segment is deserialized, not the transaction graph
+        let write_txn = store.database().unwrap().begin_write().unwrap();
+        {
+            let mut table = write_txn.open_table(store.wallet_table()).unwrap();
+            table
+                .insert("wallet_changeset::tx_graph", b"not json".as_slice())
+                .unwrap();
+        }
+        write_txn.commit().unwrap();
+        assert!(store.get_changeset().is_err());
+        store.assert_network(Network::Testnet).unwrap();
+
+        // The network recorded at creation counts before anything is persisted
+        let store =
+            RedbStore::create_for_network(temp_dir.path().join("signet.redb"), Network::Signet)
+                .unwrap();
+        assert!(matches!(
+            store.assert_network(Network::Bitcoin),
+            Err(RedbError::NetworkMismatch {
+                expected: Network::Bitcoin,
+                found: Network::Signet,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_open_read_only_bytes() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("bytes_source.redb");
+        let backup_path = temp_dir.path().join("bytes_backup.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        let mut wallet = PersistedWallet::create(&mut store, create_params).unwrap();
+        let address = wallet.reveal_next_address(KeychainKind::External);
+        wallet.persist(&mut store).unwrap();
+        store.backup_to(&backup_path).unwrap();
+
+        let bytes = fs::read(&backup_path).unwrap();
+        let mut store = RedbStore::open_read_only_bytes(bytes).unwrap();
+        let loaded = PersistedWallet::load(&mut store, LoadParams::default())
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            loaded.peek_address(KeychainKind::External, address.index),
+            address
+        );
+
+        assert!(matches!(
+            WalletPersister::persist(&mut store, &large_changeset(1)),
+            Err(RedbError::ReadOnly)
+        ));
+        assert!(RedbStore::open_read_only_bytes(Vec::new()).is_err());
+    }
+
+    #[test]
+    fn test_compact_on_drop() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("compact_on_drop.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(5_000)).unwrap();
+        store.clear().unwrap();
+        let grown_size = store.file_size().unwrap();
+
+        store.set_compact_on_drop(true);
+
+        // Dropping a clone leaves the file alone, even once it is the last handle
+        let clone = store.clone();
+        drop(store);
+        drop(clone);
+        assert_eq!(fs::metadata(&db_path).unwrap().len(), grown_size);
+
+        let mut store = RedbStore::open(&db_path).unwrap();
+        store.set_compact_on_drop(true);
+        drop(store);
+        assert!(fs::metadata(&db_path).unwrap().len() < grown_size);
+
+        let store = RedbStore::open(&db_path).unwrap();
+        assert!(store.load_changeset().unwrap().is_none());
+    }
+
+    #[test]
+    fn test_load_indexer_only() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("indexer_only.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        assert!(store.load_indexer_only().unwrap().is_none());
+
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        let mut wallet = PersistedWallet::create(&mut store, create_params).unwrap();
+        let _ = wallet.reveal_addresses_to(KeychainKind::External, 4);
+        wallet.persist(&mut store).unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(1)).unwrap();
+
+        let stored = store.load_changeset().unwrap().unwrap();
+        assert_eq!(store.load_indexer_only().unwrap().unwrap(), stored.indexer);
+
+        // Make every other segment unreadable: the indexer is parsed on its own
+        let write_txn = store.db().unwrap().begin_write().unwrap();
+        {
+            let mut table = write_txn.open_table(store.wallet_table()).unwrap();
+            for key in segment_keys(CHANGESET_KEY).iter().take(3) {
+                table.insert(key.as_str(), b"{\"trunc".as_slice()).unwrap();
+            }
+        }
+        write_txn.commit().unwrap();
+        assert!(store.load_changeset().is_err());
+        let indexer = store.load_indexer_only().unwrap().unwrap();
+        assert_eq!(indexer, stored.indexer);
+        assert_eq!(indexer.last_revealed.values().copied().max(), Some(4));
+
+        // Unfolded log entries are merged in
+        #[cfg(feature = "wal")]
+        {
+            let descriptor = stored.descriptor.as_ref().unwrap();
+            let mut update = ChangeSet::default();
+            update
+                .indexer
+                .last_revealed
+                .insert(descriptor.descriptor_id(), 7);
+            store.append_log(&update).unwrap();
+            let indexer = store.load_indexer_only().unwrap().unwrap();
+            assert_eq!(indexer.last_revealed[&descriptor.descriptor_id()], 7);
+        }
+    }
+
+    #[test]
+    fn test_changeset_segments() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("segments.redb");
+
+        // A changeset stored whole, as before segmentation, still loads
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let changeset = large_changeset(2);
+        let write_txn = store.db().unwrap().begin_write().unwrap();
+        {
+            let mut table = write_txn.open_table(store.wallet_table()).unwrap();
+            let bytes = codec::encode_changeset(&changeset, false).unwrap();
+            table.insert(CHANGESET_KEY, bytes.as_slice()).unwrap();
+        }
+        write_txn.commit().unwrap();
+        assert_eq!(store.load_changeset().unwrap(), Some(changeset.clone()));
+
+        // The next persist splits it into segments, which join into the same JSON
+        let update = large_changeset(3);
+        WalletPersister::persist(&mut store, &update).unwrap();
+        let mut merged = changeset;
+        merged.merge(update);
+        let entries: BTreeMap<String, Vec<u8>> =
+            store.iter_entries().unwrap().into_iter().collect();
+        for key in segment_keys(CHANGESET_KEY) {
+            assert!(entries.contains_key(&key), "missing segment {}", key);
+        }
+        assert_eq!(
+            store.get_changeset_bytes().unwrap().unwrap(),
+            codec::encode_changeset(&merged, false).unwrap()
+        );
+        assert_eq!(WalletPersister::initialize(&mut store).unwrap(), merged);
+        assert_eq!(store.stats().unwrap().changeset_format_version, 2);
+
+        // A chain update leaves the other segments as they were
+        let update = ChangeSet {
+            local_chain: [(0, Some(bitcoin::BlockHash::all_zeros()))].into(),
+            ..Default::default()
+        };
+        WalletPersister::persist(&mut store, &update).unwrap();
+        let after: BTreeMap<String, Vec<u8>> = store.iter_entries().unwrap().into_iter().collect();
+        let [descriptors, local_chain, tx_graph, indexer] = segment_keys(CHANGESET_KEY);
+        assert_ne!(after[&local_chain], entries[&local_chain]);
+        for key in [descriptors, tx_graph, indexer] {
+            assert_eq!(after[&key], entries[&key]);
+        }
+        assert!(store.health_check(None).unwrap().is_healthy());
+    }
+
+    #[test]
+    fn test_create_validated() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("validated.redb");
+
+        let result = RedbStore::create_validated(
+            &db_path,
+            "invalid",
+            TEST_CHANGE_DESCRIPTOR,
+            Network::Testnet,
+        );
+        assert!(matches!(result, Err(RedbError::InvalidDescriptor(_))));
+        assert!(!db_path.exists());
+
+        // Testnet keys can't be used on mainnet
+        let result = RedbStore::create_validated(
+            &db_path,
+            TEST_DESCRIPTOR,
+            TEST_CHANGE_DESCRIPTOR,
+            Network::Bitcoin,
+        );
+        assert!(matches!(result, Err(RedbError::InvalidDescriptor(_))));
+        assert!(!db_path.exists());
+
+        let mut store = RedbStore::create_validated(
+            &db_path,
+            TEST_DESCRIPTOR,
+            TEST_CHANGE_DESCRIPTOR,
+            Network::Testnet,
+        )
+        .unwrap();
+        assert_eq!(store.stored_network().unwrap(), Some(Network::Testnet));
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        PersistedWallet::create(&mut store, create_params).unwrap();
+        assert!(WalletPersister::initialize(&mut store).is_ok());
+    }
+
+    #[test]
+    fn test_release_cache() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("release_cache.redb");
+
+        let mut store = RedbStore::builder()
+            .cache_size(1024 * 1024)
+            .open_or_create(&db_path)
+            .unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(100)).unwrap();
+        let stored = store.load_changeset().unwrap().unwrap();
+
+        assert!(store.release_cache().unwrap());
+        assert_eq!(store.load_changeset().unwrap().unwrap(), stored);
+
+        // The store keeps working after the reopen
+        WalletPersister::persist(&mut store, &large_changeset(101)).unwrap();
+        assert_eq!(
+            WalletPersister::initialize(&mut store)
+                .unwrap()
+                .tx_graph
+                .txs
+                .len(),
+            101
+        );
+
+        // A shared handle can't be reopened
+        let clone = store.clone();
+        assert!(matches!(
+            store.release_cache(),
+            Err(RedbError::SharedDatabase)
+        ));
+        drop(clone);
+
+        let mut in_memory =
+            RedbStore::create_with_backend(redb::backends::InMemoryBackend::new()).unwrap();
+        assert!(!in_memory.release_cache().unwrap());
+
+        // A file that can't be opened leaves the store in place
+        let moved_path = temp_dir.path().join("moved.redb");
+        fs::rename(&db_path, &moved_path).unwrap();
+        assert!(store.release_cache().is_err());
+        WalletPersister::persist(&mut store, &large_changeset(102)).unwrap();
+        drop(store);
+        let mut store = RedbStore::open(&moved_path).unwrap();
+        assert_eq!(
+            WalletPersister::initialize(&mut store)
+                .unwrap()
+                .tx_graph
+                .txs
+                .len(),
+            102
+        );
+        drop(store);
+
+        // Stores counting reads are released too, and keep counting
+        let mut counted = RedbStore::builder()
+            .track_reads(true)
+            .open_or_create(&moved_path)
+            .unwrap();
+        assert!(counted.release_cache().unwrap());
+        let before = counted.cache_stats().unwrap().physical_reads.unwrap();
+        counted.load_changeset().unwrap().unwrap();
+        assert!(counted.cache_stats().unwrap().physical_reads.unwrap() > before);
+    }
+
+    /// A subscriber recording the fields of every event
+    #[cfg(feature = "tracing")]
+    struct CaptureSubscriber {
+        events: Arc<std::sync::Mutex<Vec<BTreeMap<String, String>>>>,
+    }
+
+    #[cfg(feature = "tracing")]
+    impl tracing::Subscriber for CaptureSubscriber {
+        fn enabled(&self, _: &tracing::Metadata<'_>) -> bool {
+            true
+        }
+
+        fn new_span(&self, _: &tracing::span::Attributes<'_>) -> tracing::span::Id {
+            tracing::span::Id::from_u64(1)
+        }
+
+        fn record(&self, _: &tracing::span::Id, _: &tracing::span::Record<'_>) {}
+
+        fn record_follows_from(&self, _: &tracing::span::Id, _: &tracing::span::Id) {}
+
+        fn event(&self, event: &tracing::Event<'_>) {
+            struct Visitor(BTreeMap<String, String>);
+            impl tracing::field::Visit for Visitor {
+                fn record_debug(
+                    &mut self,
+                    field: &tracing::field::Field,
+                    value: &dyn std::fmt::Debug,
+                ) {
+                    self.0
+                        .insert(field.name().to_string(), format!("{:?}", value));
+                }
+            }
+
+            let mut visitor = Visitor(BTreeMap::new());
+            event.record(&mut visitor);
+            self.events.lock().unwrap().push(visitor.0);
+        }
+
+        fn enter(&self, _: &tracing::span::Id) {}
+
+        fn exit(&self, _: &tracing::span::Id) {}
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_tracing_events() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("tracing.redb");
+        let mut store = RedbStore::create(&db_path).unwrap();
+
+        let events = Arc::new(std::sync::Mutex::new(Vec::new()));
+        let subscriber = CaptureSubscriber {
+            events: Arc::clone(&events),
+        };
+        tracing::subscriber::with_default(subscriber, || {
+            WalletPersister::persist(&mut store, &large_changeset(3)).unwrap();
+        });
+
+        let size = store.get_changeset_bytes().unwrap().unwrap().len();
+        let events = events.lock().unwrap();
+        let commit = events
+            .iter()
+            .find(|fields| fields["message"] == "committed changeset")
+            .unwrap();
+        assert_eq!(commit["bytes"], size.to_string());
+    }
+
+    #[test]
+    fn test_unsupported_changeset_version() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("changeset_version.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(1)).unwrap();
+        assert!(WalletPersister::initialize(&mut store).is_ok());
+
+        // Pretend a newer version wrote the changeset
+        let write_txn = store.db().unwrap().begin_write().unwrap();
+        {
+            let mut table = write_txn.open_table(store.wallet_table()).unwrap();
+            table
+                .insert(
+                    CHANGESET_VERSION_KEY,
+                    (CHANGESET_FORMAT_VERSION + 1).to_be_bytes().as_slice(),
+                )
+                .unwrap();
+        }
+        write_txn.commit().unwrap();
+
+        let expected = |result: Result<_, RedbError>| {
+            matches!(
+                result,
+                Err(RedbError::UnsupportedChangeSetVersion {
+                    found: 3,
+                    supported: CHANGESET_FORMAT_VERSION,
+                })
+            )
+        };
+        assert!(expected(
+            WalletPersister::initialize(&mut store).map(|_| ())
+        ));
+        assert!(expected(store.initialize_with_progress(|_| {}).map(|_| ())));
+        assert!(expected(
+            WalletPersister::persist(&mut store, &large_changeset(2)).map(|_| ())
+        ));
+    }
+
+    #[test]
+    fn test_on_first_create() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("first_create.redb");
+        let runs = AtomicUsize::new(0);
+
+        let mut store = RedbStore::open_or_create(&db_path).unwrap();
+        assert!(store.on_first_create(|store| {
+            runs.fetch_add(1, Ordering::SeqCst);
+            store.put_metadata("label", b"Savings").unwrap();
+        }));
+        // Only once per store
+        assert!(!store.on_first_create(|_| {
+            runs.fetch_add(1, Ordering::SeqCst);
+        }));
+        drop(store);
+
+        let mut store = RedbStore::open_or_create(&db_path).unwrap();
+        assert!(!store.on_first_create(|_| {
+            runs.fetch_add(1, Ordering::SeqCst);
+        }));
+        assert_eq!(runs.load(Ordering::SeqCst), 1);
+        assert_eq!(
+            store.get_metadata("label").unwrap(),
+            Some(b"Savings".to_vec())
+        );
+        drop(store);
+
+        let mut store = RedbStore::builder().open_or_create(&db_path).unwrap();
+        assert!(!store.on_first_create(|_| {}));
+    }
+
+    #[test]
+    fn test_create_sized() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("sized.redb");
+
+        assert_eq!(RedbStore::estimate_cache_size(0), 4 * 1024 * 1024);
+        assert_eq!(RedbStore::estimate_cache_size(10_000), 20 * 1024 * 1000);
+        assert_eq!(
+            RedbStore::estimate_cache_size(usize::MAX),
+            1024 * 1024 * 1024
+        );
+
+        let mut store = RedbStore::create_sized(&db_path, 100_000).unwrap();
+        assert_eq!(
+            store.cache_size,
+            Some(RedbStore::estimate_cache_size(100_000))
+        );
+
+        WalletPersister::persist(&mut store, &large_changeset(50)).unwrap();
+        assert_eq!(
+            WalletPersister::initialize(&mut store)
+                .unwrap()
+                .tx_graph
+                .txs
+                .len(),
+            50
+        );
+    }
+
+    #[test]
+    fn test_health_check() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("health.redb");
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let report = store.health_check(Some(Network::Testnet)).unwrap();
+        assert!(report.is_healthy());
+        assert_eq!(report.checksum_matches, None);
+
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        PersistedWallet::create(&mut store, create_params).unwrap();
+        let report = store.health_check(Some(Network::Testnet)).unwrap();
+        assert_eq!(
+            report,
+            HealthReport {
+                structure_ok: true,
+                format_supported: true,
+                checksum_matches: Some(true),
+                changeset_readable: true,
+                network_matches: Some(true),
+                problems: Vec::new(),
+            }
+        );
+
+        let report = store.health_check(Some(Network::Bitcoin)).unwrap();
+        assert_eq!(report.network_matches, Some(false));
+        assert!(!report.is_healthy());
+
+        // Corrupt the changeset behind the store's back
+        let version = store.version().unwrap();
+        let write_txn = store.db().unwrap().begin_write().unwrap();
+        {
+            let mut table = write_txn.open_table(store.wallet_table()).unwrap();
+            table.insert(CHANGESET_KEY, b"{\"trunc".as_slice()).unwrap();
+        }
+        write_txn.commit().unwrap();
+
+        let report = store.health_check(None).unwrap();
+        assert!(report.structure_ok);
+        assert!(!report.changeset_readable);
+        assert_eq!(report.checksum_matches, Some(false));
+        assert_eq!(report.network_matches, None);
+        assert_eq!(report.problems.len(), 2);
+
+        // Nothing was written
+        assert_eq!(store.version().unwrap(), version);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_open_or_create_exclusive() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("exclusive.redb");
+        let barrier = std::sync::Barrier::new(2);
+
+        let results: Vec<_> = std::thread::scope(|scope| {
+            let attempts: Vec<_> = (0..2)
+                .map(|_| {
+                    scope.spawn(|| {
+                        barrier.wait();
+                        RedbStore::open_or_create_exclusive(&db_path)
+                    })
+                })
+                .collect();
+            attempts.into_iter().map(|a| a.join().unwrap()).collect()
+        });
+
+        assert_eq!(results.iter().filter(|r| r.is_ok()).count(), 1);
+        assert!(results
+            .iter()
+            .any(|r| matches!(r, Err(RedbError::AlreadyLocked))));
+
+        // The lock goes away with the store
+        drop(results);
+        let store = RedbStore::open_or_create_exclusive(&db_path).unwrap();
+        let clone = store.clone();
+        drop(store);
+        assert!(matches!(
+            RedbStore::open_or_create_exclusive(&db_path),
+            Err(RedbError::AlreadyLocked)
+        ));
+        drop(clone);
+        RedbStore::open_or_create_exclusive(&db_path).unwrap();
+    }
+
+    #[test]
+    fn test_diff() {
+        let temp_dir = tempdir().unwrap();
+        let source_path = temp_dir.path().join("diff_source.redb");
+        let db_path = temp_dir.path().join("diff.redb");
+
+        let candidate = {
+            let mut store = RedbStore::create(&source_path).unwrap();
+            let create_params = CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR)
+                .network(Network::Testnet);
+            let mut wallet = PersistedWallet::create(&mut store, create_params).unwrap();
+            let _ = wallet.reveal_addresses_to(KeychainKind::External, 2);
+            wallet.persist(&mut store).unwrap();
+            WalletPersister::persist(&mut store, &large_changeset(2)).unwrap();
+            store.load_changeset().unwrap().unwrap()
+        };
+
+        let mut store = RedbStore::create(&db_path).unwrap();
+        let diff = store.diff(&candidate).unwrap();
+        assert!(diff.sets_descriptors);
+        assert!(diff.sets_network);
+        assert_eq!(diff.revealed.values().copied().collect::<Vec<_>>(), vec![2]);
+        assert_eq!(diff.new_txs.len(), 2);
+        assert_eq!(diff.blocks.len(), 1);
+        assert_eq!(diff.tip_change, Some((None, Some(0))));
+
+        // Nothing was written
+        assert!(store.load_changeset().unwrap().is_none());
+
+        WalletPersister::persist(&mut store, &candidate).unwrap();
+        assert!(store.diff(&candidate).unwrap().is_empty());
+        let diff = store.diff(&large_changeset(3)).unwrap();
+        assert_eq!(diff.new_txs.len(), 1);
+        assert_eq!(diff.tip_change, None);
+    }
+
+    /// A backend counting how often redb syncs it
+    #[derive(Debug)]
+    struct SyncCountingBackend {
+        inner: redb::backends::InMemoryBackend,
+        syncs: Arc<AtomicUsize>,
+    }
+
+    impl redb::StorageBackend for SyncCountingBackend {
+        fn len(&self) -> Result<u64, std::io::Error> {
+            self.inner.len()
+        }
+
+        fn read(&self, offset: u64, len: usize) -> Result<Vec<u8>, std::io::Error> {
+            self.inner.read(offset, len)
+        }
+
+        fn set_len(&self, len: u64) -> Result<(), std::io::Error> {
+            self.inner.set_len(len)
         }
-    }
-}
 
-impl From<redb::DatabaseError> for RedbError {
-    fn from(e: redb::DatabaseError) -> Self {
-        Self::Database(e.into())
-    }
-}
+        fn sync_data(&self, eventual: bool) -> Result<(), std::io::Error> {
+            self.syncs.fetch_add(1, Ordering::SeqCst);
+            self.inner.sync_data(eventual)
+        }
 
-impl From<redb::StorageError> for RedbError {
-    fn from(e: redb::StorageError) -> Self {
-        Self::Database(e.into())
+        fn write(&self, offset: u64, data: &[u8]) -> Result<(), std::io::Error> {
+            self.inner.write(offset, data)
+        }
     }
-}
 
-impl From<redb::Error> for RedbError {
-    fn from(e: redb::Error) -> Self {
-        Self::Database(e)
-    }
-}
+    #[test]
+    fn test_flush_policy() {
+        let syncs = Arc::new(AtomicUsize::new(0));
+        let backend = SyncCountingBackend {
+            inner: redb::backends::InMemoryBackend::new(),
+            syncs: Arc::clone(&syncs),
+        };
+        let mut store = RedbStore::create_with_backend(backend).unwrap();
 
-impl From<serde_json::Error> for RedbError {
-    fn from(e: serde_json::Error) -> Self {
-        Self::Serialization(e)
-    }
-}
+        store.set_flush_policy(FlushPolicy::EveryN(3));
+        let before = syncs.load(Ordering::SeqCst);
+        for i in 1..=2 {
+            WalletPersister::persist(&mut store, &large_changeset(i)).unwrap();
+        }
+        assert_eq!(syncs.load(Ordering::SeqCst), before);
 
-impl From<std::io::Error> for RedbError {
-    fn from(e: std::io::Error) -> Self {
-        Self::Io(e)
-    }
-}
+        // The third persist is the barrier
+        WalletPersister::persist(&mut store, &large_changeset(3)).unwrap();
+        let after_barrier = syncs.load(Ordering::SeqCst);
+        assert!(after_barrier > before);
+        assert_eq!(
+            WalletPersister::initialize(&mut store)
+                .unwrap()
+                .tx_graph
+                .txs
+                .len(),
+            3
+        );
 
-impl From<redb::CommitError> for RedbError {
-    fn from(e: redb::CommitError) -> Self {
-        Self::Commit(e)
-    }
-}
+        // The count starts over after the barrier
+        WalletPersister::persist(&mut store, &large_changeset(4)).unwrap();
+        assert_eq!(syncs.load(Ordering::SeqCst), after_barrier);
 
-impl From<redb::TableError> for RedbError {
-    fn from(e: redb::TableError) -> Self {
-        Self::Table(e)
+        // Persists that write nothing don't count
+        for _ in 0..2 {
+            WalletPersister::persist(&mut store, &large_changeset(4)).unwrap();
+        }
+        WalletPersister::persist(&mut store, &large_changeset(5)).unwrap();
+        assert_eq!(syncs.load(Ordering::SeqCst), after_barrier);
+
+        // persist_if_version follows the same policy
+        let version = store.version().unwrap();
+        store
+            .persist_if_version(version, &large_changeset(6))
+            .unwrap();
+        let after_cas = syncs.load(Ordering::SeqCst);
+        assert!(after_cas > after_barrier);
+
+        store.set_flush_policy(FlushPolicy::Never);
+        WalletPersister::persist(&mut store, &large_changeset(7)).unwrap();
+        assert_eq!(syncs.load(Ordering::SeqCst), after_cas);
+
+        store.set_flush_policy(FlushPolicy::Always);
+        WalletPersister::persist(&mut store, &large_changeset(8)).unwrap();
+        assert!(syncs.load(Ordering::SeqCst) > after_cas);
     }
-}
 
-impl From<redb::TransactionError> for RedbError {
-    fn from(e: redb::TransactionError) -> Self {
-        Self::Transaction(e)
+    #[test]
+    fn test_read_during_slow_write() {
+        let temp_dir = tempdir().unwrap();
+        let mut writer = RedbStore::create(temp_dir.path().join("wallet.redb")).unwrap();
+        WalletPersister::persist(&mut writer, &large_changeset(1)).unwrap();
+        let mut reader = writer.clone();
+
+        // Hold the next persist open until the reader is done
+        let writing = Arc::new(std::sync::Barrier::new(2));
+        let release = Arc::new(std::sync::Barrier::new(2));
+        writer.before_commit = Some({
+            let writing = Arc::clone(&writing);
+            let release = Arc::clone(&release);
+            Arc::new(move || {
+                writing.wait();
+                release.wait();
+            })
+        });
+        let write = std::thread::spawn(move || {
+            WalletPersister::persist(&mut writer, &large_changeset(2)).unwrap();
+        });
+        writing.wait();
+
+        // The read neither waits for the write nor sees it
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let read = {
+            let mut reader = reader.clone();
+            std::thread::spawn(move || {
+                sender
+                    .send(WalletPersister::initialize(&mut reader).unwrap())
+                    .unwrap();
+            })
+        };
+        let changeset = receiver
+            .recv_timeout(std::time::Duration::from_secs(10))
+            .expect("read waited for the write");
+        assert_eq!(changeset.tx_graph.txs.len(), 1);
+        read.join().unwrap();
+
+        release.wait();
+        write.join().unwrap();
+        let changeset = WalletPersister::initialize(&mut reader).unwrap();
+        assert_eq!(changeset.tx_graph.txs.len(), 2);
     }
-}
 
-/// Type alias for a pinned, boxed future that can be returned by async methods
-type FutureResult<'a, T, E> = Pin<Box<dyn Future<Output = Result<T, E>> + Send + 'a>>;
+    #[test]
+    fn test_open_latest() {
+        let temp_dir = tempdir().unwrap();
+        let good_path = temp_dir.path().join("wallet.redb.bak1");
+        let corrupt_path = temp_dir.path().join("wallet.redb");
 
-impl WalletPersister for RedbStore {
-    type Error = RedbError;
+        let mut store = RedbStore::create(&good_path).unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(3)).unwrap();
+        drop(store);
+        fs::write(&corrupt_path, b"not a redb file at all").unwrap();
+
+        // Make the corrupt file the newest
+        let now = std::time::SystemTime::now();
+        let set_modified = |path: &Path, time| {
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(time)
+                .unwrap();
+        };
+        set_modified(&good_path, now - std::time::Duration::from_secs(3600));
+        set_modified(&corrupt_path, now);
 
-    /// Initialize the wallet persister by loading the stored changeset
-    ///
-    /// This method is called by BDK when a wallet is being loaded.
-    /// It retrieves the stored wallet changeset from the database or returns
-    /// an empty changeset if none exists.
-    ///
-    /// # Returns
-    ///
-    /// - The stored wallet changeset, or an empty changeset if none exists
-    /// - An error if database access or deserialization fails
-    ///
-    fn initialize(persister: &mut Self) -> Result<ChangeSet, Self::Error> {
-        // Get changeset or return empty if none exists
-        persister.get_changeset().map(|opt| opt.unwrap_or_default())
+        let (mut store, path) =
+            RedbStore::open_latest(&[good_path.as_path(), corrupt_path.as_path()]).unwrap();
+        assert_eq!(path, good_path);
+        let changeset = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(changeset.tx_graph.txs.len(), 3);
+        drop(store);
+
+        // A probe reads the file in place and keeps every write in memory
+        let file_bytes = fs::read(&good_path).unwrap();
+        let mut probe =
+            RedbStore::open_with_backend(ProbeBackend::open(&good_path).unwrap()).unwrap();
+        WalletPersister::persist(&mut probe, &large_changeset(5)).unwrap();
+        assert_eq!(
+            WalletPersister::initialize(&mut probe)
+                .unwrap()
+                .tx_graph
+                .txs
+                .len(),
+            5
+        );
+        drop(probe);
+        assert_eq!(fs::read(&good_path).unwrap(), file_bytes);
+
+        // Without an intact candidate, the last error is returned, and probing left the
+        // candidate as it was
+        assert!(RedbStore::open_latest(&[corrupt_path.as_path()]).is_err());
+        assert_eq!(fs::read(&corrupt_path).unwrap(), b"not a redb file at all");
+        assert!(matches!(
+            RedbStore::open_latest(&[]),
+            Err(RedbError::Io(e)) if e.kind() == std::io::ErrorKind::InvalidInput
+        ));
     }
 
-    /// Persist a wallet changeset to the database
-    ///
-    /// This method is called by BDK when wallet changes need to be saved.
-    /// It merges the new changeset with any existing one and stores the result.
-    ///
-    /// # Arguments
-    ///
-    /// * `changeset` - The wallet changeset to persist
-    ///
-    /// # Returns
-    ///
-    /// - `Ok(())` if the changeset was successfully stored
-    /// - An error if serialization or database access fails
-    ///
-    fn persist(persister: &mut Self, changeset: &ChangeSet) -> Result<(), Self::Error> {
-        // Get existing changeset if any
-        let existing_changeset = persister.get_changeset()?;
+    #[test]
+    fn test_stats() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("stats.redb");
+        let mut store = RedbStore::create(&db_path).unwrap();
 
-        // Merge with existing or use the new one
-        let final_changeset = match existing_changeset {
-            Some(mut existing) => {
-                existing.merge(changeset.clone());
-                existing
-            }
-            None => changeset.clone(),
-        };
+        let stats = store.stats().unwrap();
+        assert_eq!(stats.changeset_bytes, 0);
+        assert_eq!(stats.namespace_count, 0);
+        assert_eq!(stats.last_persisted, None);
+
+        WalletPersister::persist(&mut store, &large_changeset(5)).unwrap();
+        let mut other =
+            RedbStore::from_database_with_namespace(Arc::clone(&store.db().unwrap()), "other")
+                .unwrap();
+        WalletPersister::persist(&mut other, &large_changeset(2)).unwrap();
 
-        // Store the merged changeset
-        persister.store_changeset(&final_changeset)
+        let stats = store.stats().unwrap();
+        assert_eq!(stats.file_size, fs::metadata(&db_path).unwrap().len());
+        assert!(stats.file_size > 0);
+        assert_eq!(
+            stats.changeset_bytes,
+            store.get_changeset_bytes().unwrap().unwrap().len()
+        );
+        assert!(stats.entry_count > 2);
+        assert_eq!(stats.namespace_count, 2);
+        assert_eq!(stats.changeset_format_version, CHANGESET_FORMAT_VERSION);
+        assert_eq!(stats.last_persisted, store.last_persisted_at().unwrap());
+        assert!(stats.last_persisted.is_some());
+
+        // Each wallet reports its own changeset
+        let other_stats = other.stats().unwrap();
+        assert_eq!(other_stats.namespace_count, 2);
+        assert!(other_stats.changeset_bytes < stats.changeset_bytes);
     }
-}
 
-impl AsyncWalletPersister for RedbStore {
-    type Error = RedbError;
+    #[tokio::test]
+    async fn test_persist_blocking() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("persist_blocking.redb");
+        let mut store = RedbStore::create(&db_path).unwrap();
 
-    /// Initialize the wallet persister asynchronously by loading the stored changeset
-    ///
-    /// This method is called by BDK when a wallet is being loaded asynchronously.
-    /// It retrieves the stored wallet changeset from the database or returns
-    /// an empty changeset if none exists.
-    ///
-    /// # Returns
-    ///
-    /// - A future that resolves to the stored wallet changeset, or an empty changeset if none exists
-    /// - An error if database access or deserialization fails
-    ///
-    fn initialize<'a>(persister: &'a mut Self) -> FutureResult<'a, ChangeSet, Self::Error>
-    where
-        Self: 'a,
-    {
-        Box::pin(async move {
-            // Get changeset or return empty if none exists
-            persister.get_changeset().map(|opt| opt.unwrap_or_default())
-        })
+        let changeset = large_changeset(3);
+        store.persist_blocking(&changeset).unwrap();
+
+        // Committed before returning, without awaiting anything
+        assert_eq!(store.get_changeset().unwrap().unwrap(), changeset);
+
+        // Mixes with the async path
+        AsyncWalletPersister::persist(&mut store, &large_changeset(4))
+            .await
+            .unwrap();
+        store.persist_blocking(&large_changeset(5)).unwrap();
+        let loaded = AsyncWalletPersister::initialize(&mut store).await.unwrap();
+        assert_eq!(loaded.tx_graph.txs.len(), 5);
     }
 
-    /// Persist a wallet changeset to the database asynchronously
-    ///
-    /// This method is called by BDK when wallet changes need to be saved asynchronously.
-    /// It merges the new changeset with any existing one and stores the result.
-    ///
-    /// # Arguments
-    ///
-    /// * `changeset` - The wallet changeset to persist
-    ///
-    /// # Returns
-    ///
-    /// - A future that resolves to `Ok(())` if the changeset was successfully stored
-    /// - An error if serialization or database access fails
-    fn persist<'a>(
-        persister: &'a mut Self,
-        changeset: &'a ChangeSet,
-    ) -> FutureResult<'a, (), Self::Error>
-    where
-        Self: 'a,
-    {
-        Box::pin(async move {
-            // Get existing changeset if any
-            let existing_changeset = persister.get_changeset()?;
+    #[cfg(feature = "tokio")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_spawn_maintenance() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("maintenance.redb");
+        let mut store = RedbStore::create(&db_path).unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(5_000)).unwrap();
+        store.clear().unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(2)).unwrap();
+        #[cfg(feature = "wal")]
+        store.append_log(&large_changeset(4)).unwrap();
+        let expected_txs = if cfg!(feature = "wal") { 4 } else { 2 };
+        let grown_size = store.file_size().unwrap();
+
+        // The application keeps its own reference and a clone, and goes on using both
+        let store = Arc::new(store);
+        let mut clone = (*store).clone();
+        let mut handle = Arc::clone(&store).spawn_maintenance(std::time::Duration::from_millis(10));
+        for _ in 0..500 {
+            if handle.cycles() > 0 {
+                break;
+            }
+            store.persist_shared(&ChangeSet::default()).unwrap();
+            WalletPersister::persist(&mut clone, &ChangeSet::default()).unwrap();
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(handle.cycles() > 0);
+        assert!(handle.errors().try_recv().is_err());
+        handle.abort();
 
-            // Merge with existing or use the new one
-            let final_changeset = match existing_changeset {
-                Some(mut existing) => {
-                    existing.merge(changeset.clone());
-                    existing
-                }
-                None => changeset.clone(),
-            };
+        // The file shrank, and any log was folded into the intact changeset
+        assert!(store.file_size().unwrap() < grown_size);
+        #[cfg(feature = "wal")]
+        {
+            let read_txn = store.db().unwrap().begin_read().unwrap();
+            assert!(store.read_log(&read_txn).unwrap().is_empty());
+        }
+        assert_eq!(
+            clone.get_changeset().unwrap().unwrap().tx_graph.txs.len(),
+            expected_txs
+        );
+        assert!(store.health_check(None).unwrap().is_healthy());
 
-            // Store the merged changeset
-            persister.store_changeset(&final_changeset)
-        })
+        // A handle taken out of the store keeps the database from compacting
+        let db = store.database().unwrap();
+        let mut handle = Arc::clone(&store).spawn_maintenance(std::time::Duration::from_millis(10));
+        let error = tokio::time::timeout(std::time::Duration::from_secs(5), handle.errors().recv())
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(error, RedbError::SharedDatabase));
+        handle.abort();
+        drop(db);
+
+        // Failed cycles are reported, not fatal
+        let mut read_only = clone;
+        read_only.read_only = true;
+        let mut handle =
+            Arc::new(read_only).spawn_maintenance(std::time::Duration::from_millis(10));
+        for _ in 0..2 {
+            let error =
+                tokio::time::timeout(std::time::Duration::from_secs(5), handle.errors().recv())
+                    .await
+                    .unwrap()
+                    .unwrap();
+            assert!(matches!(error, RedbError::ReadOnly));
+        }
+        handle.abort();
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use bdk_wallet::{CreateParams, KeychainKind, LoadParams, PersistedWallet};
-    use bitcoin::Network;
-    use futures::future::join_all;
-    use std::fs;
-    use std::fs::OpenOptions;
-    use std::sync::Arc;
-    use tempfile::tempdir;
-    use tokio::sync::Mutex;
+    #[cfg(feature = "tokio")]
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_maintenance_during_async_persist() {
+        let temp_dir = tempdir().unwrap();
+        let db_path = temp_dir.path().join("maintenance_persist.redb");
+        let mut store = RedbStore::create(&db_path).unwrap();
+        WalletPersister::persist(&mut store, &large_changeset(2_000)).unwrap();
+        store.clear().unwrap();
+        let grown_size = store.file_size().unwrap();
+
+        // The persist holds its write transaction open until maintenance has had a go
+        let (entered, entered_rx) = std::sync::mpsc::channel();
+        let mut writer = store.clone();
+        writer.before_commit = Some(Arc::new(move || {
+            let _ = entered.send(());
+            std::thread::sleep(std::time::Duration::from_millis(300));
+        }));
+        let persist = tokio::spawn(async move {
+            AsyncWalletPersister::persist(&mut writer, &large_changeset(3)).await
+        });
+        tokio::task::spawn_blocking(move || entered_rx.recv().unwrap())
+            .await
+            .unwrap();
 
-    // Example descriptor for testing
-    const TEST_DESCRIPTOR: &str = "wpkh(tprv8ZgxMBicQKsPdcAqYBpzAFwU5yxBUo88ggoBqu1qPcHUfSbKK1sKMLmC7EAk438btHQrSdu3jGGQa6PA71nvH5nkDexhLteJqkM4dQmWF9g/84'/1'/0'/0/*)";
-    const TEST_CHANGE_DESCRIPTOR: &str = "wpkh(tprv8ZgxMBicQKsPdcAqYBpzAFwU5yxBUo88ggoBqu1qPcHUfSbKK1sKMLmC7EAk438btHQrSdu3jGGQa6PA71nvH5nkDexhLteJqkM4dQmWF9g/84'/1'/0'/1/*)";
+        // The offloaded persist runs on a clone, which must not keep the cycle from compacting
+        let store = Arc::new(store);
+        let mut handle = Arc::clone(&store).spawn_maintenance(std::time::Duration::from_millis(10));
+        persist.await.unwrap().unwrap();
+        for _ in 0..500 {
+            if handle.cycles() > 0 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+        assert!(handle.cycles() > 0);
+        assert!(handle.errors().try_recv().is_err());
+        handle.abort();
+
+        assert!(store.file_size().unwrap() < grown_size);
+        assert_eq!(
+            store.get_changeset().unwrap().unwrap().tx_graph.txs.len(),
+            3
+        );
+    }
 
     #[test]
-    fn test_create_and_persist() {
+    fn test_describe() {
         let temp_dir = tempdir().unwrap();
-        let db_path = temp_dir.path().join("wallet.redb");
+        let db_path = temp_dir.path().join("describe.redb");
 
         let mut store = RedbStore::create(&db_path).unwrap();
+        assert_eq!(store.describe().unwrap(), WalletSummary::default());
 
-        // Create params with descriptors
         let create_params =
             CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
-
         let mut wallet = PersistedWallet::create(&mut store, create_params).unwrap();
+        let _ = wallet
+            .reveal_addresses_to(KeychainKind::External, 3)
+            .collect::<Vec<_>>();
+        wallet.reveal_next_address(KeychainKind::Internal);
+        wallet.persist(&mut store).unwrap();
 
-        // Make a change to the wallet - reveal an address which will create a change
-        let _address = wallet.reveal_next_address(KeychainKind::External);
-
-        // Now persist should return true because we've made changes
-        let persisted = wallet.persist(&mut store).unwrap();
-        assert!(persisted);
-
-        // Check that we can load the wallet back
-        let load_params = LoadParams::default();
-        let loaded_wallet = PersistedWallet::load(&mut store, load_params).unwrap();
-        assert!(loaded_wallet.is_some());
+        let summary = store.describe().unwrap();
+        assert_eq!(summary.network, Some(Network::Testnet));
+        assert_eq!(
+            summary.descriptor,
+            Some(wallet.public_descriptor(KeychainKind::External).to_string())
+        );
+        assert_eq!(
+            summary.change_descriptor,
+            Some(wallet.public_descriptor(KeychainKind::Internal).to_string())
+        );
+        assert_eq!(summary.last_revealed.get(&KeychainKind::External), Some(&3));
+        assert_eq!(summary.last_revealed.get(&KeychainKind::Internal), Some(&0));
+        assert_eq!(summary.tx_count, 0);
+        assert_eq!(summary.tip_height, Some(0));
+        assert!(summary.to_string().contains("External index:    3"));
+
+        // Transactions are counted without being parsed
+        WalletPersister::persist(&mut store, &large_changeset(5)).unwrap();
+        assert_eq!(store.describe().unwrap().tx_count, 5);
     }
 
     #[test]
-    fn test_empty_store() {
+    fn test_initialize_filtered() {
         let temp_dir = tempdir().unwrap();
-        let db_path = temp_dir.path().join("empty.redb");
+        let db_path = temp_dir.path().join("filtered.redb");
 
-        // Create an empty store
         let mut store = RedbStore::create(&db_path).unwrap();
+        let create_params =
+            CreateParams::new(TEST_DESCRIPTOR, TEST_CHANGE_DESCRIPTOR).network(Network::Testnet);
+        let mut wallet = PersistedWallet::create(&mut store, create_params).unwrap();
+        let _ = wallet
+            .reveal_addresses_to(KeychainKind::External, 2)
+            .collect::<Vec<_>>();
+        let _ = wallet
+            .reveal_addresses_to(KeychainKind::Internal, 5)
+            .collect::<Vec<_>>();
+        wallet.persist(&mut store).unwrap();
+        let external = wallet
+            .public_descriptor(KeychainKind::External)
+            .descriptor_id();
+        let internal = wallet
+            .public_descriptor(KeychainKind::Internal)
+            .descriptor_id();
+        let full_descriptors = {
+            let full = WalletPersister::initialize(&mut store).unwrap();
+            (full.descriptor, full.change_descriptor)
+        };
 
-        // Initialize should return an empty changeset
-        let changeset = WalletPersister::initialize(&mut store).unwrap();
-        assert!(changeset.is_empty());
-    }
-
-    #[test]
-    fn test_open_nonexistent_file() {
-        let temp_dir = tempdir().unwrap();
-        let db_path = temp_dir.path().join("nonexistent.redb");
+        let changeset = store
+            .initialize_filtered(&[KeychainKind::External])
+            .unwrap();
+        assert_eq!(changeset.indexer.last_revealed.get(&external), Some(&2));
+        assert_eq!(changeset.indexer.last_revealed.get(&internal), None);
+        assert_eq!(changeset.descriptor, full_descriptors.0);
+        assert_eq!(changeset.change_descriptor, full_descriptors.1);
+        assert_eq!(changeset.network, Some(Network::Testnet));
+
+        // The result still loads into a wallet expecting both keychains
+        let loaded = bdk_wallet::Wallet::load()
+            .descriptor(KeychainKind::External, Some(TEST_DESCRIPTOR))
+            .descriptor(KeychainKind::Internal, Some(TEST_CHANGE_DESCRIPTOR))
+            .extract_keys()
+            .load_wallet_no_persist(changeset.clone())
+            .unwrap()
+            .unwrap();
+        assert_eq!(loaded.derivation_index(KeychainKind::External), Some(2));
+        assert_eq!(loaded.derivation_index(KeychainKind::Internal), None);
 
-        // Attempt to open a non-existent database file
-        let result = RedbStore::open(&db_path);
-        assert!(result.is_err());
+        // Everything but the keychains matches a full load
+        let full = WalletPersister::initialize(&mut store).unwrap();
+        assert_eq!(changeset.tx_graph, full.tx_graph);
+        assert_eq!(changeset.local_chain, full.local_chain);
+        assert_eq!(
+            store
+                .initialize_filtered(&[KeychainKind::External, KeychainKind::Internal])
+                .unwrap(),
+            full
+        );
+        assert!(store
+            .initialize_filtered(&[])
+            .unwrap()
+            .indexer
+            .last_revealed
+            .is_empty());
     }
 
     #[test]
-    fn test_open_or_create() {
+    fn test_reopen() {
         let temp_dir = tempdir().unwrap();
-        let db_path = temp_dir.path().join("open_or_create.redb");
+        let db_path = temp_dir.path().join("reopen.redb");
+        let changeset = large_changeset(3);
 
-        // File doesn't exist, should create it
-        let store = RedbStore::open_or_create(&db_path).unwrap();
+        // Reads keep being counted after a reopen
+        let mut store = RedbStore::builder()
+            .track_reads(true)
+            .open_or_create(&db_path)
+            .unwrap();
+        WalletPersister::persist(&mut store, &changeset).unwrap();
+        assert!(store.reopen().unwrap());
+        let before = store.cache_stats().unwrap().physical_reads.unwrap();
+        assert_eq!(store.load_changeset().unwrap(), Some(changeset.clone()));
+        assert!(store.cache_stats().unwrap().physical_reads.unwrap() > before);
+
+        // A file that can't be opened leaves the store as it was
+        let moved_path = temp_dir.path().join("moved.redb");
+        fs::rename(&db_path, &moved_path).unwrap();
+        assert!(store.reopen().is_err());
+        assert_eq!(store.load_changeset().unwrap(), Some(changeset.clone()));
+        WalletPersister::persist(&mut store, &large_changeset(4)).unwrap();
         drop(store);
 
-        // File now exists, should open it
-        let store = RedbStore::open_or_create(&db_path).unwrap();
+        // Settings from a caller's builder can't be reproduced
+        let mut store = RedbStore::open_with_config(&moved_path, redb::Builder::new()).unwrap();
+        assert!(!store.reopen().unwrap());
+        assert_eq!(store.load_changeset().unwrap(), Some(large_changeset(4)));
+
         drop(store);
 
-        // Verify the file exists
-        assert!(db_path.exists());
+        // A database that can't be opened again leaves the store closed
+        let mut store = RedbStore::open(&moved_path).unwrap();
+        let holder = Arc::new(std::sync::Mutex::new(None));
+        store.before_reopen = Some({
+            let (holder, path) = (Arc::clone(&holder), moved_path.clone());
+            Arc::new(move || *holder.lock().unwrap() = Some(Database::open(&path).unwrap()))
+        });
+        assert!(store.reopen().is_err());
+        assert!(matches!(
+            WalletPersister::persist(&mut store, &changeset),
+            Err(RedbError::Closed)
+        ));
+        assert!(matches!(
+            WalletPersister::initialize(&mut store),
+            Err(RedbError::Closed)
+        ));
+        assert!(matches!(store.database(), Err(RedbError::Closed)));
+
+        // Reopening a closed store brings it back
+        store.before_reopen = None;
+        drop(holder.lock().unwrap().take());
+        assert!(store.reopen().unwrap());
+        assert_eq!(store.load_changeset().unwrap(), Some(large_changeset(4)));
     }
 
     #[test]
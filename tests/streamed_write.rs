@@ -0,0 +1,121 @@
+// Peak memory of a persist, measured with a counting allocator. The allocator is global, so
+// this test has a binary of its own instead of living with the library's unit tests.
+
+use std::sync::Arc;
+
+use bdk_chain::Merge;
+use bdk_wallet::{ChangeSet, WalletPersister};
+use bitcoin::hashes::Hash;
+use bitcoin::{absolute, transaction, Amount, OutPoint, ScriptBuf, Transaction, TxIn, TxOut, Txid};
+use redb::TableDefinition;
+use redb_wallet_storage::{codec, RedbStore};
+
+/// Counts the bytes allocated on each thread, so the measurement doesn't see other
+/// threads' allocations
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATED: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+    static PEAK_ALLOCATED: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+fn track_allocation(added: usize, removed: usize) {
+    let _ = ALLOCATED.try_with(|allocated| {
+        let now = (allocated.get() + added).saturating_sub(removed);
+        allocated.set(now);
+        let _ = PEAK_ALLOCATED.try_with(|peak| peak.set(peak.get().max(now)));
+    });
+}
+
+unsafe impl std::alloc::GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: std::alloc::Layout) -> *mut u8 {
+        track_allocation(layout.size(), 0);
+        std::alloc::System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: std::alloc::Layout) {
+        track_allocation(0, layout.size());
+        std::alloc::System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: std::alloc::Layout, new_size: usize) -> *mut u8 {
+        track_allocation(new_size, layout.size());
+        std::alloc::System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Run `f`, returning its result and the most memory it held at once on this thread
+fn peak_allocation<T>(f: impl FnOnce() -> T) -> (T, usize) {
+    let base = ALLOCATED.with(|allocated| allocated.get());
+    PEAK_ALLOCATED.with(|peak| peak.set(base));
+    let result = f();
+    (result, PEAK_ALLOCATED.with(|peak| peak.get()) - base)
+}
+
+/// A changeset holding one transaction for each lock time in `lock_times`
+fn transactions(lock_times: std::ops::Range<u32>) -> ChangeSet {
+    let mut changeset = ChangeSet::default();
+    for i in lock_times {
+        let tx = Transaction {
+            version: transaction::Version::TWO,
+            lock_time: absolute::LockTime::from_consensus(i),
+            input: vec![TxIn {
+                previous_output: OutPoint::new(Txid::all_zeros(), i),
+                ..Default::default()
+            }],
+            output: vec![TxOut {
+                value: Amount::from_sat(u64::from(i) + 1),
+                script_pubkey: ScriptBuf::new(),
+            }],
+        };
+        changeset.tx_graph.txs.insert(Arc::new(tx));
+    }
+    changeset
+}
+
+#[test]
+fn test_persist_streams_into_the_database() {
+    let temp_dir = tempfile::tempdir().unwrap();
+    let open_wallet = |name: &str| {
+        let mut store = RedbStore::create(temp_dir.path().join(name)).unwrap();
+        WalletPersister::persist(&mut store, &transactions(0..20_000)).unwrap();
+        store
+    };
+    let update = transactions(20_000..20_001);
+
+    // The same merge committed the buffered way: serialized into a vector, then inserted
+    // over the transaction graph, the segment a persist of a new transaction rewrites
+    let buffered = open_wallet("buffered.redb");
+    let (merged_len, buffered_peak) = peak_allocation(|| {
+        let write_txn = buffered.database().unwrap().begin_write().unwrap();
+        let bytes = {
+            let mut table = write_txn
+                .open_table(TableDefinition::<&str, &[u8]>::new("wallet_data"))
+                .unwrap();
+            let mut merged = buffered.load_changeset().unwrap().unwrap();
+            merged.merge(update.clone());
+            let bytes = codec::encode_changeset(&merged, false).unwrap();
+            table
+                .insert("wallet_changeset::tx_graph", bytes.as_slice())
+                .unwrap();
+            bytes
+        };
+        write_txn.commit().unwrap();
+        bytes.len()
+    });
+
+    let mut store = open_wallet("streamed.redb");
+    let (result, streamed_peak) = peak_allocation(|| WalletPersister::persist(&mut store, &update));
+    result.unwrap();
+    assert!(
+        streamed_peak < buffered_peak,
+        "persist peaked at {streamed_peak} bytes, the buffered write at {buffered_peak}"
+    );
+
+    let stored = store.load_changeset().unwrap().unwrap();
+    assert_eq!(stored, transactions(0..20_001));
+    assert_eq!(store.serialized_size(&stored).unwrap(), merged_len);
+}